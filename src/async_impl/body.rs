@@ -5,36 +5,90 @@ use std::task::{Context, Poll};
 
 use bytes::Bytes;
 use futures_core::Stream;
+use http::HeaderValue;
 use http_body::Body as HttpBody;
 use tokio::time::Delay;
 
+use crate::error::BoxError;
+use crate::TimeoutKind;
+
 /// An asynchronous request body.
 pub struct Body {
     inner: Inner,
+    content_type: Option<HeaderValue>,
+    /// The `inspect_cloneable` callbacks of a reusable body. A streaming
+    /// body runs them in its stream instead.
+    inspectors: Vec<Box<dyn InspectChunk>>,
 }
 
 // The `Stream` trait isn't stable, so the impl isn't public.
 pub(crate) struct ImplStream(Body);
 
+type BoxBody = Pin<Box<dyn HttpBody<Data = Bytes, Error = BoxError> + Send + Sync>>;
+
 enum Inner {
     Reusable(Bytes),
     Streaming {
-        body: Pin<
-            Box<
-                dyn HttpBody<Data = Bytes, Error = Box<dyn std::error::Error + Send + Sync>>
-                    + Send
-                    + Sync,
-            >,
-        >,
+        body: BoxBody,
         timeout: Option<Delay>,
     },
 }
 
 struct WrapStream<S>(S);
 
-struct WrapHyper(hyper::Body);
+struct Inspect<F> {
+    body: BoxBody,
+    f: F,
+}
+
+trait InspectChunk: Send + Sync {
+    fn call(&mut self, chunk: &[u8]);
+
+    fn clone_box(&self) -> Box<dyn InspectChunk>;
+}
+
+struct MapErr<F> {
+    body: BoxBody,
+    f: F,
+}
+
+struct WrapHyper<B>(B);
+
+#[cfg(all(feature = "json", feature = "stream"))]
+struct JsonArray<S> {
+    items: Pin<Box<S>>,
+    opened: bool,
+    first: bool,
+    done: bool,
+}
 
 impl Body {
+    /// Create an empty `Body`.
+    ///
+    /// Unlike sending a request without a body, this states explicitly
+    /// that the body has no content, so methods like `POST` are sent with
+    /// `Content-Length: 0`.
+    ///
+    /// ```
+    /// # use reqwest::Body;
+    /// let body = Body::empty();
+    /// assert_eq!(body.is_empty(), Some(true));
+    /// ```
+    pub fn empty() -> Body {
+        Body::reusable(Bytes::new())
+    }
+
+    /// Returns whether this `Body` has no content.
+    ///
+    /// `None` is returned if the underlying data is a stream, since its
+    /// length isn't known until it has been read.
+    pub fn is_empty(&self) -> Option<bool> {
+        match &self.inner {
+            Inner::Reusable(bytes) => Some(bytes.is_empty()),
+            Inner::Streaming { .. } => None,
+        }
+    }
+
     /// Returns a reference to the internal data of the `Body`.
     ///
     /// `None` is returned, if the underlying data is a stream.
@@ -45,6 +99,136 @@ impl Body {
         }
     }
 
+    /// Attach a `Content-Type` to this `Body`.
+    ///
+    /// When the body is sent, the value is used as the request's
+    /// `Content-Type` header, unless the request already sets one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use reqwest::Body;
+    /// # use reqwest::header::HeaderValue;
+    /// let body = Body::from("{}")
+    ///     .with_content_type(HeaderValue::from_static("application/json"));
+    /// ```
+    pub fn with_content_type(mut self, content_type: HeaderValue) -> Body {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Returns the `Content-Type` attached to this `Body`, if any.
+    pub fn content_type(&self) -> Option<&HeaderValue> {
+        self.content_type.as_ref()
+    }
+
+    /// Call `f` with each chunk of this `Body` as it is streamed.
+    ///
+    /// The bytes sent are unchanged, which makes this useful for upload
+    /// progress, checksums or logging.
+    ///
+    /// The returned `Body` is always streaming, so a request using it can
+    /// not be cloned or replayed on redirects. Use
+    /// [`inspect_cloneable`](Body::inspect_cloneable) to keep that ability.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use reqwest::Body;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let sent = Arc::new(AtomicUsize::new(0));
+    /// let counter = sent.clone();
+    /// let body = Body::from("hello world").inspect(move |chunk| {
+    ///     counter.fetch_add(chunk.len(), Ordering::Relaxed);
+    /// });
+    /// ```
+    pub fn inspect<F>(mut self, f: F) -> Body
+    where
+        F: FnMut(&[u8]) + Send + Sync + 'static,
+    {
+        let content_type = self.content_type.take();
+        let (body, timeout) = self.into_streaming();
+        Body {
+            inner: Inner::Streaming {
+                body: Box::pin(Inspect { body, f }),
+                timeout,
+            },
+            content_type,
+            inspectors: Vec::new(),
+        }
+    }
+
+    /// Call `f` with each chunk of this `Body` as it is streamed, keeping a
+    /// body that isn't a stream reusable.
+    ///
+    /// This is the same as [`inspect`](Body::inspect), except a request
+    /// using a body created from bytes can still be cloned and replayed on
+    /// redirects. Each copy of the body gets a clone of `f`, which is
+    /// called every time that copy is sent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use reqwest::Body;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let sent = Arc::new(AtomicUsize::new(0));
+    /// let counter = sent.clone();
+    /// let body = Body::from("hello world").inspect_cloneable(move |chunk| {
+    ///     counter.fetch_add(chunk.len(), Ordering::Relaxed);
+    /// });
+    /// assert_eq!(body.as_bytes(), Some(&b"hello world"[..]));
+    /// ```
+    pub fn inspect_cloneable<F>(mut self, f: F) -> Body
+    where
+        F: FnMut(&[u8]) + Clone + Send + Sync + 'static,
+    {
+        match self.inner {
+            Inner::Reusable(_) => {
+                self.inspectors.push(Box::new(f));
+                self
+            }
+            Inner::Streaming { .. } => self.inspect(f),
+        }
+    }
+
+    /// Map the errors of this `Body` with `f`.
+    ///
+    /// This is useful to add context to the errors of a wrapped stream, such
+    /// as the path of the file being uploaded. The mapped error becomes the
+    /// source of the `reqwest::Error` a request fails with.
+    ///
+    /// The returned `Body` is always streaming, so a request using it can
+    /// not be cloned or replayed on redirects.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use reqwest::Body;
+    /// let body = Body::from("hello world").map_err(|err| {
+    ///     format!("reading upload.txt: {}", err)
+    /// });
+    /// ```
+    pub fn map_err<F, E>(mut self, f: F) -> Body
+    where
+        F: FnMut(Box<dyn std::error::Error + Send + Sync>) -> E + Send + Sync + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let content_type = self.content_type.take();
+        let (body, timeout) = self.into_streaming();
+        Body {
+            inner: Inner::Streaming {
+                body: Box::pin(MapErr { body, f }),
+                timeout,
+            },
+            content_type,
+            inspectors: Vec::new(),
+        }
+    }
+
     /// Wrap a futures `Stream` in a box inside `Body`.
     ///
     /// # Example
@@ -78,6 +262,49 @@ impl Body {
         Body::stream(stream)
     }
 
+    /// Serialize a `Stream` of items as a JSON array.
+    ///
+    /// Each item is serialized as it's polled, so the whole array is never
+    /// held in memory. The body sets a `Content-Type: application/json`
+    /// header, unless the request has its own.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use reqwest::Body;
+    /// # use futures_util;
+    /// # fn main() {
+    /// let records = futures_util::stream::iter(vec![1, 2, 3]);
+    ///
+    /// let body = Body::from_json_stream(records);
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `json` and `stream` features to be enabled.
+    #[cfg(all(feature = "json", feature = "stream"))]
+    pub fn from_json_stream<S, T>(items: S) -> Body
+    where
+        S: Stream<Item = T> + Send + Sync + 'static,
+        T: serde::Serialize,
+    {
+        let body = Box::pin(JsonArray {
+            items: Box::pin(items),
+            opened: false,
+            first: true,
+            done: false,
+        });
+        Body {
+            inner: Inner::Streaming {
+                body,
+                timeout: None,
+            },
+            content_type: Some(HeaderValue::from_static("application/json")),
+            inspectors: Vec::new(),
+        }
+    }
+
     pub(crate) fn stream<S>(stream: S) -> Body
     where
         S: futures_core::stream::TryStream + Send + Sync + 'static,
@@ -94,15 +321,23 @@ impl Body {
                 body,
                 timeout: None,
             },
+            content_type: None,
+            inspectors: Vec::new(),
         }
     }
 
-    pub(crate) fn response(body: hyper::Body, timeout: Option<Delay>) -> Body {
+    pub(crate) fn response<B>(body: B, timeout: Option<Delay>) -> Body
+    where
+        B: HttpBody<Data = Bytes> + Send + Sync + Unpin + 'static,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
         Body {
             inner: Inner::Streaming {
                 body: Box::pin(WrapHyper(body)),
                 timeout,
             },
+            content_type: None,
+            inspectors: Vec::new(),
         }
     }
 
@@ -113,35 +348,35 @@ impl Body {
                 body: Box::pin(WrapHyper(body)),
                 timeout: None,
             },
+            content_type: None,
+            inspectors: Vec::new(),
         }
     }
 
-    pub(crate) fn empty() -> Body {
-        Body::reusable(Bytes::new())
-    }
-
     pub(crate) fn reusable(chunk: Bytes) -> Body {
         Body {
             inner: Inner::Reusable(chunk),
+            content_type: None,
+            inspectors: Vec::new(),
         }
     }
 
-    pub(crate) fn try_reuse(self) -> (Option<Bytes>, Self) {
-        let reuse = match self.inner {
-            Inner::Reusable(ref chunk) => Some(chunk.clone()),
-            Inner::Streaming { .. } => None,
-        };
-
-        (reuse, self)
-    }
-
     pub(crate) fn try_clone(&self) -> Option<Body> {
         match self.inner {
-            Inner::Reusable(ref chunk) => Some(Body::reusable(chunk.clone())),
+            Inner::Reusable(ref chunk) => Some(Body {
+                inner: Inner::Reusable(chunk.clone()),
+                content_type: self.content_type.clone(),
+                inspectors: self.inspectors.iter().map(|f| f.clone_box()).collect(),
+            }),
             Inner::Streaming { .. } => None,
         }
     }
 
+    /// Whether this `Body` has `inspect_cloneable` callbacks to run.
+    pub(crate) fn is_inspected(&self) -> bool {
+        !self.inspectors.is_empty()
+    }
+
     pub(crate) fn into_stream(self) -> ImplStream {
         ImplStream(self)
     }
@@ -152,6 +387,30 @@ impl Body {
             Inner::Streaming { ref body, .. } => body.size_hint().exact(),
         }
     }
+
+    fn into_streaming(self) -> (BoxBody, Option<Delay>) {
+        let (body, timeout) = self.inner.into_streaming();
+        if self.inspectors.is_empty() {
+            return (body, timeout);
+        }
+
+        let mut inspectors = self.inspectors;
+        let f = move |chunk: &[u8]| {
+            for f in &mut inspectors {
+                f.call(chunk);
+            }
+        };
+        (Box::pin(Inspect { body, f }), timeout)
+    }
+}
+
+impl Inner {
+    fn into_streaming(self) -> (BoxBody, Option<Delay>) {
+        match self {
+            Inner::Reusable(bytes) => (Box::pin(WrapHyper(hyper::Body::from(bytes))), None),
+            Inner::Streaming { body, timeout } => (body, timeout),
+        }
+    }
 }
 
 impl From<Bytes> for Body {
@@ -212,7 +471,8 @@ impl HttpBody for ImplStream {
             } => {
                 if let Some(ref mut timeout) = timeout {
                     if let Poll::Ready(()) = Pin::new(timeout).poll(cx) {
-                        return Poll::Ready(Some(Err(crate::error::body(crate::error::TimedOut))));
+                        let err = crate::error::TimedOut(TimeoutKind::Body);
+                        return Poll::Ready(Some(Err(crate::error::body(err))));
                     }
                 }
                 futures_core::ready!(Pin::new(body).poll_data(cx))
@@ -227,14 +487,26 @@ impl HttpBody for ImplStream {
             }
         };
 
+        // Only a reusable body has inspectors of its own.
+        if let Some(Ok(ref chunk)) = opt_try_chunk {
+            for f in &mut self.0.inspectors {
+                f.call(chunk);
+            }
+        }
+
         Poll::Ready(opt_try_chunk)
     }
 
     fn poll_trailers(
-        self: Pin<&mut Self>,
-        _cx: &mut Context,
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
     ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
-        Poll::Ready(Ok(None))
+        match self.0.inner {
+            Inner::Streaming { ref mut body, .. } => {
+                Pin::new(body).poll_trailers(cx).map_err(crate::error::body)
+            }
+            Inner::Reusable(_) => Poll::Ready(Ok(None)),
+        }
     }
 
     fn is_end_stream(&self) -> bool {
@@ -296,9 +568,155 @@ where
     }
 }
 
+// ===== impl JsonArray =====
+
+#[cfg(all(feature = "json", feature = "stream"))]
+impl<S, T> HttpBody for JsonArray<S>
+where
+    S: Stream<Item = T>,
+    T: serde::Serialize,
+{
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        if !self.opened {
+            self.opened = true;
+            return Poll::Ready(Some(Ok(Bytes::from_static(b"["))));
+        }
+
+        match futures_core::ready!(self.items.as_mut().poll_next(cx)) {
+            Some(item) => {
+                let mut buf = Vec::new();
+                if !self.first {
+                    buf.push(b',');
+                }
+                self.first = false;
+                serde_json::to_writer(&mut buf, &item)?;
+                Poll::Ready(Some(Ok(buf.into())))
+            }
+            None => {
+                self.done = true;
+                Poll::Ready(Some(Ok(Bytes::from_static(b"]"))))
+            }
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+// ===== impl Inspect =====
+
+// `f` is never pinned, and the body is already boxed.
+impl<F> Unpin for Inspect<F> {}
+
+impl<F> HttpBody for Inspect<F>
+where
+    F: FnMut(&[u8]),
+{
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let item = futures_core::ready!(self.body.as_mut().poll_data(cx));
+        if let Some(Ok(ref chunk)) = item {
+            (self.f)(chunk);
+        }
+        Poll::Ready(item)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        self.body.as_mut().poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
+
+impl<F> InspectChunk for F
+where
+    F: FnMut(&[u8]) + Clone + Send + Sync + 'static,
+{
+    fn call(&mut self, chunk: &[u8]) {
+        self(chunk)
+    }
+
+    fn clone_box(&self) -> Box<dyn InspectChunk> {
+        Box::new(self.clone())
+    }
+}
+
+// ===== impl MapErr =====
+
+// `f` is never pinned, and the body is already boxed.
+impl<F> Unpin for MapErr<F> {}
+
+impl<F, E> HttpBody for MapErr<F>
+where
+    F: FnMut(Box<dyn std::error::Error + Send + Sync>) -> E,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Data = Bytes;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let item = futures_core::ready!(self.body.as_mut().poll_data(cx));
+        Poll::Ready(item.map(|res| res.map_err(|err| (self.f)(err).into())))
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        let res = futures_core::ready!(self.body.as_mut().poll_trailers(cx));
+        Poll::Ready(res.map_err(|err| (self.f)(err).into()))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
+
 // ===== impl WrapHyper =====
 
-impl HttpBody for WrapHyper {
+impl<B> HttpBody for WrapHyper<B>
+where
+    B: HttpBody<Data = Bytes> + Unpin,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
     type Data = Bytes;
     type Error = Box<dyn std::error::Error + Send + Sync>;
 
@@ -313,10 +731,10 @@ impl HttpBody for WrapHyper {
     }
 
     fn poll_trailers(
-        self: Pin<&mut Self>,
-        _cx: &mut Context,
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
     ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
-        Poll::Ready(Ok(None))
+        Pin::new(&mut self.0).poll_trailers(cx).map_err(Into::into)
     }
 
     fn is_end_stream(&self) -> bool {
@@ -338,4 +756,73 @@ mod tests {
         let body = Body::from(&test_data[..]);
         assert_eq!(body.as_bytes(), Some(&test_data[..]));
     }
+
+    #[test]
+    fn test_is_empty() {
+        assert_eq!(Body::empty().is_empty(), Some(true));
+        assert_eq!(Body::from("hello").is_empty(), Some(false));
+
+        let streaming = Body::response(hyper::Body::empty(), None);
+        assert_eq!(streaming.is_empty(), None);
+    }
+
+    #[cfg(all(feature = "json", feature = "stream"))]
+    #[tokio::test]
+    async fn test_from_json_stream() {
+        let items = futures_util::stream::iter(vec![
+            serde_json::json!({"id": 1}),
+            serde_json::json!({"id": 2}),
+            serde_json::json!({"id": 3}),
+        ]);
+        let body = Body::from_json_stream(items);
+        assert_eq!(body.content_type().unwrap(), "application/json");
+
+        let bytes = hyper::body::to_bytes(body.into_stream()).await.unwrap();
+        assert_eq!(bytes, r#"[{"id":1},{"id":2},{"id":3}]"#);
+
+        let empty = Body::from_json_stream(futures_util::stream::iter(Vec::<u8>::new()));
+        let bytes = hyper::body::to_bytes(empty.into_stream()).await.unwrap();
+        assert_eq!(bytes, "[]");
+    }
+
+    #[tokio::test]
+    async fn test_inspect_cloneable() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let inspected = seen.clone();
+        let body = Body::from("hello").inspect_cloneable(move |chunk| {
+            inspected.lock().unwrap().extend_from_slice(chunk);
+        });
+        assert_eq!(body.as_bytes(), Some(&b"hello"[..]));
+
+        let clone = body.try_clone().expect("bytes body is cloneable");
+        let bytes = hyper::body::to_bytes(body.into_stream()).await.unwrap();
+        assert_eq!(bytes, "hello");
+        assert_eq!(&seen.lock().unwrap()[..], b"hello");
+
+        let bytes = hyper::body::to_bytes(clone.map_err(|err| err).into_stream())
+            .await
+            .unwrap();
+        assert_eq!(bytes, "hello");
+        assert_eq!(&seen.lock().unwrap()[..], b"hellohello");
+    }
+
+    #[tokio::test]
+    async fn test_map_err() {
+        use std::error::Error as _;
+        use std::io;
+
+        let chunks: Vec<Result<&'static str, io::Error>> = vec![
+            Ok("hello"),
+            Err(io::Error::new(io::ErrorKind::UnexpectedEof, "disk gone")),
+        ];
+        let body = Body::stream(futures_util::stream::iter(chunks))
+            .map_err(|err| format!("reading upload.txt: {}", err));
+
+        let err = hyper::body::to_bytes(body.into_stream()).await.unwrap_err();
+        assert!(err.is_body());
+        let source = err.source().unwrap().to_string();
+        assert_eq!(source, "reading upload.txt: disk gone");
+    }
 }
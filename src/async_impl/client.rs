@@ -3,46 +3,58 @@
     feature = "rustls-tls",
 ))]
 use std::any::Any;
-use std::convert::TryInto;
-use std::net::IpAddr;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
 #[cfg(feature = "cookies")]
 use std::sync::RwLock;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::{fmt, str};
 
-use bytes::Bytes;
+use futures_core::Stream;
+use futures_util::stream::StreamExt;
 use http::header::{
-    Entry, HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH,
-    CONTENT_TYPE, LOCATION, PROXY_AUTHORIZATION, RANGE, REFERER, TRANSFER_ENCODING, USER_AGENT,
+    Entry, HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, AUTHORIZATION,
+    CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION, PROXY_AUTHORIZATION, RANGE,
+    REFERER, TRANSFER_ENCODING, USER_AGENT,
 };
 use http::uri::Scheme;
-use http::Uri;
+use http::{Uri, Version};
+use http_body::Body as HttpBody;
 use hyper::client::ResponseFuture;
+use hyper::service::Service;
 #[cfg(feature = "native-tls-crate")]
 use native_tls_crate::TlsConnector;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::time::Delay;
 
 use log::debug;
 
-use super::decoder::Accepts;
+use super::decoder::{Accepts, ContentEncoding};
 use super::request::{Request, RequestBuilder};
 use super::response::Response;
+use super::resume::Resume;
+use super::upgrade::{PendingUpgrade, Upgraded};
 use super::Body;
-use crate::connect::{Connector, HttpConnector};
+use crate::connect::{self, ConnReuse, Connector, HttpConnector, RetireConn};
 #[cfg(feature = "cookies")]
 use crate::cookie;
 use crate::error;
 use crate::into_url::{expect_uri, try_uri};
 use crate::redirect::{self, remove_sensitive_headers};
+use crate::retry;
 #[cfg(feature = "__tls")]
 use crate::tls::TlsBackend;
 #[cfg(feature = "__tls")]
 use crate::{Certificate, Identity};
-use crate::{IntoUrl, Method, Proxy, StatusCode, Url};
+use crate::{IntoUrl, Method, Proxy, StatusCode, TimeoutKind, Url};
+
+const DEFAULT_USER_AGENT: &str = concat!("reqwest/", env!("CARGO_PKG_VERSION"));
 
 /// An asynchronous `Client` to make Requests with.
 ///
@@ -56,6 +68,10 @@ use crate::{IntoUrl, Method, Proxy, StatusCode, Url};
 /// You do **not** have to wrap the `Client` it in an [`Rc`] or [`Arc`] to **reuse** it,
 /// because it already uses an [`Arc`] internally.
 ///
+/// The `Client` also implements `tower::Service<Request>`, so it can be
+/// wrapped with `tower` middleware. For stacks built around the `http`
+/// types, see [`Client::http_service`].
+///
 /// [`Rc`]: std::rc::Rc
 #[derive(Clone)]
 pub struct Client {
@@ -71,34 +87,60 @@ struct Config {
     // NOTE: When adding a new field, update `fmt::Debug for ClientBuilder`
     accepts: Accepts,
     headers: HeaderMap,
-    #[cfg(feature = "native-tls")]
+    merged_headers: HeaderMap,
+    default_user_agent: bool,
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
     hostname_verification: bool,
     #[cfg(feature = "__tls")]
     certs_verification: bool,
+    #[cfg(feature = "rustls-tls")]
+    invalid_certs_hosts: Vec<String>,
     connect_timeout: Option<Duration>,
     connection_verbose: bool,
     pool_idle_timeout: Option<Duration>,
     pool_max_idle_per_host: usize,
+    preserve_header_order: bool,
+    concurrency_limit_per_host: Option<usize>,
+    connection_reuse_max_requests: Option<usize>,
     #[cfg(feature = "__tls")]
     identity: Option<Identity>,
     proxies: Vec<Proxy>,
     auto_sys_proxy: bool,
+    system_proxy: Option<bool>,
     redirect_policy: redirect::Policy,
+    retry_policy: retry::Policy,
     referer: bool,
+    referer_trim_query: bool,
     timeout: Option<Duration>,
+    max_response_size: Option<u64>,
+    resume_downloads: bool,
     #[cfg(feature = "__tls")]
     root_certs: Vec<Certificate>,
     #[cfg(feature = "__tls")]
     tls: TlsBackend,
+    #[cfg(feature = "__tls")]
+    tls_selected: Vec<&'static str>,
+    #[cfg(feature = "rustls-tls")]
+    tls_session_cache: Option<usize>,
+    #[cfg(feature = "rustls-tls")]
+    tls_early_data: bool,
     http2_only: bool,
+    http2_tls_only: bool,
     http1_title_case_headers: bool,
+    http1_writev: Option<bool>,
     http2_initial_stream_window_size: Option<u32>,
     http2_initial_connection_window_size: Option<u32>,
-    local_address: Option<IpAddr>,
+    local_address_ipv4: Option<Ipv4Addr>,
+    local_address_ipv6: Option<Ipv6Addr>,
     nodelay: bool,
+    tcp_user_timeout: Option<Duration>,
+    #[cfg(feature = "cookies")]
+    cookie_store: Option<cookie::CookieStoreHandle>,
     #[cfg(feature = "cookies")]
-    cookie_store: Option<cookie::CookieStore>,
+    cookie_store_capacity: Option<usize>,
     trust_dns: bool,
+    dns_overrides: HashMap<String, IpAddr>,
+    hosts_files: Vec<PathBuf>,
     error: Option<crate::Error>,
 }
 
@@ -121,34 +163,60 @@ impl ClientBuilder {
                 error: None,
                 accepts: Accepts::default(),
                 headers,
-                #[cfg(feature = "native-tls")]
+                merged_headers: HeaderMap::new(),
+                default_user_agent: false,
+                #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
                 hostname_verification: true,
                 #[cfg(feature = "__tls")]
                 certs_verification: true,
+                #[cfg(feature = "rustls-tls")]
+                invalid_certs_hosts: Vec::new(),
                 connect_timeout: None,
                 connection_verbose: false,
                 pool_idle_timeout: Some(Duration::from_secs(90)),
                 pool_max_idle_per_host: std::usize::MAX,
+                preserve_header_order: false,
+                concurrency_limit_per_host: None,
+                connection_reuse_max_requests: None,
                 proxies: Vec::new(),
                 auto_sys_proxy: true,
+                system_proxy: None,
                 redirect_policy: redirect::Policy::default(),
+                retry_policy: retry::Policy::default(),
                 referer: true,
+                referer_trim_query: false,
                 timeout: None,
+                max_response_size: None,
+                resume_downloads: false,
                 #[cfg(feature = "__tls")]
                 root_certs: Vec::new(),
                 #[cfg(feature = "__tls")]
                 identity: None,
                 #[cfg(feature = "__tls")]
                 tls: TlsBackend::default(),
+                #[cfg(feature = "__tls")]
+                tls_selected: Vec::new(),
+                #[cfg(feature = "rustls-tls")]
+                tls_session_cache: None,
+                #[cfg(feature = "rustls-tls")]
+                tls_early_data: false,
                 http2_only: false,
+                http2_tls_only: false,
                 http1_title_case_headers: false,
+                http1_writev: None,
                 http2_initial_stream_window_size: None,
                 http2_initial_connection_window_size: None,
-                local_address: None,
+                local_address_ipv4: None,
+                local_address_ipv6: None,
                 nodelay: true,
+                tcp_user_timeout: None,
                 trust_dns: cfg!(feature = "trust-dns"),
+                dns_overrides: HashMap::new(),
+                hosts_files: Vec::new(),
                 #[cfg(feature = "cookies")]
                 cookie_store: None,
+                #[cfg(feature = "cookies")]
+                cookie_store_capacity: None,
             },
         }
     }
@@ -160,25 +228,68 @@ impl ClientBuilder {
     /// This method fails if TLS backend cannot be initialized, or the resolver
     /// cannot load the system configuration.
     pub fn build(self) -> crate::Result<Client> {
-        let config = self.config;
+        let mut config = self.config;
 
         if let Some(err) = config.error {
             return Err(err);
         }
 
+        #[cfg(feature = "__tls")]
+        {
+            if let Some((first, rest)) = config.tls_selected.split_first() {
+                if let Some(other) = rest.iter().find(|name| *name != first) {
+                    return Err(crate::error::builder(format!(
+                        "conflicting TLS backends selected: `{}` and `{}`",
+                        first, other
+                    )));
+                }
+            }
+        }
+
+        if config.concurrency_limit_per_host == Some(0) {
+            return Err(crate::error::builder(
+                "concurrency_limit_per_host must be greater than zero",
+            ));
+        }
+
+        if config.connection_reuse_max_requests == Some(0) {
+            return Err(crate::error::builder(
+                "connection_reuse_max_requests must be greater than zero",
+            ));
+        }
+
+        if config.default_user_agent && !config.headers.contains_key(USER_AGENT) {
+            config
+                .headers
+                .insert(USER_AGENT, HeaderValue::from_static(DEFAULT_USER_AGENT));
+        }
+
         let mut proxies = config.proxies;
-        if config.auto_sys_proxy {
+        if config.system_proxy.unwrap_or(config.auto_sys_proxy) {
             proxies.push(Proxy::system());
         }
         let proxies = Arc::new(proxies);
 
+        #[cfg(feature = "__tls")]
+        let tls_backend = Some(config.tls.name());
+        #[cfg(not(feature = "__tls"))]
+        let tls_backend = None;
+
+        let client_config = ClientConfig {
+            timeout: config.timeout,
+            connect_timeout: config.connect_timeout,
+            max_redirects: config.redirect_policy.max_redirects(),
+            has_proxy: proxies.iter().any(|proxy| proxy.is_active()),
+            tls_backend,
+        };
+
         let mut connector = {
             #[cfg(feature = "__tls")]
             fn user_agent(headers: &HeaderMap) -> Option<HeaderValue> {
                 headers.get(USER_AGENT).cloned()
             }
 
-            let http = match config.trust_dns {
+            let mut http = match config.trust_dns {
                 false => HttpConnector::new_gai(),
                 #[cfg(feature = "trust-dns")]
                 true => HttpConnector::new_trust_dns()?,
@@ -186,10 +297,52 @@ impl ClientBuilder {
                 true => unreachable!("trust-dns shouldn't be enabled unless the feature is"),
             };
 
+            let mut overrides = config.dns_overrides;
+            for path in &config.hosts_files {
+                connect::read_hosts_file(path, &mut overrides)?;
+            }
+            http.set_overrides(overrides);
+            let (ipv4, ipv6) = (config.local_address_ipv4, config.local_address_ipv6);
+            http.set_local_addresses_by_family(ipv4, ipv6);
+
+            #[cfg(target_os = "linux")]
+            http.set_user_timeout(config.tcp_user_timeout);
+            #[cfg(not(target_os = "linux"))]
+            {
+                if config.tcp_user_timeout.is_some() {
+                    return Err(crate::error::builder(
+                        "tcp_user_timeout is only supported on Linux",
+                    ));
+                }
+            }
+
             #[cfg(feature = "__tls")]
             match config.tls {
                 #[cfg(feature = "default-tls")]
                 TlsBackend::Default => {
+                    #[cfg(feature = "rustls-tls")]
+                    {
+                        if !config.invalid_certs_hosts.is_empty() {
+                            return Err(crate::error::builder(
+                                "accepting invalid certs per host requires the rustls backend",
+                            ));
+                        }
+                        if config.tls_early_data {
+                            return Err(crate::error::builder(
+                                "TLS early data requires the rustls backend",
+                            ));
+                        }
+                    }
+
+                    #[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+                    {
+                        if !config.hostname_verification {
+                            return Err(crate::error::builder(
+                                "accepting invalid hostnames requires native-tls or rustls",
+                            ));
+                        }
+                    }
+
                     let mut tls = TlsConnector::builder();
 
                     #[cfg(feature = "native-tls")]
@@ -216,18 +369,24 @@ impl ClientBuilder {
                         tls,
                         proxies.clone(),
                         user_agent(&config.headers),
-                        config.local_address,
                         config.nodelay,
                     )?
                 },
                 #[cfg(feature = "native-tls")]
                 TlsBackend::BuiltNativeTls(conn) => {
+                    #[cfg(feature = "rustls-tls")]
+                    {
+                        if config.tls_early_data {
+                            return Err(crate::error::builder(
+                                "TLS early data requires the rustls backend",
+                            ));
+                        }
+                    }
                     Connector::from_built_default_tls(
                         http,
                         conn,
                         proxies.clone(),
                         user_agent(&config.headers),
-                        config.local_address,
                         config.nodelay)
                 },
                 #[cfg(feature = "rustls-tls")]
@@ -237,15 +396,19 @@ impl ClientBuilder {
                         conn,
                         proxies.clone(),
                         user_agent(&config.headers),
-                        config.local_address,
                         config.nodelay)
                 },
                 #[cfg(feature = "rustls-tls")]
                 TlsBackend::Rustls => {
-                    use crate::tls::NoVerifier;
+                    use crate::tls::{AcceptInvalidFor, IgnoreHostname, NoVerifier};
 
                     let mut tls = rustls::ClientConfig::new();
-                    if config.http2_only {
+                    if config.http2_only || config.http2_tls_only {
+                        if config.tls_early_data {
+                            return Err(crate::error::builder(
+                                "TLS early data can't be used with HTTP/2 only",
+                            ));
+                        }
                         tls.set_protocols(&["h2".into()]);
                     } else {
                         tls.set_protocols(&["h2".into(), "http/1.1".into()]);
@@ -256,6 +419,18 @@ impl ClientBuilder {
                     if !config.certs_verification {
                         tls.dangerous()
                             .set_certificate_verifier(Arc::new(NoVerifier));
+                    } else if !config.invalid_certs_hosts.is_empty() {
+                        let inner: Box<dyn rustls::ServerCertVerifier> =
+                            if config.hostname_verification {
+                                Box::new(rustls::WebPKIVerifier::new())
+                            } else {
+                                Box::new(IgnoreHostname::new())
+                            };
+                        let verifier = AcceptInvalidFor::new(config.invalid_certs_hosts, inner);
+                        tls.dangerous().set_certificate_verifier(Arc::new(verifier));
+                    } else if !config.hostname_verification {
+                        tls.dangerous()
+                            .set_certificate_verifier(Arc::new(IgnoreHostname::new()));
                     }
 
                     for cert in config.root_certs {
@@ -266,12 +441,19 @@ impl ClientBuilder {
                         id.add_to_rustls(&mut tls)?;
                     }
 
+                    match config.tls_session_cache {
+                        Some(0) => tls.set_persistence(Arc::new(rustls::NoClientSessionStorage {})),
+                        Some(capacity) => {
+                            tls.set_persistence(rustls::ClientSessionMemoryCache::new(capacity))
+                        }
+                        None => {}
+                    }
+
                     Connector::new_rustls_tls(
                         http,
                         tls,
                         proxies.clone(),
                         user_agent(&config.headers),
-                        config.local_address,
                         config.nodelay,
                     )
                 },
@@ -287,11 +469,14 @@ impl ClientBuilder {
             }
 
             #[cfg(not(feature = "__tls"))]
-            Connector::new(http, proxies.clone(), config.local_address, config.nodelay)
+            Connector::new(http, proxies.clone(), config.nodelay)
         };
 
         connector.set_timeout(config.connect_timeout);
         connector.set_verbose(config.connection_verbose);
+        connector.set_http2_tls_only(config.http2_tls_only);
+        connector.set_reuse_max_requests(config.connection_reuse_max_requests);
+        connector.set_warm_limits(config.pool_idle_timeout, config.pool_max_idle_per_host);
 
         let mut builder = hyper::Client::builder();
         if config.http2_only {
@@ -314,22 +499,57 @@ impl ClientBuilder {
             builder.http1_title_case_headers(true);
         }
 
-        let hyper_client = builder.build(connector);
+        if let Some(writev) = config.http1_writev {
+            builder.http1_writev(writev);
+        }
+
+        let hyper_client = builder.build(connector.clone());
+
+        #[cfg(feature = "rustls-tls")]
+        let hyper_early_data = if config.tls_early_data {
+            let mut connector = connector.clone();
+            connector.set_tls_early_data(true);
+            Some(builder.build(connector))
+        } else {
+            None
+        };
+        #[cfg(not(feature = "rustls-tls"))]
+        let hyper_early_data = None;
 
         let proxies_maybe_http_auth = proxies.iter().any(|p| p.maybe_has_http_auth());
 
+        #[cfg(feature = "cookies")]
+        {
+            if let (Some(handle), Some(capacity)) =
+                (config.cookie_store.as_ref(), config.cookie_store_capacity)
+            {
+                handle.0.write().unwrap().set_capacity(capacity);
+            }
+        }
+
         Ok(Client {
             inner: Arc::new(ClientRef {
                 accepts: config.accepts,
                 #[cfg(feature = "cookies")]
-                cookie_store: config.cookie_store.map(RwLock::new),
+                cookie_store: config.cookie_store.map(|handle| handle.0),
                 hyper: hyper_client,
+                hyper_early_data,
+                connector,
                 headers: config.headers,
+                merged_headers: config.merged_headers,
                 redirect_policy: config.redirect_policy,
+                retry_policy: config.retry_policy,
                 referer: config.referer,
+                referer_trim_query: config.referer_trim_query,
                 request_timeout: config.timeout,
+                max_response_size: config.max_response_size,
+                resume_downloads: config.resume_downloads,
+                preserve_header_order: config.preserve_header_order,
+                host_limits: config.concurrency_limit_per_host.map(HostLimits::new),
+                connection_verbose: config.connection_verbose,
                 proxies,
                 proxies_maybe_http_auth,
+                config: client_config,
             }),
         })
     }
@@ -371,8 +591,33 @@ impl ClientBuilder {
         };
         self
     }
+
+    /// Sends a `reqwest/<version>` `User-Agent` when none is otherwise set.
+    ///
+    /// The default is only used if neither [`user_agent`] nor
+    /// [`default_headers`] configured one, and a `User-Agent` set on an
+    /// individual request still takes precedence.
+    ///
+    /// Default is off, meaning no `User-Agent` is sent unless one is set.
+    ///
+    /// [`user_agent`]: ClientBuilder::user_agent
+    /// [`default_headers`]: ClientBuilder::default_headers
+    pub fn default_user_agent_if_unset(mut self) -> ClientBuilder {
+        self.config.default_user_agent = true;
+        self
+    }
+
     /// Sets the default headers for every request.
     ///
+    /// A request that sets a header itself replaces the default values of
+    /// that header. See [`default_headers_merge`] to keep both.
+    ///
+    /// Credentials, meaning `Authorization`, `Cookie` and
+    /// `Proxy-Authorization` values, are marked as sensitive, so they are
+    /// hidden from `Debug` output.
+    ///
+    /// [`default_headers_merge`]: ClientBuilder::default_headers_merge
+    ///
     /// # Example
     ///
     /// ```rust
@@ -412,11 +657,76 @@ impl ClientBuilder {
     /// ```
     pub fn default_headers(mut self, headers: HeaderMap) -> ClientBuilder {
         for (key, value) in headers.iter() {
-            self.config.headers.insert(key, value.clone());
+            self.config.merged_headers.remove(key);
+            self.config
+                .headers
+                .insert(key, default_header_value(key, value));
+        }
+        self
+    }
+
+    /// Sets default headers that are merged with the headers of each request.
+    ///
+    /// Unlike [`default_headers`], a request that sets one of these headers
+    /// itself doesn't replace the defaults: its values are appended after
+    /// the default values. This suits headers where several values are
+    /// meaningful, such as `Accept`.
+    ///
+    /// A header set here is no longer a replaced default if it was set with
+    /// [`default_headers`] before, and the other way around.
+    /// Credentials are marked as sensitive, as with [`default_headers`].
+    ///
+    /// [`default_headers`]: ClientBuilder::default_headers
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use reqwest::header;
+    /// # async fn doc() -> Result<(), reqwest::Error> {
+    /// let mut headers = header::HeaderMap::new();
+    /// headers.insert(header::ACCEPT, header::HeaderValue::from_static("text/html"));
+    ///
+    /// let client = reqwest::Client::builder()
+    ///     .default_headers_merge(headers)
+    ///     .build()?;
+    /// // sends both `Accept: text/html` and `Accept: application/json`
+    /// let res = client
+    ///     .get("https://www.rust-lang.org")
+    ///     .header(header::ACCEPT, "application/json")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn default_headers_merge(mut self, headers: HeaderMap) -> ClientBuilder {
+        for key in headers.keys() {
+            self.config.headers.remove(key);
+            self.config.merged_headers.remove(key);
+        }
+        for (key, value) in headers.iter() {
+            self.config
+                .merged_headers
+                .append(key, default_header_value(key, value));
         }
         self
     }
 
+    /// Send the headers of each request in the order they were added to it.
+    ///
+    /// Headers the client adds, such as default headers not already set on
+    /// the request, follow the request's own headers. Without this, merging
+    /// default headers can reorder the request's headers, which matters to
+    /// signing schemes that depend on the order headers are sent in.
+    ///
+    /// This copies the headers of every request, and of every redirect,
+    /// into a new map before sending them.
+    ///
+    /// Default is `false`.
+    pub fn preserve_header_order(mut self, enable: bool) -> ClientBuilder {
+        self.config.preserve_header_order = enable;
+        self
+    }
+
     /// Enable a persistent cookie store for the client.
     ///
     /// Cookies received in responses will be preserved and included in
@@ -430,13 +740,49 @@ impl ClientBuilder {
     #[cfg(feature = "cookies")]
     pub fn cookie_store(mut self, enable: bool) -> ClientBuilder {
         self.config.cookie_store = if enable {
-            Some(cookie::CookieStore::default())
+            Some(cookie::CookieStoreHandle::new())
         } else {
             None
         };
         self
     }
 
+    /// Use a shared cookie store for the client.
+    ///
+    /// This enables a persistent cookie store, like `cookie_store(true)`,
+    /// which is shared with every other client built with the same handle.
+    /// See [`Client::cookie_store_handle`](Client::cookie_store_handle).
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cookies` feature to be enabled.
+    #[cfg(feature = "cookies")]
+    pub fn cookie_store_handle(mut self, handle: cookie::CookieStoreHandle) -> ClientBuilder {
+        self.config.cookie_store = Some(handle);
+        self
+    }
+
+    /// Bound the number of cookies kept by the cookie store.
+    ///
+    /// When more than `max` cookies are stored, the least recently used
+    /// ones are evicted, where a cookie is used when it is stored or sent
+    /// in a request. Expired cookies are evicted first.
+    ///
+    /// This has no effect unless a cookie store is enabled. A store shared
+    /// with [`cookie_store_handle`](ClientBuilder::cookie_store_handle) is
+    /// bounded for every client using it.
+    ///
+    /// By default, the cookie store is unbounded.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cookies` feature to be enabled.
+    #[cfg(feature = "cookies")]
+    pub fn cookie_store_capacity(mut self, max: usize) -> ClientBuilder {
+        self.config.cookie_store_capacity = Some(max);
+        self
+    }
+
     /// Enable auto gzip decompression by checking the `Content-Encoding` response header.
     ///
     /// If auto gzip decompression is turned on:
@@ -447,6 +793,8 @@ impl ClientBuilder {
     /// - When receiving a response, if it's headers contain a `Content-Encoding` value that
     ///   equals to `gzip`, both values `Content-Encoding` and `Content-Length` are removed from the
     ///   headers' set. The response body is automatically decompressed.
+    /// - Responses to requests with a `Range` header are not decompressed, since
+    ///   a partial body can't be decoded on its own.
     ///
     /// If the `gzip` feature is turned on, the default option is enabled.
     ///
@@ -469,6 +817,8 @@ impl ClientBuilder {
     /// - When receiving a response, if it's headers contain a `Content-Encoding` value that
     ///   equals to `br`, both values `Content-Encoding` and `Content-Length` are removed from the
     ///   headers' set. The response body is automatically decompressed.
+    /// - Responses to requests with a `Range` header are not decompressed, since
+    ///   a partial body can't be decoded on its own.
     ///
     /// If the `brotli` feature is turned on, the default option is enabled.
     ///
@@ -515,6 +865,52 @@ impl ClientBuilder {
         }
     }
 
+    /// Set the order in which encodings are advertised in `Accept-Encoding`.
+    ///
+    /// Servers that honor the order pick the first encoding they support, so
+    /// list the preferred encodings first. Encodings missing from `order`
+    /// keep their default position after the listed ones.
+    ///
+    /// This only affects the order; an encoding is only advertised if its
+    /// decompression is enabled (see `gzip` and `brotli`).
+    ///
+    /// Default is `gzip` before `br`.
+    pub fn accept_encoding_priority(mut self, order: Vec<ContentEncoding>) -> ClientBuilder {
+        self.config.accepts.set_priority(&order);
+        self
+    }
+
+    /// Limit the size of response bodies.
+    ///
+    /// Reading a response body that is larger than `max` bytes, after any
+    /// automatic decompression, fails with an error for which
+    /// `Error::is_body_too_large()` returns `true`.
+    ///
+    /// Default is no limit.
+    pub fn max_response_size(mut self, max: u64) -> ClientBuilder {
+        self.config.max_response_size = Some(max);
+        self
+    }
+
+    /// Enable or disable resuming response bodies after a connection error.
+    ///
+    /// When enabled, if reading a response body fails partway, the rest of
+    /// it is requested with a `Range` request and reading continues from
+    /// there, instead of the body returning an error.
+    ///
+    /// This only applies to `GET` and `HEAD` requests whose response has a
+    /// `200 OK` status, a `Content-Length` and an `Accept-Ranges: bytes`
+    /// header. If the server doesn't answer the `Range` request with the
+    /// expected `206 Partial Content`, the original error is returned. An
+    /// `ETag` or `Last-Modified` header of the response is sent back as
+    /// `If-Range`, so a body that changed in the meantime isn't spliced.
+    ///
+    /// Default is `false`.
+    pub fn resume_downloads(mut self, enable: bool) -> ClientBuilder {
+        self.config.resume_downloads = enable;
+        self
+    }
+
     // Redirect options
 
     /// Set a `RedirectPolicy` for this client.
@@ -525,6 +921,19 @@ impl ClientBuilder {
         self
     }
 
+    // Retry options
+
+    /// Set a `retry::Policy` for this client.
+    ///
+    /// Requests that fail with a transient error are sent again, as the
+    /// policy allows, before the error is returned.
+    ///
+    /// Default doesn't retry requests.
+    pub fn retry(mut self, policy: retry::Policy) -> ClientBuilder {
+        self.config.retry_policy = policy;
+        self
+    }
+
     /// Enable or disable automatic setting of the `Referer` header.
     ///
     /// Default is `true`.
@@ -533,13 +942,26 @@ impl ClientBuilder {
         self
     }
 
+    /// Enable or disable omitting the query string from the `Referer` header.
+    ///
+    /// When enabled, the `Referer` sent on a redirect only contains the
+    /// origin and path of the previous URL, so tokens carried in its query
+    /// parameters aren't passed on to the next location.
+    ///
+    /// Default is `false`.
+    pub fn referer_trim_query(mut self, enable: bool) -> ClientBuilder {
+        self.config.referer_trim_query = enable;
+        self
+    }
+
     // Proxy options
 
     /// Add a `Proxy` to the list of proxies the `Client` will use.
     ///
     /// # Note
     ///
-    /// Adding a proxy will disable the automatic usage of the "system" proxy.
+    /// Adding a proxy will disable the automatic usage of the "system" proxy,
+    /// unless it has been explicitly enabled with `system_proxy(true)`.
     pub fn proxy(mut self, proxy: Proxy) -> ClientBuilder {
         self.config.proxies.push(proxy);
         self.config.auto_sys_proxy = false;
@@ -552,6 +974,20 @@ impl ClientBuilder {
     pub fn no_proxy(mut self) -> ClientBuilder {
         self.config.proxies.clear();
         self.config.auto_sys_proxy = false;
+        self.config.system_proxy = None;
+        self
+    }
+
+    /// Enable or disable the "system" proxy, configured from environment
+    /// variables such as `HTTP_PROXY` and `HTTPS_PROXY`.
+    ///
+    /// When enabled, the system proxy is consulted after any proxies added
+    /// with `proxy()`, so it can be used as a fallback. When disabled, the
+    /// system proxy is not used even if no other proxy is configured.
+    ///
+    /// By default, the system proxy is used only if no proxy has been added.
+    pub fn system_proxy(mut self, enable: bool) -> ClientBuilder {
+        self.config.system_proxy = Some(enable);
         self
     }
 
@@ -565,8 +1001,13 @@ impl ClientBuilder {
 
     /// Enables a request timeout.
     ///
-    /// The timeout is applied from the when the request starts connecting
-    /// until the response body has finished.
+    /// The timeout is applied from when the request is sent until the
+    /// response body has finished. Since it starts before a pooled
+    /// connection is checked out or a new one is connected, time spent
+    /// waiting for a connection counts against it as well.
+    ///
+    /// A request that times out fails with an error for which
+    /// `Error::is_timeout` is true, and which carries the request `Url`.
     ///
     /// Default is no timeout.
     pub fn timeout(mut self, timeout: Duration) -> ClientBuilder {
@@ -576,6 +1017,12 @@ impl ClientBuilder {
 
     /// Set a timeout for only the connect phase of a `Client`.
     ///
+    /// The timeout bounds the whole connect phase, including DNS resolution,
+    /// every address tried when a host resolves to several (IPv6 and IPv4
+    /// attempts race using Happy Eyeballs), and any TLS handshake. It is not
+    /// applied to each address attempt separately. The request `timeout`, if
+    /// set, still bounds the request as a whole.
+    ///
     /// Default is `None`.
     ///
     /// # Note
@@ -591,6 +1038,9 @@ impl ClientBuilder {
     ///
     /// Enabling this option will emit [log][] messages at the `TRACE` level
     /// for read and write operations on connections.
+    /// It also logs, at the `DEBUG` level, the HTTP version negotiated for
+    /// each request, which can be used to confirm HTTP/2 was used.
+    /// The version is available afterwards from `Response::version`.
     ///
     /// [log]: https://crates.io/crates/log
     pub fn connection_verbose(mut self, verbose: bool) -> ClientBuilder {
@@ -619,6 +1069,35 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the maximum number of requests in flight to any single host.
+    ///
+    /// Requests over the limit wait for an earlier request to the same host
+    /// and port to finish before they are sent, which counts towards their
+    /// `timeout`. A request is in flight until its response body has been
+    /// read to the end or dropped, so holding on to unread responses can
+    /// keep later requests to the same host waiting.
+    ///
+    /// Default is no limit. Building the `Client` fails if `max` is zero.
+    pub fn concurrency_limit_per_host(mut self, max: usize) -> ClientBuilder {
+        self.config.concurrency_limit_per_host = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of requests a connection serves before it's
+    /// closed.
+    ///
+    /// A connection is closed once the body of its last response has been
+    /// read to the end or dropped, and later requests open a new one. This
+    /// spreads requests over servers behind a load balancer, for instance.
+    /// Requests sent on the connection concurrently may go slightly over
+    /// the limit. Only HTTP/1 connections are limited.
+    ///
+    /// Default is no limit. Building the `Client` fails if `max` is zero.
+    pub fn connection_reuse_max_requests(mut self, max: usize) -> ClientBuilder {
+        self.config.connection_reuse_max_requests = Some(max);
+        self
+    }
+
     #[doc(hidden)]
     #[deprecated(note = "renamed to `pool_max_idle_per_host`")]
     pub fn max_idle_per_host(self, max: usize) -> ClientBuilder {
@@ -631,12 +1110,42 @@ impl ClientBuilder {
         self
     }
 
+    /// Set whether HTTP/1 connections should try to use vectored writes,
+    /// or always flatten into a single buffer.
+    ///
+    /// Disabling them may help with an IO transport or proxy that doesn't
+    /// handle vectored writes well.
+    ///
+    /// Default is to let hyper guess which mode to use.
+    pub fn http1_writev(mut self, enable: bool) -> ClientBuilder {
+        self.config.http1_writev = Some(enable);
+        self
+    }
+
     /// Only use HTTP/2.
+    ///
+    /// For `http` URLs, this means speaking cleartext HTTP/2 (`h2c`) with
+    /// prior knowledge, which few servers accept. See
+    /// [`http2_prior_knowledge_https_only`] to only use HTTP/2 over TLS.
+    ///
+    /// [`http2_prior_knowledge_https_only`]: ClientBuilder::http2_prior_knowledge_https_only
     pub fn http2_prior_knowledge(mut self) -> ClientBuilder {
         self.config.http2_only = true;
         self
     }
 
+    /// Only use HTTP/2 for `https` URLs, and HTTP/1 for `http` URLs.
+    ///
+    /// Connections over TLS speak HTTP/2 from the start when the TLS
+    /// handshake didn't negotiate a protocol with ALPN, as with the
+    /// `native-tls` backend. A protocol the server picked with ALPN is still
+    /// used. Cleartext connections keep using HTTP/1 instead of `h2c` as with
+    /// [`http2_prior_knowledge`](ClientBuilder::http2_prior_knowledge).
+    pub fn http2_prior_knowledge_https_only(mut self) -> ClientBuilder {
+        self.config.http2_tls_only = true;
+        self
+    }
+
     /// Sets the `SETTINGS_INITIAL_WINDOW_SIZE` option for HTTP2 stream-level flow control.
     ///
     /// Default is currently 65,535 but may change internally to optimize for common uses.
@@ -678,6 +1187,24 @@ impl ClientBuilder {
         self
     }
 
+    /// Set `TCP_USER_TIMEOUT` on sockets of new connections.
+    ///
+    /// This bounds how long transmitted data may remain unacknowledged
+    /// before the connection is dropped. Unlike the request timeouts it
+    /// applies to the connection itself, and it complements TCP keepalive
+    /// in detecting dead peers on long-lived connections.
+    ///
+    /// Default is `None`, which leaves the system default in place.
+    ///
+    /// # Platform
+    ///
+    /// This is only supported on Linux. On other platforms, setting it
+    /// causes `build()` to return an error.
+    pub fn tcp_user_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.tcp_user_timeout = Some(timeout);
+        self
+    }
+
     /// Bind to a local IP Address.
     ///
     /// # Example
@@ -689,11 +1216,45 @@ impl ClientBuilder {
     ///     .local_address(local_addr)
     ///     .build().unwrap();
     /// ```
-    pub fn local_address<T>(mut self, addr: T) -> ClientBuilder
+    pub fn local_address<T>(self, addr: T) -> ClientBuilder
     where
         T: Into<Option<IpAddr>>,
     {
-        self.config.local_address = addr.into();
+        let (ipv4, ipv6) = match addr.into() {
+            Some(IpAddr::V4(ipv4)) => (Some(ipv4), None),
+            Some(IpAddr::V6(ipv6)) => (None, Some(ipv6)),
+            None => (None, None),
+        };
+        self.local_addresses(ipv4, ipv6)
+    }
+
+    /// Bind to a local IP Address for each address family.
+    ///
+    /// Connections to IPv4 addresses are bound to `ipv4`, and connections
+    /// to IPv6 addresses to `ipv6`. This is useful on multi-homed hosts,
+    /// where the right source address differs by family. A family without
+    /// an address isn't bound.
+    ///
+    /// This replaces any address set with `local_address`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::net::{Ipv4Addr, Ipv6Addr};
+    /// let client = reqwest::Client::builder()
+    ///     .local_addresses(
+    ///         Some(Ipv4Addr::new(12, 4, 1, 8)),
+    ///         Some(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+    ///     )
+    ///     .build().unwrap();
+    /// ```
+    pub fn local_addresses(
+        mut self,
+        ipv4: Option<Ipv4Addr>,
+        ipv6: Option<Ipv6Addr>,
+    ) -> ClientBuilder {
+        self.config.local_address_ipv4 = ipv4;
+        self.config.local_address_ipv6 = ipv6;
         self
     }
 
@@ -737,10 +1298,14 @@ impl ClientBuilder {
     /// site will be trusted for use from any other. This introduces a
     /// significant vulnerability to man-in-the-middle attacks.
     ///
+    /// The certificate chain is still verified, unless
+    /// `danger_accept_invalid_certs` is also used.
+    ///
     /// # Optional
     ///
-    /// This requires the optional `native-tls` feature to be enabled.
-    #[cfg(feature = "native-tls")]
+    /// This requires the optional `native-tls` or `rustls-tls` feature to be
+    /// enabled.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
     pub fn danger_accept_invalid_hostnames(
         mut self,
         accept_invalid_hostname: bool,
@@ -771,17 +1336,45 @@ impl ClientBuilder {
         self
     }
 
+    /// Skip certificate validation for connections to `hosts` only.
+    ///
+    /// Certificates of all other hosts are still validated. This is safer
+    /// than `danger_accept_invalid_certs` for talking to a few internal
+    /// services with self-signed certificates. Hosts are matched against the
+    /// server name sent in the TLS handshake, ignoring ASCII case.
+    ///
+    /// # Warning
+    ///
+    /// Any certificate is trusted for the listed hosts, so connections to
+    /// them can be intercepted.
+    ///
+    /// This only works with the `rustls` backend; `build` fails if another
+    /// backend is used.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `rustls-tls` feature to be enabled.
+    #[cfg(feature = "rustls-tls")]
+    pub fn danger_accept_invalid_certs_for(mut self, hosts: Vec<String>) -> ClientBuilder {
+        self.config.invalid_certs_hosts = hosts;
+        self
+    }
+
     /// Force using the native TLS backend.
     ///
     /// Since multiple TLS backends can be optionally enabled, this option will
     /// force the `native-tls` backend to be used for this `Client`.
     ///
+    /// Selecting a different TLS backend on the same builder causes `build`
+    /// to return an error.
+    ///
     /// # Optional
     ///
     /// This requires the optional `native-tls` feature to be enabled.
     #[cfg(feature = "native-tls")]
     pub fn use_native_tls(mut self) -> ClientBuilder {
         self.config.tls = TlsBackend::Default;
+        self.config.tls_selected.push("native-tls");
         self
     }
 
@@ -790,12 +1383,63 @@ impl ClientBuilder {
     /// Since multiple TLS backends can be optionally enabled, this option will
     /// force the `rustls` backend to be used for this `Client`.
     ///
+    /// Selecting a different TLS backend on the same builder causes `build`
+    /// to return an error.
+    ///
     /// # Optional
     ///
     /// This requires the optional `rustls-tls` feature to be enabled.
     #[cfg(feature = "rustls-tls")]
     pub fn use_rustls_tls(mut self) -> ClientBuilder {
         self.config.tls = TlsBackend::Rustls;
+        self.config.tls_selected.push("rustls-tls");
+        self
+    }
+
+    /// Set how many TLS sessions are kept for resumption.
+    ///
+    /// Resuming a session with a host we connected to before, using a
+    /// session ticket or ID, avoids a full handshake on reconnect. Passing
+    /// `0` disables resumption, so that connections can't be linked to each
+    /// other by the server.
+    ///
+    /// Default is a cache of 32 sessions.
+    ///
+    /// This only applies to the `rustls` backend. The native backend leaves
+    /// session resumption to the platform TLS library.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `rustls-tls` feature to be enabled.
+    #[cfg(feature = "rustls-tls")]
+    pub fn tls_session_cache(mut self, capacity: usize) -> ClientBuilder {
+        self.config.tls_session_cache = Some(capacity);
+        self
+    }
+
+    /// Enable sending TLS 1.3 early data (0-RTT) on resumed sessions.
+    ///
+    /// When a connection resumes a session from the cache, the request is
+    /// sent along with the handshake instead of after it, saving a round
+    /// trip.
+    ///
+    /// Early data can be replayed by an attacker, so only `GET` and `HEAD`
+    /// requests are ever sent as early data. Other requests use connections
+    /// of their own. As the protocol isn't negotiated before early data is
+    /// sent, the connections sending it only offer HTTP/1.1, and `build`
+    /// fails if HTTP/2 is required. Other requests still negotiate HTTP/2.
+    ///
+    /// Default is `false`.
+    ///
+    /// This only applies to the `rustls` backend, and `build` fails if
+    /// another backend is used.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `rustls-tls` feature to be enabled.
+    #[cfg(feature = "rustls-tls")]
+    pub fn tls_early_data(mut self, enable: bool) -> ClientBuilder {
+        self.config.tls_early_data = enable;
         self
     }
 
@@ -803,6 +1447,8 @@ impl ClientBuilder {
     ///
     /// If the passed `Any` argument is not a TLS backend that reqwest
     /// understands, the `ClientBuilder` will error when calling `build`.
+    /// The same happens if it conflicts with another TLS backend selected on
+    /// this builder.
     ///
     /// # Advanced
     ///
@@ -829,6 +1475,7 @@ impl ClientBuilder {
                 let tls = conn.take().expect("is definitely Some");
                 let tls = crate::tls::TlsBackend::BuiltNativeTls(tls);
                 self.config.tls = tls;
+                self.config.tls_selected.push("native-tls");
                 return self;
             }
         }
@@ -839,6 +1486,7 @@ impl ClientBuilder {
                 let tls = conn.take().expect("is definitely Some");
                 let tls = crate::tls::TlsBackend::BuiltRustls(tls);
                 self.config.tls = tls;
+                self.config.tls_selected.push("rustls-tls");
                 return self;
             }
         }
@@ -877,11 +1525,40 @@ impl ClientBuilder {
             self
         }
     }
-}
 
-type HyperClient = hyper::Client<Connector, super::body::ImplStream>;
+    /// Override DNS resolution for a single domain to the given address.
+    ///
+    /// Connections to `domain` use `addr` with the port of the request URL,
+    /// instead of looking the domain up with the resolver.
+    ///
+    /// Overrides set here take precedence over entries loaded with
+    /// [`resolve_from_hosts_file`](ClientBuilder::resolve_from_hosts_file).
+    pub fn resolve(mut self, domain: &str, addr: IpAddr) -> ClientBuilder {
+        self.config
+            .dns_overrides
+            .insert(domain.to_ascii_lowercase(), addr);
+        self
+    }
 
-impl Default for Client {
+    /// Override DNS resolution with the entries of a hosts file.
+    ///
+    /// The file uses the format of `/etc/hosts`: each line holds an IP
+    /// address followed by one or more host names, and `#` starts a comment.
+    /// It is read when the client is built. If a name is listed more than
+    /// once, the first address wins.
+    ///
+    /// # Errors
+    ///
+    /// `build()` fails if the file can't be read or a line can't be parsed.
+    pub fn resolve_from_hosts_file(mut self, path: PathBuf) -> ClientBuilder {
+        self.config.hosts_files.push(path);
+        self
+    }
+}
+
+pub(super) type HyperClient = hyper::Client<Connector, super::body::ImplStream>;
+
+impl Default for Client {
     fn default() -> Self {
         Self::new()
     }
@@ -975,6 +1652,155 @@ impl Client {
         RequestBuilder::new(self.clone(), req)
     }
 
+    /// Send a `GET` request to each of `urls` concurrently.
+    ///
+    /// The returned stream yields each response paired with its URL, in the
+    /// order they complete. The requests share this `Client`'s connection
+    /// pool, and at most 32 of them are in flight at the same time; use
+    /// [`get_many_limited`] to choose another limit.
+    ///
+    /// [`get_many_limited`]: #method.get_many_limited
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use futures_util::stream::StreamExt;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let urls = vec![
+    ///     "https://www.rust-lang.org".parse()?,
+    ///     "https://hyper.rs".parse()?,
+    /// ];
+    ///
+    /// let client = reqwest::Client::new();
+    /// let mut responses = client.get_many(urls);
+    /// while let Some((url, res)) = responses.next().await {
+    ///     println!("{}: {:?}", url, res.map(|res| res.status()));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_many<I>(&self, urls: I) -> impl Stream<Item = (Url, crate::Result<Response>)>
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        self.get_many_inner(urls, GET_MANY_LIMIT)
+    }
+
+    /// Send a `GET` request to each of `urls`, with at most `limit` of them
+    /// in flight at the same time.
+    ///
+    /// This is otherwise the same as [`get_many`](#method.get_many). A
+    /// `limit` of zero counts as one.
+    pub fn get_many_limited<I>(
+        &self,
+        urls: I,
+        limit: usize,
+    ) -> impl Stream<Item = (Url, crate::Result<Response>)>
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        self.get_many_inner(urls, limit)
+    }
+
+    fn get_many_inner<I>(
+        &self,
+        urls: I,
+        limit: usize,
+    ) -> impl Stream<Item = (Url, crate::Result<Response>)>
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        let client = self.clone();
+        futures_util::stream::iter(urls)
+            .map(move |url| {
+                let pending = client.get(url.clone()).send();
+                async move { (url, pending.await) }
+            })
+            .buffer_unordered(limit.max(1))
+    }
+
+    /// Open a tunnel to `authority` with a `CONNECT` request.
+    ///
+    /// The request is sent like any other request to `http://{authority}`,
+    /// so it goes through a proxy configured for `http` if there is one, or
+    /// to `authority` itself otherwise. Once the server answers with a
+    /// successful status, the connection is handed over as an [`Upgraded`]
+    /// byte stream.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .proxy(reqwest::Proxy::http("http://proxy.example:3128")?)
+    ///     .build()?;
+    /// let tunnel = client.connect("ssh.example:22").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `authority` is not a valid `host:port`, there
+    /// was an error while sending the request, the response status is not
+    /// successful, or the connection could not be upgraded.
+    pub async fn connect(&self, authority: &str) -> crate::Result<Upgraded> {
+        let authority = authority
+            .parse::<http::uri::Authority>()
+            .map_err(error::builder)?;
+        let url = Url::parse(&format!("http://{}", authority)).map_err(error::builder)?;
+
+        let res = self.request(Method::CONNECT, url).send().await?;
+        let res = res.error_for_status()?;
+        if !res.status().is_success() {
+            let msg = format!("unexpected CONNECT response status: {}", res.status());
+            return Err(error::upgrade(msg).with_url(res.url().clone()));
+        }
+        res.upgrade().await
+    }
+
+    /// Establish a connection to the host of `url` ahead of time.
+    ///
+    /// The connection is opened the same way a request would open it,
+    /// respecting the connect timeout, proxies and TLS configuration, but
+    /// no request is sent. The next request to the same scheme, host and
+    /// port that needs a new connection uses it instead of connecting,
+    /// after which it is pooled like any other connection.
+    ///
+    /// A preconnected connection is kept until a request uses it, for as
+    /// long as the [`pool_idle_timeout`](ClientBuilder::pool_idle_timeout).
+    /// Connections closed by the server in the meantime are not used, and
+    /// at most [`pool_max_idle_per_host`](ClientBuilder::pool_max_idle_per_host)
+    /// are kept for each host, the oldest making room for new ones.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::new();
+    /// client.preconnect("https://www.rust-lang.org").await?;
+    ///
+    /// // Doesn't need to wait for a new connection.
+    /// let res = client.get("https://www.rust-lang.org").send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `url` is not a valid URL, or the connection
+    /// could not be established.
+    pub async fn preconnect<U: IntoUrl>(&self, url: U) -> crate::Result<()> {
+        let url = url.into_url()?;
+        let uri = expect_uri(&url);
+        self.inner
+            .connector
+            .preconnect(uri)
+            .await
+            .map_err(|e| error::request(e).with_url(url))
+    }
+
     /// Executes a `Request`.
     ///
     /// A `Request` can be built manually with `Request::new()` or obtained
@@ -994,12 +1820,72 @@ impl Client {
         self.execute_request(request)
     }
 
+    /// Returns a `tower::Service` that accepts `http::Request<Body>` and
+    /// yields `http::Response<Body>`, sharing this `Client`'s connection pool.
+    pub fn http_service(&self) -> HttpService {
+        HttpService {
+            client: self.clone(),
+        }
+    }
+
+    /// Returns a handle to this `Client`'s cookie store, if it has one.
+    ///
+    /// Pass the handle to `ClientBuilder::cookie_store_handle` to build
+    /// another client sharing the same cookies.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// use std::time::Duration;
+    ///
+    /// let fast = reqwest::Client::builder()
+    ///     .cookie_store(true)
+    ///     .timeout(Duration::from_secs(1))
+    ///     .build()?;
+    /// let slow = reqwest::Client::builder()
+    ///     .cookie_store_handle(fast.cookie_store_handle().unwrap())
+    ///     .timeout(Duration::from_secs(60))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cookies` feature to be enabled.
+    #[cfg(feature = "cookies")]
+    pub fn cookie_store_handle(&self) -> Option<cookie::CookieStoreHandle> {
+        self.inner
+            .cookie_store
+            .clone()
+            .map(cookie::CookieStoreHandle)
+    }
+
+    /// Returns a snapshot of the configuration this `Client` was built with.
+    ///
+    /// This lets code handed a `Client` log or validate how it behaves.
+    pub fn config(&self) -> &ClientConfig {
+        &self.inner.config
+    }
+
     pub(super) fn execute_request(&self, req: Request) -> Pending {
-        let (method, url, mut headers, body, timeout) = req.pieces();
+        let (method, url, mut headers, body, timeout, connect_timeout, auto_decompress) =
+            req.pieces();
         if url.scheme() != "http" && url.scheme() != "https" {
             return Pending::new_err(error::url_bad_scheme(url));
         }
 
+        let order = self.inner.header_order(&headers);
+
+        // a content type carried by the body comes before the defaults,
+        // but never overrides the request's own header.
+        if let Some(content_type) = body.as_ref().and_then(|body| body.content_type()) {
+            if !headers.contains_key(CONTENT_TYPE) {
+                headers.insert(CONTENT_TYPE, content_type.clone());
+            }
+        }
+
         // insert default headers in the request headers
         // without overwriting already appended headers.
         for (key, value) in &self.inner.headers {
@@ -1008,6 +1894,15 @@ impl Client {
             }
         }
 
+        // merged default headers go before the request's own values.
+        for key in self.inner.merged_headers.keys() {
+            let own = headers.get_all(key).iter().cloned().collect::<Vec<_>>();
+            headers.remove(key);
+            for value in self.inner.merged_headers.get_all(key).iter().chain(&own) {
+                headers.append(key, value.clone());
+            }
+        }
+
         // Add cookies from the cookie store.
         #[cfg(feature = "cookies")]
         {
@@ -1031,13 +1926,21 @@ impl Client {
 
         let (reusable, body) = match body {
             Some(body) => {
-                let (reusable, body) = body.try_reuse();
-                (Some(reusable), body)
+                // hyper omits the length of an empty body, but one that was
+                // set explicitly should still be announced.
+                if body.is_empty() == Some(true) && !headers.contains_key(CONTENT_LENGTH) {
+                    headers.insert(CONTENT_LENGTH, HeaderValue::from_static("0"));
+                }
+                (Some(body.try_clone()), body)
             }
             None => (None, Body::empty()),
         };
 
-        self.proxy_auth(&uri, &mut headers);
+        self.inner.proxy_auth(&uri, &mut headers);
+        let headers = restore_header_order(order, headers);
+
+        let connect_timeout_guard = connect_timeout
+            .map(|timeout| self.inner.connector.request_connect_timeout(&uri, timeout));
 
         let mut req = hyper::Request::builder()
             .method(method.clone())
@@ -1051,7 +1954,19 @@ impl Client {
 
         *req.headers_mut() = headers.clone();
 
-        let in_flight = self.inner.hyper.request(req);
+        let accepts = if auto_decompress && !headers.contains_key(RANGE) {
+            self.inner.accepts
+        } else {
+            Accepts::none()
+        };
+
+        let in_flight = self.inner.request(req);
+
+        let acquiring = self
+            .inner
+            .host_limits
+            .as_ref()
+            .map(|host_limits| host_limits.acquire(&url));
 
         Pending {
             inner: PendingInner::Request(PendingRequest {
@@ -1059,6 +1974,7 @@ impl Client {
                 url,
                 headers,
                 body: reusable,
+                accepts,
 
                 urls: Vec::new(),
 
@@ -1066,35 +1982,111 @@ impl Client {
 
                 in_flight,
                 timeout,
+                connect_timeout,
+                connect_timeout_guard,
+
+                acquiring,
+                permit: None,
+
+                retries: 0,
+                backoff: None,
+                draining: None,
             }),
         }
     }
+}
 
-    fn proxy_auth(&self, dst: &Uri, headers: &mut HeaderMap) {
-        if !self.inner.proxies_maybe_http_auth {
-            return;
-        }
+impl Service<Request> for Client {
+    type Response = Response;
+    type Error = crate::Error;
+    type Future = super::request::ResponseFuture;
 
-        // Only set the header here if the destination scheme is 'http',
-        // since otherwise, the header will be included in the CONNECT tunnel
-        // request instead.
-        if dst.scheme() != Some(&Scheme::HTTP) {
-            return;
-        }
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
 
-        if headers.contains_key(PROXY_AUTHORIZATION) {
-            return;
-        }
+    fn call(&mut self, req: Request) -> Self::Future {
+        super::request::ResponseFuture::new(self.execute_request(req))
+    }
+}
 
-        for proxy in self.inner.proxies.iter() {
-            if proxy.is_match(dst) {
-                if let Some(header) = proxy.http_basic_auth(dst) {
-                    headers.insert(PROXY_AUTHORIZATION, header);
-                }
+/// A `tower::Service` speaking the `http` request and response types.
+///
+/// Created with [`Client::http_service`]. Each `http::Request<Body>` is
+/// converted into a [`Request`] before being executed; conversion failures
+/// are returned as the service's error.
+#[derive(Clone, Debug)]
+pub struct HttpService {
+    client: Client,
+}
 
-                break;
-            }
-        }
+impl Service<http::Request<Body>> for HttpService {
+    type Response = http::Response<Body>;
+    type Error = crate::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let pending = Request::try_from(req).map(|req| self.client.execute_request(req));
+        Box::pin(async move { Ok(pending?.await?.into()) })
+    }
+}
+
+/// The effective configuration of a built `Client`.
+///
+/// Returned by [`Client::config`].
+#[derive(Clone, Debug)]
+pub struct ClientConfig {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    max_redirects: Option<usize>,
+    has_proxy: bool,
+    tls_backend: Option<&'static str>,
+}
+
+impl ClientConfig {
+    /// The total timeout of each request, if any.
+    ///
+    /// Default is `None`.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// The timeout of the connect phase, if any.
+    ///
+    /// Default is `None`.
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// The maximum number of redirects followed.
+    ///
+    /// This is `Some(0)` if redirects aren't followed, and `None` for a
+    /// custom redirect policy. Default is `Some(10)`.
+    pub fn max_redirects(&self) -> Option<usize> {
+        self.max_redirects
+    }
+
+    /// Whether any proxy is configured, including one found in the
+    /// system's environment.
+    pub fn has_proxy(&self) -> bool {
+        self.has_proxy
+    }
+
+    /// The TLS backend in use, named after the crate feature selecting it
+    /// (such as `"default-tls"` or `"rustls-tls"`), or `None` if TLS isn't
+    /// enabled.
+    pub fn tls_backend(&self) -> Option<&'static str> {
+        self.tls_backend
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn with_timeout(mut self, timeout: Option<Duration>) -> ClientConfig {
+        self.timeout = timeout;
+        self
     }
 }
 
@@ -1124,6 +2116,10 @@ impl Config {
             if let Some(_) = self.cookie_store {
                 f.field("cookie_store", &true);
             }
+
+            if let Some(ref capacity) = self.cookie_store_capacity {
+                f.field("cookie_store_capacity", capacity);
+            }
         }
 
         f.field("accepts", &self.accepts);
@@ -1136,20 +2132,44 @@ impl Config {
             f.field("redirect_policy", &self.redirect_policy);
         }
 
+        if !self.retry_policy.is_default() {
+            f.field("retry_policy", &self.retry_policy);
+        }
+
         if self.referer {
             f.field("referer", &true);
         }
 
+        if self.referer_trim_query {
+            f.field("referer_trim_query", &true);
+        }
+
         f.field("default_headers", &self.headers);
 
+        if !self.merged_headers.is_empty() {
+            f.field("default_headers_merge", &self.merged_headers);
+        }
+
+        if self.default_user_agent {
+            f.field("default_user_agent", &true);
+        }
+
         if self.http1_title_case_headers {
             f.field("http1_title_case_headers", &true);
         }
 
+        if let Some(ref v) = self.http1_writev {
+            f.field("http1_writev", v);
+        }
+
         if self.http2_only {
             f.field("http2_prior_knowledge", &true);
         }
 
+        if self.http2_tls_only {
+            f.field("http2_prior_knowledge_https_only", &true);
+        }
+
         if let Some(ref d) = self.connect_timeout {
             f.field("connect_timeout", d);
         }
@@ -1158,15 +2178,51 @@ impl Config {
             f.field("timeout", d);
         }
 
-        if let Some(ref v) = self.local_address {
-            f.field("local_address", v);
+        if let Some(ref max) = self.max_response_size {
+            f.field("max_response_size", max);
+        }
+
+        if self.resume_downloads {
+            f.field("resume_downloads", &true);
+        }
+
+        if self.preserve_header_order {
+            f.field("preserve_header_order", &true);
+        }
+
+        if let Some(ref v) = self.concurrency_limit_per_host {
+            f.field("concurrency_limit_per_host", v);
+        }
+
+        if let Some(ref v) = self.connection_reuse_max_requests {
+            f.field("connection_reuse_max_requests", v);
+        }
+
+        if let Some(ref v) = self.local_address_ipv4 {
+            f.field("local_address_ipv4", v);
+        }
+
+        if let Some(ref v) = self.local_address_ipv6 {
+            f.field("local_address_ipv6", v);
         }
 
         if self.nodelay {
             f.field("tcp_nodelay", &true);
         }
 
-        #[cfg(feature = "native-tls")]
+        if let Some(ref d) = self.tcp_user_timeout {
+            f.field("tcp_user_timeout", d);
+        }
+
+        if !self.dns_overrides.is_empty() {
+            f.field("dns_overrides", &self.dns_overrides);
+        }
+
+        if !self.hosts_files.is_empty() {
+            f.field("hosts_files", &self.hosts_files);
+        }
+
+        #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
         {
             if !self.hostname_verification {
                 f.field("danger_accept_invalid_hostnames", &true);
@@ -1180,6 +2236,24 @@ impl Config {
             }
         }
 
+        #[cfg(feature = "rustls-tls")]
+        {
+            if !self.invalid_certs_hosts.is_empty() {
+                f.field("danger_accept_invalid_certs_for", &self.invalid_certs_hosts);
+            }
+        }
+
+        #[cfg(feature = "rustls-tls")]
+        {
+            if let Some(ref capacity) = self.tls_session_cache {
+                f.field("tls_session_cache", capacity);
+            }
+
+            if self.tls_early_data {
+                f.field("tls_early_data", &true);
+            }
+        }
+
         #[cfg(all(feature = "native-tls-crate", feature = "rustls-tls"))]
         {
             f.field("tls_backend", &self.tls);
@@ -1190,17 +2264,78 @@ impl Config {
 struct ClientRef {
     accepts: Accepts,
     #[cfg(feature = "cookies")]
-    cookie_store: Option<RwLock<cookie::CookieStore>>,
+    cookie_store: Option<Arc<RwLock<cookie::CookieStore>>>,
     headers: HeaderMap,
+    merged_headers: HeaderMap,
     hyper: HyperClient,
+    // Used for `GET` and `HEAD` requests when TLS early data is enabled.
+    hyper_early_data: Option<HyperClient>,
+    connector: Connector,
     redirect_policy: redirect::Policy,
+    retry_policy: retry::Policy,
     referer: bool,
+    referer_trim_query: bool,
     request_timeout: Option<Duration>,
+    max_response_size: Option<u64>,
+    resume_downloads: bool,
+    preserve_header_order: bool,
+    host_limits: Option<HostLimits>,
+    connection_verbose: bool,
     proxies: Arc<Vec<Proxy>>,
     proxies_maybe_http_auth: bool,
+    config: ClientConfig,
 }
 
 impl ClientRef {
+    /// Sends `req`, as TLS early data if enabled and its method allows it.
+    fn request(&self, req: hyper::Request<super::body::ImplStream>) -> ResponseFuture {
+        self.hyper_for(req.method()).request(req)
+    }
+
+    /// The hyper client for requests with `method`.
+    fn hyper_for(&self, method: &Method) -> &HyperClient {
+        match self.hyper_early_data {
+            Some(ref hyper) if *method == Method::GET || *method == Method::HEAD => hyper,
+            _ => &self.hyper,
+        }
+    }
+
+    /// The order of `headers`, if the client preserves it.
+    fn header_order(&self, headers: &HeaderMap) -> Option<Vec<HeaderName>> {
+        if self.preserve_header_order {
+            Some(headers.keys().cloned().collect())
+        } else {
+            None
+        }
+    }
+
+    fn proxy_auth(&self, dst: &Uri, headers: &mut HeaderMap) {
+        if !self.proxies_maybe_http_auth {
+            return;
+        }
+
+        // Only set the header here if the destination scheme is 'http',
+        // since otherwise, the header will be included in the CONNECT tunnel
+        // request instead.
+        if dst.scheme() != Some(&Scheme::HTTP) {
+            return;
+        }
+
+        if headers.contains_key(PROXY_AUTHORIZATION) {
+            return;
+        }
+
+        for proxy in self.proxies.iter() {
+            if proxy.is_match(dst) {
+                if let Some(header) = proxy.http_basic_auth(dst) {
+                    headers.insert(PROXY_AUTHORIZATION, header);
+                }
+
+                break;
+            }
+        }
+    }
+
     fn fmt_fields(&self, f: &mut fmt::DebugStruct<'_, '_>) {
         // Instead of deriving Debug, only print fields when their output
         // would provide relevant or interesting data.
@@ -1222,19 +2357,143 @@ impl ClientRef {
             f.field("redirect_policy", &self.redirect_policy);
         }
 
+        if !self.retry_policy.is_default() {
+            f.field("retry_policy", &self.retry_policy);
+        }
+
         if self.referer {
             f.field("referer", &true);
         }
 
+        if self.referer_trim_query {
+            f.field("referer_trim_query", &true);
+        }
+
         f.field("default_headers", &self.headers);
 
+        if !self.merged_headers.is_empty() {
+            f.field("default_headers_merge", &self.merged_headers);
+        }
+
         if let Some(ref d) = self.request_timeout {
             f.field("timeout", d);
         }
+
+        if let Some(ref max) = self.max_response_size {
+            f.field("max_response_size", max);
+        }
+
+        if self.resume_downloads {
+            f.field("resume_downloads", &true);
+        }
+
+        if self.preserve_header_order {
+            f.field("preserve_header_order", &true);
+        }
+
+        if let Some(ref host_limits) = self.host_limits {
+            f.field("concurrency_limit_per_host", &host_limits.limit);
+        }
     }
 }
 
-pub(super) struct Pending {
+/// Limits the requests in flight to each host, keyed by host and port.
+struct HostLimits {
+    limit: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+type AcquirePermit = Pin<Box<dyn Future<Output = OwnedSemaphorePermit> + Send>>;
+
+impl HostLimits {
+    fn new(limit: usize) -> HostLimits {
+        HostLimits {
+            limit,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn acquire(&self, url: &Url) -> AcquirePermit {
+        let host = url.host_str().unwrap_or("");
+        let key = format!("{}:{}", host, url.port_or_known_default().unwrap_or(0));
+
+        let mut hosts = self.hosts.lock().unwrap();
+        if !hosts.contains_key(&key) {
+            // Forget the hosts no request holds or waits for a permit of.
+            hosts.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+        let limit = self.limit;
+        let semaphore = hosts
+            .entry(key)
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone();
+        Box::pin(semaphore.acquire_owned())
+    }
+}
+
+/// A response body holding on to the permit of its host, if any, until it
+/// has been read to the end. The same goes for retiring its connection.
+struct HostPermit<B> {
+    body: B,
+    permit: Option<OwnedSemaphorePermit>,
+    retire: Option<RetireConn>,
+}
+
+impl<B: HttpBody> HostPermit<B> {
+    fn new(
+        body: B,
+        permit: Option<OwnedSemaphorePermit>,
+        retire: Option<RetireConn>,
+    ) -> HostPermit<B> {
+        if body.is_end_stream() {
+            return HostPermit {
+                body,
+                permit: None,
+                retire: None,
+            };
+        }
+        HostPermit {
+            body,
+            permit,
+            retire,
+        }
+    }
+}
+
+impl<B: HttpBody + Unpin> HttpBody for HostPermit<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let data = futures_core::ready!(Pin::new(&mut self.body).poll_data(cx));
+        if data.is_none() {
+            self.permit = None;
+            self.retire = None;
+        }
+        Poll::Ready(data)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.body).poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
+
+/// A `Future` that will resolve to a `Response`.
+pub struct Pending {
     inner: PendingInner,
 }
 
@@ -1247,7 +2506,8 @@ struct PendingRequest {
     method: Method,
     url: Url,
     headers: HeaderMap,
-    body: Option<Option<Bytes>>,
+    body: Option<Option<Body>>,
+    accepts: Accepts,
 
     urls: Vec<Url>,
 
@@ -1255,6 +2515,20 @@ struct PendingRequest {
 
     in_flight: ResponseFuture,
     timeout: Option<Delay>,
+    connect_timeout: Option<Duration>,
+    // Keeps `connect_timeout` applied to new connections to the current
+    // host while the request is pending.
+    connect_timeout_guard: Option<connect::ConnectTimeoutGuard>,
+
+    acquiring: Option<AcquirePermit>,
+    permit: Option<OwnedSemaphorePermit>,
+
+    // Retries made under the client's retry policy.
+    retries: usize,
+    backoff: Option<Delay>,
+    // The body of a response that is retried, read during the backoff so
+    // that its connection can be reused.
+    draining: Option<hyper::Body>,
 }
 
 impl PendingRequest {
@@ -1273,6 +2547,124 @@ impl PendingRequest {
     fn headers(self: Pin<&mut Self>) -> &mut HeaderMap {
         unsafe { &mut Pin::get_unchecked_mut(self).headers }
     }
+
+    fn acquiring(self: Pin<&mut Self>) -> &mut Option<AcquirePermit> {
+        unsafe { &mut Pin::get_unchecked_mut(self).acquiring }
+    }
+
+    fn permit(self: Pin<&mut Self>) -> &mut Option<OwnedSemaphorePermit> {
+        unsafe { &mut Pin::get_unchecked_mut(self).permit }
+    }
+
+    fn is_idempotent(&self) -> bool {
+        let idempotent = [
+            Method::GET,
+            Method::HEAD,
+            Method::PUT,
+            Method::DELETE,
+            Method::OPTIONS,
+            Method::TRACE,
+        ];
+        idempotent.contains(&self.method)
+    }
+
+    /// Whether the retry policy allows sending this request again at all.
+    fn may_retry(&self) -> bool {
+        if let Some(None) = self.body {
+            return false;
+        }
+        self.client.retry_policy.allows(self.is_idempotent())
+    }
+
+    fn retries_error(&self, err: &hyper::Error) -> bool {
+        let connect = err.is_connect();
+        let policy = &self.client.retry_policy;
+        self.may_retry()
+            && (connect || is_dead_connection(err))
+            && policy.retries_error(self.retries, connect)
+    }
+
+    fn retries_status(&self, status: StatusCode) -> bool {
+        let policy = &self.client.retry_policy;
+        self.may_retry() && policy.retries_status(self.retries, status)
+    }
+
+    /// Sends the request again once the policy's backoff has passed.
+    fn retry_after_backoff(&mut self) {
+        let delay = self.client.retry_policy.delay(self.retries);
+        self.retries += 1;
+        self.backoff = Some(tokio::time::delay_for(delay));
+    }
+
+    /// Reads the body of a discarded response to its end, if it's small
+    /// enough to be worth it. Its connection can then be reused.
+    fn drain(&mut self, body: hyper::Body) {
+        let small = match HttpBody::size_hint(&body).upper() {
+            Some(len) => len <= DRAIN_MAX_LEN,
+            None => false,
+        };
+        self.draining = if small { Some(body) } else { None };
+    }
+
+    fn poll_drain(&mut self, cx: &mut Context) {
+        loop {
+            let done = match self.draining {
+                Some(ref mut body) => match Pin::new(body).poll_data(cx) {
+                    Poll::Ready(Some(Ok(_))) => false,
+                    Poll::Ready(_) => true,
+                    Poll::Pending => return,
+                },
+                None => return,
+            };
+            if done {
+                self.draining = None;
+            }
+        }
+    }
+
+    /// Builds the request again, as it was last sent.
+    fn replay(&self) -> hyper::Request<super::body::ImplStream> {
+        let body = match self.body {
+            Some(Some(ref body)) => body.try_clone().expect("body is reusable"),
+            _ => Body::empty(),
+        };
+        let mut req = hyper::Request::builder()
+            .method(self.method.clone())
+            .uri(expect_uri(&self.url))
+            .body(body.into_stream())
+            .expect("valid request parts");
+        *req.headers_mut() = self.headers.clone();
+        req
+    }
+}
+
+/// The number of requests `Client::get_many` keeps in flight.
+const GET_MANY_LIMIT: usize = 32;
+
+/// The largest body of a retried response that is read to reuse its
+/// connection.
+const DRAIN_MAX_LEN: u64 = 64 * 1024;
+
+/// Whether `err` means the connection died before a response arrived,
+/// rather than that it couldn't be opened.
+fn is_dead_connection(err: &hyper::Error) -> bool {
+    use std::error::Error as _;
+
+    if err.is_connect() {
+        return false;
+    }
+    if err.is_incomplete_message() || err.is_closed() || err.is_canceled() {
+        return true;
+    }
+    let dead = [
+        std::io::ErrorKind::ConnectionReset,
+        std::io::ErrorKind::ConnectionAborted,
+        std::io::ErrorKind::BrokenPipe,
+    ];
+    match err.source().map(|e| e.downcast_ref::<std::io::Error>()) {
+        Some(Some(io)) => dead.contains(&io.kind()),
+        _ => false,
+    }
 }
 
 impl Pending {
@@ -1307,21 +2699,55 @@ impl Future for PendingRequest {
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if let Some(delay) = self.as_mut().timeout().as_mut().as_pin_mut() {
             if let Poll::Ready(()) = delay.poll(cx) {
-                return Poll::Ready(Err(
-                    crate::error::request(crate::error::TimedOut).with_url(self.url.clone())
-                ));
+                let err = crate::error::TimedOut(TimeoutKind::Overall);
+                return Poll::Ready(Err(crate::error::request(err).with_url(self.url.clone())));
             }
         }
 
         loop {
-            let res = match self.as_mut().in_flight().as_mut().poll(cx) {
+            if let Some(acquiring) = self.as_mut().acquiring() {
+                let permit = futures_core::ready!(acquiring.as_mut().poll(cx));
+                *self.as_mut().permit() = Some(permit);
+                *self.as_mut().acquiring() = None;
+            }
+
+            if self.backoff.is_some() {
+                self.poll_drain(cx);
+                let backoff = self.backoff.as_mut().expect("backoff");
+                futures_core::ready!(Pin::new(backoff).poll(cx));
+                self.backoff = None;
+                // A body not read by now loses its connection.
+                self.draining = None;
+                let req = self.replay();
+                *self.as_mut().in_flight().get_mut() = self.client.request(req);
+            }
+
+            let mut res = match self.as_mut().in_flight().as_mut().poll(cx) {
                 Poll::Ready(Err(e)) => {
+                    if self.retries_error(&e) {
+                        debug!("retrying {} after error: {}", self.url, e);
+                        self.retry_after_backoff();
+                        continue;
+                    }
                     return Poll::Ready(Err(crate::error::request(e).with_url(self.url.clone())));
                 }
                 Poll::Ready(Ok(res)) => res,
                 Poll::Pending => return Poll::Pending,
             };
 
+            // The connection of a successful `CONNECT` or a `101` response
+            // is taken over by the upgrade, so its body is left empty.
+            let is_upgrade = res.status() == StatusCode::SWITCHING_PROTOCOLS
+                || (self.method == Method::CONNECT && res.status().is_success());
+            // Count the response towards `connection_reuse_max_requests`.
+            // Its connection is retired once the guard is dropped, which for
+            // redirects and retries is right away.
+            let counted = !is_upgrade && res.version() != Version::HTTP_2;
+            let retire = match res.extensions_mut().remove::<ConnReuse>() {
+                Some(reuse) if counted => reuse.served(),
+                _ => None,
+            };
+
             #[cfg(feature = "cookies")]
             {
                 if let Some(store_wrapper) = self.client.cookie_store.as_ref() {
@@ -1331,12 +2757,21 @@ impl Future for PendingRequest {
                         .peekable();
                     if cookies.peek().is_some() {
                       let mut store = store_wrapper.write().unwrap();
-                      store.0.store_response_cookies(cookies, &self.url);
+                      store.store_response_cookies(cookies, &self.url);
                     }
                 }
             }
+            if self.retries_status(res.status()) {
+                debug!("retrying {} after status {}", self.url, res.status());
+                self.retry_after_backoff();
+                self.drain(res.into_body());
+                continue;
+            }
+            let keep_method = self.client.redirect_policy.keeps_method(res.status());
             let should_redirect = match res.status() {
-                StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER => {
+                StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER
+                    if !keep_method =>
+                {
                     self.body = None;
                     for header in &[
                         TRANSFER_ENCODING,
@@ -1355,12 +2790,14 @@ impl Future for PendingRequest {
                     }
                     true
                 }
-                StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {
-                    match self.body {
-                        Some(Some(_)) | None => true,
-                        Some(None) => false,
-                    }
-                }
+                StatusCode::MOVED_PERMANENTLY
+                | StatusCode::FOUND
+                | StatusCode::SEE_OTHER
+                | StatusCode::TEMPORARY_REDIRECT
+                | StatusCode::PERMANENT_REDIRECT => match self.body {
+                    Some(Some(_)) | None => true,
+                    Some(None) => false,
+                },
                 _ => false,
             };
             if should_redirect {
@@ -1369,7 +2806,13 @@ impl Future for PendingRequest {
                         // Some sites may send a utf-8 Location header,
                         // even though we're supposed to treat those bytes
                         // as opaque, we'll check specifically for utf8.
-                        self.url.join(str::from_utf8(val.as_bytes()).ok()?).ok()
+                        let mut loc = self.url.join(str::from_utf8(val.as_bytes()).ok()?).ok()?;
+                        // A `Location` without a fragment inherits the
+                        // fragment of the request, as the Fetch spec says.
+                        if loc.fragment().is_none() {
+                            loc.set_fragment(self.url.fragment());
+                        }
+                        Some(loc)
                     })();
 
                     // Check that the `url` is also a valid `http::Uri`.
@@ -1390,7 +2833,8 @@ impl Future for PendingRequest {
                 });
                 if let Some(loc) = loc {
                     if self.client.referer {
-                        if let Some(referer) = make_referer(&loc, &self.url) {
+                        let trim_query = self.client.referer_trim_query;
+                        if let Some(referer) = make_referer(&loc, &self.url, trim_query) {
                             self.headers.insert(REFERER, referer);
                         }
                     }
@@ -1408,11 +2852,13 @@ impl Future for PendingRequest {
 
                             let mut headers =
                                 std::mem::replace(self.as_mut().headers(), HeaderMap::new());
+                            let order = self.client.header_order(&headers);
 
                             remove_sensitive_headers(&mut headers, &self.url, &self.urls);
                             let uri = expect_uri(&self.url);
+                            self.client.proxy_auth(&uri, &mut headers);
                             let body = match self.body {
-                                Some(Some(ref body)) => Body::reusable(body.clone()),
+                                Some(Some(ref body)) => body.try_clone().expect("body is reusable"),
                                 _ => Body::empty(),
                             };
                             let mut req = hyper::Request::builder()
@@ -1432,9 +2878,25 @@ impl Future for PendingRequest {
                                 }
                             }
 
+                            let mut headers = restore_header_order(order, headers);
                             *req.headers_mut() = headers.clone();
                             std::mem::swap(self.as_mut().headers(), &mut headers);
-                            *self.as_mut().in_flight().get_mut() = self.client.hyper.request(req);
+                            *self.as_mut().in_flight().get_mut() = self.client.request(req);
+                            // New connections to the next host keep using
+                            // the request's connect timeout.
+                            if let Some(timeout) = self.connect_timeout {
+                                let guard =
+                                    self.client.connector.request_connect_timeout(&uri, timeout);
+                                self.connect_timeout_guard = Some(guard);
+                            }
+                            // The new location may be another host.
+                            *self.as_mut().permit() = None;
+                            let acquiring = self
+                                .client
+                                .host_limits
+                                .as_ref()
+                                .map(|host_limits| host_limits.acquire(&self.url));
+                            *self.as_mut().acquiring() = acquiring;
                             continue;
                         }
                         redirect::ActionKind::Stop => {
@@ -1448,11 +2910,45 @@ impl Future for PendingRequest {
             }
 
             debug!("response '{}' for {}", res.status(), self.url);
+            if self.client.connection_verbose {
+                debug!("negotiated {:?} for {}", res.version(), self.url);
+            }
+            // Responses to `HEAD`, and `204` and `304` responses, never have
+            // a body, whatever their `Content-Length` says.
+            let is_bodiless = self.method == Method::HEAD
+                || res.status() == StatusCode::NO_CONTENT
+                || res.status() == StatusCode::NOT_MODIFIED;
+            let res = if is_upgrade {
+                let (mut parts, body) = res.into_parts();
+                parts.extensions.insert(PendingUpgrade(body.on_upgrade()));
+                hyper::Response::from_parts(parts, hyper::Body::empty())
+            } else if is_bodiless {
+                let (parts, _) = res.into_parts();
+                hyper::Response::from_parts(parts, hyper::Body::empty())
+            } else {
+                res
+            };
+            let resumable = self.client.resume_downloads
+                && !is_upgrade
+                && !is_bodiless
+                && Resume::is_resumable(&self.method, &res);
+            let timeout = self.timeout.take();
+            // The host permit is only released once the body is done.
+            let permit = self.as_mut().permit().take();
+            let (parts, body) = res.into_parts();
+            let body = if resumable {
+                let client = &self.client.hyper;
+                let body = Resume::new(client, &self.url, &self.headers, &parts.headers, body);
+                Body::response(HostPermit::new(body, permit, retire), timeout)
+            } else {
+                Body::response(HostPermit::new(body, permit, retire), timeout)
+            };
+            let res = hyper::Response::from_parts(parts, body);
             let res = Response::new(
                 res,
                 self.url.clone(),
-                self.client.accepts,
-                self.timeout.take(),
+                self.accepts,
+                self.client.max_response_size,
             );
             return Poll::Ready(Ok(res));
         }
@@ -1472,7 +2968,33 @@ impl fmt::Debug for Pending {
     }
 }
 
-fn make_referer(next: &Url, previous: &Url) -> Option<HeaderValue> {
+/// Rebuilds `headers` with the names in `order` first, in that order, and
+/// the rest after them.
+fn restore_header_order(order: Option<Vec<HeaderName>>, headers: HeaderMap) -> HeaderMap {
+    let order = match order {
+        Some(order) => order,
+        None => return headers,
+    };
+
+    let mut ordered = HeaderMap::with_capacity(headers.len());
+    let rest = headers.keys().filter(|name| !order.contains(name));
+    for name in order.iter().chain(rest) {
+        for value in headers.get_all(name) {
+            ordered.append(name.clone(), value.clone());
+        }
+    }
+    ordered
+}
+
+fn default_header_value(key: &HeaderName, value: &HeaderValue) -> HeaderValue {
+    let mut value = value.clone();
+    if key == AUTHORIZATION || key == COOKIE || key == PROXY_AUTHORIZATION {
+        value.set_sensitive(true);
+    }
+    value
+}
+
+fn make_referer(next: &Url, previous: &Url, trim_query: bool) -> Option<HeaderValue> {
     if next.scheme() == "http" && previous.scheme() == "https" {
         return None;
     }
@@ -1481,14 +3003,17 @@ fn make_referer(next: &Url, previous: &Url) -> Option<HeaderValue> {
     let _ = referer.set_username("");
     let _ = referer.set_password(None);
     referer.set_fragment(None);
+    if trim_query {
+        referer.set_query(None);
+    }
     referer.as_str().parse().ok()
 }
 
 #[cfg(feature = "cookies")]
 fn add_cookie_header(headers: &mut HeaderMap, cookie_store: &cookie::CookieStore, url: &Url) {
     let header = cookie_store
-        .0
-        .get_request_cookies(url)
+        .request_cookies(url)
+        .into_iter()
         .map(|c| format!("{}={}", c.name(), c.value()))
         .collect::<Vec<_>>()
         .join("; ");
@@ -1502,6 +3027,78 @@ fn add_cookie_header(headers: &mut HeaderMap, cookie_store: &cookie::CookieStore
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn tls_early_data_only_for_safe_methods() {
+        use http::Method;
+
+        let client = crate::Client::builder()
+            .use_rustls_tls()
+            .tls_early_data(true)
+            .build()
+            .unwrap();
+        let inner = &client.inner;
+        let early = inner.hyper_early_data.as_ref().expect("early data client");
+
+        assert!(std::ptr::eq(inner.hyper_for(&Method::GET), early));
+        assert!(std::ptr::eq(inner.hyper_for(&Method::HEAD), early));
+        assert!(std::ptr::eq(inner.hyper_for(&Method::POST), &inner.hyper));
+        assert!(std::ptr::eq(inner.hyper_for(&Method::PUT), &inner.hyper));
+
+        let client = crate::Client::builder().use_rustls_tls().build().unwrap();
+        let inner = &client.inner;
+        assert!(inner.hyper_early_data.is_none());
+        assert!(std::ptr::eq(inner.hyper_for(&Method::GET), &inner.hyper));
+    }
+
+    #[test]
+    fn default_headers_credentials_are_sensitive() {
+        use http::header::{HeaderMap, AUTHORIZATION, COOKIE, USER_AGENT};
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, "Bearer secret".parse().unwrap());
+        headers.insert(USER_AGENT, "test".parse().unwrap());
+        let mut merged = HeaderMap::new();
+        merged.insert(COOKIE, "session=secret".parse().unwrap());
+
+        let client = crate::Client::builder()
+            .default_headers(headers)
+            .default_headers_merge(merged)
+            .build()
+            .unwrap();
+        assert!(client.inner.headers[AUTHORIZATION].is_sensitive());
+        assert!(!client.inner.headers[USER_AGENT].is_sensitive());
+        assert!(client.inner.merged_headers[COOKIE].is_sensitive());
+        assert!(!format!("{:?}", client).contains("secret"));
+    }
+
+    #[test]
+    fn client_config() {
+        use std::time::Duration;
+
+        let client = crate::Client::builder().no_proxy().build().unwrap();
+        let config = client.config();
+        assert_eq!(config.timeout(), None);
+        assert_eq!(config.connect_timeout(), None);
+        assert_eq!(config.max_redirects(), Some(10));
+        assert!(!config.has_proxy());
+        #[cfg(feature = "default-tls")]
+        assert_eq!(config.tls_backend(), Some("default-tls"));
+
+        let client = crate::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .connect_timeout(Duration::from_secs(1))
+            .redirect(crate::redirect::Policy::none())
+            .proxy(crate::Proxy::http("http://localhost:8080").unwrap())
+            .build()
+            .unwrap();
+        let config = client.config();
+        assert_eq!(config.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(1)));
+        assert_eq!(config.max_redirects(), Some(0));
+        assert!(config.has_proxy());
+    }
+
     #[tokio::test]
     async fn execute_request_rejects_invald_urls() {
         let url_str = "hxxps://www.rust-lang.org/";
@@ -1513,4 +3110,72 @@ mod tests {
         assert!(err.is_builder());
         assert_eq!(url_str, err.url().unwrap().as_str());
     }
+
+    #[test]
+    fn system_proxy_toggle() {
+        let proxy = || crate::Proxy::http("http://my.prox").unwrap();
+
+        let client = crate::Client::builder().build().unwrap();
+        assert_eq!(client.inner.proxies.len(), 1);
+
+        let client = crate::Client::builder().system_proxy(false).build().unwrap();
+        assert!(client.inner.proxies.is_empty());
+
+        let client = crate::Client::builder().proxy(proxy()).build().unwrap();
+        assert_eq!(client.inner.proxies.len(), 1);
+
+        let client = crate::Client::builder()
+            .proxy(proxy())
+            .system_proxy(true)
+            .build()
+            .unwrap();
+        assert_eq!(client.inner.proxies.len(), 2);
+
+        let client = crate::Client::builder()
+            .system_proxy(true)
+            .no_proxy()
+            .build()
+            .unwrap();
+        assert!(client.inner.proxies.is_empty());
+    }
+
+    #[test]
+    fn zero_concurrency_limit_per_host() {
+        let err = crate::Client::builder()
+            .concurrency_limit_per_host(0)
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+
+    #[test]
+    fn zero_connection_reuse_max_requests() {
+        let err = crate::Client::builder()
+            .connection_reuse_max_requests(0)
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+
+    #[cfg(feature = "native-tls")]
+    #[test]
+    fn same_tls_backend_selected_twice() {
+        let tls = native_tls_crate::TlsConnector::new().unwrap();
+        crate::Client::builder()
+            .use_native_tls()
+            .use_preconfigured_tls(tls)
+            .build()
+            .expect("same backend is not a conflict");
+    }
+
+    #[cfg(all(feature = "native-tls", feature = "rustls-tls"))]
+    #[test]
+    fn conflicting_tls_backends() {
+        let err = crate::Client::builder()
+            .use_native_tls()
+            .use_rustls_tls()
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
 }
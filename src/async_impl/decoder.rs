@@ -24,6 +24,19 @@ pub(super) struct Accepts {
     pub(super) gzip: bool,
     #[cfg(feature = "brotli")]
     pub(super) brotli: bool,
+    /// Whether `br` is advertised ahead of `gzip` in `Accept-Encoding`.
+    pub(super) prefer_brotli: bool,
+}
+
+/// A content coding that can be advertised in the `Accept-Encoding` header.
+///
+/// See [`ClientBuilder::accept_encoding_priority`](crate::ClientBuilder::accept_encoding_priority).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// The `gzip` coding.
+    Gzip,
+    /// The `br` (brotli) coding.
+    Brotli,
 }
 
 /// A response decompressor over a non-blocking stream of chunks.
@@ -31,6 +44,13 @@ pub(super) struct Accepts {
 /// The inner decoder may be constructed asynchronously.
 pub(crate) struct Decoder {
     inner: Inner,
+    limit: Option<Limit>,
+}
+
+/// Tracks the decoded length of a body against a maximum size.
+struct Limit {
+    max: u64,
+    read: u64,
 }
 
 enum Inner {
@@ -48,6 +68,10 @@ enum Inner {
     /// A decoder that doesn't have a value yet.
     #[cfg(any(feature = "brotli", feature = "gzip"))]
     Pending(Pending),
+
+    /// A decoder for content it cannot decode, yielding the error once.
+    #[cfg(any(feature = "brotli", feature = "gzip"))]
+    Error(Option<error::Error>),
 }
 
 /// A future attempt to poll the response body for EOF so we know whether to use gzip or not.
@@ -55,6 +79,7 @@ struct Pending(Peekable<IoStream>, DecoderType);
 
 struct IoStream(super::body::ImplStream);
 
+#[derive(Clone, Copy)]
 enum DecoderType {
     #[cfg(feature = "gzip")]
     Gzip,
@@ -73,6 +98,7 @@ impl Decoder {
     pub(crate) fn empty() -> Decoder {
         Decoder {
             inner: Inner::PlainText(Body::empty().into_stream()),
+            limit: None,
         }
     }
 
@@ -82,6 +108,7 @@ impl Decoder {
     fn plain_text(body: Body) -> Decoder {
         Decoder {
             inner: Inner::PlainText(body.into_stream()),
+            limit: None,
         }
     }
 
@@ -97,6 +124,7 @@ impl Decoder {
                 IoStream(body.into_stream()).peekable(),
                 DecoderType::Gzip,
             )),
+            limit: None,
         }
     }
 
@@ -112,9 +140,78 @@ impl Decoder {
                 IoStream(body.into_stream()).peekable(),
                 DecoderType::Brotli,
             )),
+            limit: None,
         }
     }
 
+    #[cfg(any(feature = "brotli", feature = "gzip"))]
+    fn of_type(decoder_type: DecoderType, body: Body) -> Decoder {
+        match decoder_type {
+            #[cfg(feature = "gzip")]
+            DecoderType::Gzip => Decoder::gzip(body),
+            #[cfg(feature = "brotli")]
+            DecoderType::Brotli => Decoder::brotli(body),
+        }
+    }
+
+    /// Detects a `Content-Encoding` with more than one coding applied, such
+    /// as `gzip, br`.
+    ///
+    /// Returns the decoders to apply, in the order the codings were applied,
+    /// or the name of a coding that cannot be decoded. Returns `None` if the
+    /// content isn't encoded more than once, or if none of the codings are
+    /// enabled, in which case the body is left as is.
+    #[cfg(any(feature = "brotli", feature = "gzip"))]
+    fn detect_stacked(
+        headers: &mut HeaderMap,
+        accepts: Accepts,
+    ) -> Option<Result<Vec<DecoderType>, String>> {
+        use http::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+
+        let codings = headers
+            .get_all(CONTENT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|coding| coding.trim())
+            .filter(|coding| !coding.is_empty() && !coding.eq_ignore_ascii_case("identity"))
+            .collect::<Vec<_>>();
+
+        if codings.len() < 2 {
+            return None;
+        }
+
+        let types = codings
+            .iter()
+            .map(|coding| match *coding {
+                #[cfg(feature = "gzip")]
+                "gzip" if accepts.gzip => Some(DecoderType::Gzip),
+                #[cfg(feature = "brotli")]
+                "br" if accepts.brotli => Some(DecoderType::Brotli),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if types.iter().all(Option::is_none) {
+            return None;
+        }
+
+        if let Some(content_length) = headers.get(CONTENT_LENGTH) {
+            if content_length == "0" {
+                log::warn!("encoded response with content-length of 0");
+                return None;
+            }
+        }
+
+        if let Some(pos) = types.iter().position(Option::is_none) {
+            return Some(Err(codings[pos].to_owned()));
+        }
+
+        headers.remove(CONTENT_ENCODING);
+        headers.remove(CONTENT_LENGTH);
+        Some(Ok(types.into_iter().flatten().collect()))
+    }
+
     #[cfg(feature = "gzip")]
     fn detect_gzip(headers: &mut HeaderMap) -> bool {
         use http::header::{CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING};
@@ -190,6 +287,31 @@ impl Decoder {
         body: Body,
         _accepts: Accepts,
     ) -> Decoder {
+        #[cfg(any(feature = "brotli", feature = "gzip"))]
+        {
+            match Decoder::detect_stacked(_headers, _accepts) {
+                // Decode the last applied coding first.
+                Some(Ok(types)) => {
+                    let mut types = types.into_iter().rev();
+                    let last = types.next().expect("stacked encoding");
+                    return types.fold(Decoder::of_type(last, body), |decoder, decoder_type| {
+                        Decoder::of_type(decoder_type, Body::stream(decoder))
+                    });
+                }
+                Some(Err(coding)) => {
+                    let err = crate::error::decode(format!(
+                        "unsupported content-encoding in stack: {}",
+                        coding
+                    ));
+                    return Decoder {
+                        inner: Inner::Error(Some(err)),
+                        limit: None,
+                    };
+                }
+                None => (),
+            }
+        }
+
         #[cfg(feature = "gzip")]
         {
             if _accepts.gzip && Decoder::detect_gzip(_headers) {
@@ -206,19 +328,24 @@ impl Decoder {
 
         Decoder::plain_text(body)
     }
-}
 
-impl Stream for Decoder {
-    type Item = Result<Bytes, error::Error>;
+    /// Limit the number of decoded bytes this decoder will yield.
+    ///
+    /// Once more than `max` bytes have been decoded, the stream yields a
+    /// "body too large" error.
+    pub(super) fn max_size(mut self, max: Option<u64>) -> Decoder {
+        self.limit = max.map(|max| Limit { max, read: 0 });
+        self
+    }
 
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+    fn poll_decoded(&mut self, cx: &mut Context) -> Poll<Option<Result<Bytes, error::Error>>> {
         // Do a read or poll for a pending decoder value.
         match self.inner {
             #[cfg(any(feature = "brotli", feature = "gzip"))]
             Inner::Pending(ref mut future) => match Pin::new(future).poll(cx) {
                 Poll::Ready(Ok(inner)) => {
                     self.inner = inner;
-                    return self.poll_next(cx);
+                    return self.poll_decoded(cx);
                 }
                 Poll::Ready(Err(e)) => {
                     return Poll::Ready(Some(Err(crate::error::decode_io(e))));
@@ -226,6 +353,8 @@ impl Stream for Decoder {
                 Poll::Pending => return Poll::Pending,
             },
             Inner::PlainText(ref mut body) => Pin::new(body).poll_next(cx),
+            #[cfg(any(feature = "brotli", feature = "gzip"))]
+            Inner::Error(ref mut err) => Poll::Ready(err.take().map(Err)),
             #[cfg(feature = "gzip")]
             Inner::Gzip(ref mut decoder) => {
                 return match futures_core::ready!(Pin::new(decoder).poll_next(cx)) {
@@ -246,6 +375,23 @@ impl Stream for Decoder {
     }
 }
 
+impl Stream for Decoder {
+    type Item = Result<Bytes, error::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let item = futures_core::ready!(self.poll_decoded(cx));
+
+        if let (Some(limit), Some(Ok(ref chunk))) = (self.limit.as_mut(), &item) {
+            limit.read += chunk.len() as u64;
+            if limit.read > limit.max {
+                return Poll::Ready(Some(Err(crate::error::body_too_large(limit.max))));
+            }
+        }
+
+        Poll::Ready(item)
+    }
+}
+
 impl HttpBody for Decoder {
     type Data = Bytes;
     type Error = crate::Error;
@@ -258,10 +404,25 @@ impl HttpBody for Decoder {
     }
 
     fn poll_trailers(
-        self: Pin<&mut Self>,
-        _cx: &mut Context,
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
     ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
-        Poll::Ready(Ok(None))
+        // Trailers come from the undecoded body, after all of it was read.
+        match self.inner {
+            Inner::PlainText(ref mut body) => Pin::new(body).poll_trailers(cx),
+            #[cfg(feature = "gzip")]
+            #[allow(deprecated)]
+            Inner::Gzip(ref mut decoder) => {
+                Pin::new(&mut decoder.get_mut().get_mut().0).poll_trailers(cx)
+            }
+            #[cfg(feature = "brotli")]
+            #[allow(deprecated)]
+            Inner::Brotli(ref mut decoder) => {
+                Pin::new(&mut decoder.get_mut().get_mut().0).poll_trailers(cx)
+            }
+            #[cfg(any(feature = "brotli", feature = "gzip"))]
+            Inner::Pending(_) | Inner::Error(_) => Poll::Ready(Ok(None)),
+        }
     }
 
     fn size_hint(&self) -> http_body::SizeHint {
@@ -330,11 +491,23 @@ impl Accepts {
             gzip: false,
             #[cfg(feature = "brotli")]
             brotli: false,
+            prefer_brotli: false,
         }
     }
 
+    pub(super) fn set_priority(&mut self, order: &[ContentEncoding]) {
+        let gzip = order.iter().position(|&enc| enc == ContentEncoding::Gzip);
+        let brotli = order.iter().position(|&enc| enc == ContentEncoding::Brotli);
+        self.prefer_brotli = match (gzip, brotli) {
+            (Some(gzip), Some(brotli)) => brotli < gzip,
+            (None, Some(_)) => true,
+            _ => false,
+        };
+    }
+
     pub(super) fn as_str(&self) -> Option<&'static str> {
         match (self.is_gzip(), self.is_brotli()) {
+            (true, true) if self.prefer_brotli => Some("br, gzip"),
             (true, true) => Some("gzip, br"),
             (true, false) => Some("gzip"),
             (false, true) => Some("br"),
@@ -374,6 +547,7 @@ impl Default for Accepts {
             gzip: true,
             #[cfg(feature = "brotli")]
             brotli: true,
+            prefer_brotli: false,
         }
     }
 }
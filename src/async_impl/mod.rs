@@ -1,7 +1,9 @@
 pub use self::body::Body;
-pub use self::client::{Client, ClientBuilder};
-pub use self::request::{Request, RequestBuilder};
+pub use self::client::{Client, ClientBuilder, ClientConfig, HttpService};
+pub use self::decoder::ContentEncoding;
+pub use self::request::{Request, RequestBuilder, RequestHead, ResponseFuture};
 pub use self::response::{Response, ResponseBuilderExt};
+pub use self::upgrade::Upgraded;
 
 #[cfg(feature = "blocking")]
 pub(crate) use self::decoder::Decoder;
@@ -12,3 +14,5 @@ pub mod decoder;
 pub mod multipart;
 pub(crate) mod request;
 mod response;
+mod resume;
+mod upgrade;
@@ -1,6 +1,10 @@
 //! multipart/form-data
 use std::borrow::Cow;
 use std::fmt;
+#[cfg(feature = "stream")]
+use std::io;
+#[cfg(feature = "stream")]
+use std::path::Path;
 use std::pin::Pin;
 
 use bytes::{Bytes};
@@ -22,6 +26,7 @@ pub struct Form {
 pub struct Part {
     meta: PartMetadata,
     value: Body,
+    body_length: Option<u64>,
 }
 
 pub(crate) struct FormParts<P> {
@@ -29,6 +34,7 @@ pub(crate) struct FormParts<P> {
     pub(crate) computed_headers: Vec<Vec<u8>>,
     pub(crate) fields: Vec<(Cow<'static, str>, P)>,
     pub(crate) percent_encoding: PercentEncoding,
+    pub(crate) max_in_memory: Option<u64>,
 }
 
 pub(crate) struct PartMetadata {
@@ -39,6 +45,7 @@ pub(crate) struct PartMetadata {
 
 pub(crate) trait PartProps {
     fn value_len(&self) -> Option<u64>;
+    fn value_bytes(&self) -> Option<&[u8]>;
     fn metadata(&self) -> &PartMetadata;
 }
 
@@ -64,6 +71,39 @@ impl Form {
         self.inner.boundary()
     }
 
+    /// Returns whether any part of this form is a stream.
+    ///
+    /// A request with a streaming form is sent without a `Content-Length`
+    /// unless every stream has a known length, and it can't be cloned, so
+    /// it won't be retried or repeated on redirects. A form of only text
+    /// and bytes parts is buffered and doesn't have these limitations,
+    /// unless a part's body has callbacks added with
+    /// [`Body::inspect_cloneable`](crate::Body::inspect_cloneable).
+    pub fn is_streaming(&self) -> bool {
+        self.inner.is_streaming()
+            || self
+                .inner
+                .fields
+                .iter()
+                .any(|(_, part)| part.value.is_inspected())
+    }
+
+    /// Use a fixed boundary instead of the randomly generated one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the boundary is not 1 to 70 characters allowed by RFC 2046,
+    /// or if it appears in the value of a part already added to the form.
+    /// Parts whose value is a stream or reader cannot be checked.
+    pub fn with_boundary<T>(self, boundary: T) -> crate::Result<Form>
+    where
+        T: Into<String>,
+    {
+        Ok(Form {
+            inner: self.inner.with_boundary(boundary.into())?,
+        })
+    }
+
     /// Add a data field with supplied name and value.
     ///
     /// # Examples
@@ -81,6 +121,65 @@ impl Form {
         self.part(name, Part::text(value))
     }
 
+    /// Add a data field with supplied name, value and mime type.
+    ///
+    /// This is useful for fields that must declare their content type, such
+    /// as `text/plain; charset=utf-8` or `application/json`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `mime` can't be parsed as a mime type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let form = reqwest::multipart::Form::new()
+    ///     .text("username", "seanmonstar")
+    ///     .text_with_mime("profile", r#"{"age":30}"#, "application/json")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn text_with_mime<T, U>(self, name: T, value: U, mime: &str) -> crate::Result<Form>
+    where
+        T: Into<Cow<'static, str>>,
+        U: Into<Cow<'static, str>>,
+    {
+        Ok(self.part(name, Part::text(value).mime_str(mime)?))
+    }
+
+    /// Adds a file field.
+    ///
+    /// The path will be used to try to guess the filename and mime, and the
+    /// file's size is read so the form can be sent with a `Content-Length`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn run() -> std::io::Result<()> {
+    /// let form = reqwest::multipart::Form::new()
+    ///     .file("key", "/path/to/file")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Errors when the file cannot be opened.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    pub async fn file<T, U>(self, name: T, path: U) -> io::Result<Form>
+    where
+        T: Into<Cow<'static, str>>,
+        U: AsRef<Path>,
+    {
+        Ok(self.part(name, Part::file(path).await?))
+    }
+
     /// Adds a customized Part.
     pub fn part<T>(self, name: T, part: Part) -> Form
     where
@@ -104,12 +203,35 @@ impl Form {
         self.with_inner(|inner| inner.percent_encode_noop())
     }
 
+    /// Limit the total length of the text and bytes parts of this `Form`.
+    ///
+    /// These parts are held in memory, and a form of only such parts is
+    /// copied into a single buffer when it's sent. If their total length
+    /// exceeds `bytes`, [`RequestBuilder::multipart`] fails with a builder
+    /// error rather than building the body. Stream parts aren't counted.
+    ///
+    /// By default, there is no limit.
+    ///
+    /// [`RequestBuilder::multipart`]: crate::RequestBuilder::multipart
+    pub fn max_in_memory(self, bytes: usize) -> Form {
+        self.with_inner(|inner| inner.max_in_memory(bytes as u64))
+    }
+
+    /// Fails if the parts held in memory exceed `max_in_memory`.
+    pub(crate) fn check_in_memory(&self) -> crate::Result<()> {
+        self.inner.check_in_memory()
+    }
+
     /// Consume this instance and transform into an instance of Body for use in a request.
     pub(crate) fn stream(mut self) -> Body {
         if self.inner.fields.is_empty() {
             return Body::empty();
         }
 
+        if !self.is_streaming() {
+            return Body::reusable(self.buffer());
+        }
+
         // create initial part to init reduce chain
         let (name, part) = self.inner.fields.remove(0);
         let start = Box::pin(self.part_stream(name, part))
@@ -129,6 +251,25 @@ impl Form {
         Body::stream(stream.chain(last))
     }
 
+    /// Write the whole form into a single buffer, when no part is a stream.
+    fn buffer(&mut self) -> Bytes {
+        let mut buf = Vec::new();
+        for (name, part) in self.inner.take_fields() {
+            let value = part.value.as_bytes().expect("form is not streaming");
+            buf.extend_from_slice(format!("--{}\r\n", self.boundary()).as_bytes());
+            buf.extend(
+                self.inner
+                    .percent_encoding
+                    .encode_headers(&name, &part.meta),
+            );
+            buf.extend_from_slice(b"\r\n\r\n");
+            buf.extend_from_slice(value);
+            buf.extend_from_slice(b"\r\n");
+        }
+        buf.extend_from_slice(format!("--{}--\r\n", self.boundary()).as_bytes());
+        buf.into()
+    }
+
     /// Generate a hyper::Body stream for a single Part instance of a Form request.
     pub(crate) fn part_stream<T>(
         &mut self,
@@ -210,10 +351,53 @@ impl Part {
         Part::new(value.into())
     }
 
+    /// Makes a new parameter from an arbitrary stream with a known length.
+    ///
+    /// The length is used to compute the `Content-Length` of the form, and
+    /// must match the number of bytes the stream yields.
+    pub fn stream_with_length<T: Into<Body>>(value: T, length: u64) -> Part {
+        let mut part = Part::new(value.into());
+        part.body_length = Some(length);
+        part
+    }
+
+    /// Makes a file parameter.
+    ///
+    /// The file is streamed when the request is sent, and its size is read
+    /// up front so the form length stays known.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the file cannot be opened.
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    pub async fn file<T: AsRef<Path>>(path: T) -> io::Result<Part> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|filename| filename.to_string_lossy().into_owned());
+        let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let mime = mime_guess::from_ext(ext).first_or_octet_stream();
+        let file = tokio::fs::File::open(path).await?;
+        let length = file.metadata().await?.len();
+        let body = Body::stream(FileStream::new(file));
+        let field = Part::stream_with_length(body, length).mime(mime);
+
+        Ok(if let Some(file_name) = file_name {
+            field.file_name(file_name)
+        } else {
+            field
+        })
+    }
+
     fn new(value: Body) -> Part {
         Part {
             meta: PartMetadata::new(),
             value,
+            body_length: None,
         }
     }
 
@@ -242,6 +426,7 @@ impl Part {
         Part {
             meta: func(self.meta),
             value: self.value,
+            body_length: self.body_length,
         }
     }
 }
@@ -257,7 +442,11 @@ impl fmt::Debug for Part {
 
 impl PartProps for Part {
     fn value_len(&self) -> Option<u64> {
-        self.value.content_length()
+        self.body_length.or_else(|| self.value.content_length())
+    }
+
+    fn value_bytes(&self) -> Option<&[u8]> {
+        self.value.as_bytes()
     }
 
     fn metadata(&self) -> &PartMetadata {
@@ -274,6 +463,7 @@ impl<P: PartProps> FormParts<P> {
             computed_headers: Vec::new(),
             fields: Vec::new(),
             percent_encoding: PercentEncoding::PathSegment,
+            max_in_memory: None,
         }
     }
 
@@ -281,6 +471,26 @@ impl<P: PartProps> FormParts<P> {
         &self.boundary
     }
 
+    pub(crate) fn with_boundary(mut self, boundary: String) -> crate::Result<Self> {
+        if !is_valid_boundary(&boundary) {
+            return Err(crate::error::builder("invalid multipart boundary"));
+        }
+
+        let found = self
+            .fields
+            .iter()
+            .filter_map(|(_, part)| part.value_bytes())
+            .any(|bytes| contains(bytes, boundary.as_bytes()));
+        if found {
+            return Err(crate::error::builder(
+                "multipart boundary appears in a part",
+            ));
+        }
+
+        self.boundary = boundary;
+        Ok(self)
+    }
+
     /// Adds a customized Part.
     pub(crate) fn part<T>(mut self, name: T, part: P) -> Self
     where
@@ -308,6 +518,40 @@ impl<P: PartProps> FormParts<P> {
         self
     }
 
+    /// Limit the total length of the parts held in memory.
+    pub(crate) fn max_in_memory(mut self, max: u64) -> Self {
+        self.max_in_memory = Some(max);
+        self
+    }
+
+    /// Fails if the parts held in memory are longer than `max_in_memory`.
+    pub(crate) fn check_in_memory(&self) -> crate::Result<()> {
+        let max = match self.max_in_memory {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        let in_memory = self
+            .fields
+            .iter()
+            .filter_map(|(_, part)| part.value_bytes())
+            .map(|bytes| bytes.len() as u64)
+            .sum::<u64>();
+        if in_memory > max {
+            return Err(crate::error::builder(format!(
+                "multipart form holds {} bytes in memory, over the limit of {}",
+                in_memory, max
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether any part has a body that isn't already in memory.
+    pub(crate) fn is_streaming(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|(_, part)| part.value_bytes().is_none())
+    }
+
     // If predictable, computes the length the request will have
     // The length should be preditable if only String and file fields have been added,
     // but not if a generic reader has been added;
@@ -497,6 +741,61 @@ fn gen_boundary() -> String {
     format!("{:016x}-{:016x}-{:016x}-{:016x}", a, b, c, d)
 }
 
+// RFC 2046, section 5.1.1
+fn is_valid_boundary(boundary: &str) -> bool {
+    let bchar = |b: u8| b.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&b);
+
+    !boundary.is_empty()
+        && boundary.len() <= 70
+        && !boundary.ends_with(' ')
+        && boundary.bytes().all(bchar)
+}
+
+/// Reads a file as a stream of chunks.
+#[cfg(feature = "stream")]
+struct FileStream {
+    file: tokio::fs::File,
+    buf: bytes::BytesMut,
+}
+
+#[cfg(feature = "stream")]
+impl FileStream {
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    fn new(file: tokio::fs::File) -> FileStream {
+        FileStream {
+            file,
+            buf: bytes::BytesMut::new(),
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl Stream for FileStream {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        use tokio::io::AsyncRead;
+
+        let this = &mut *self;
+        this.buf.reserve(FileStream::CHUNK_SIZE);
+        match futures_core::ready!(Pin::new(&mut this.file).poll_read_buf(cx, &mut this.buf)) {
+            Ok(0) => std::task::Poll::Ready(None),
+            Ok(_) => std::task::Poll::Ready(Some(Ok(this.buf.split().freeze()))),
+            Err(err) => std::task::Poll::Ready(Some(Err(err))),
+        }
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -570,6 +869,115 @@ mod tests {
         assert_eq!(std::str::from_utf8(&out).unwrap(), expected);
     }
 
+    #[test]
+    fn text_with_mime() {
+        let mut form = Form::new()
+            .text_with_mime("key1", r#"{"a":1}"#, "application/json")
+            .unwrap();
+        form.inner.boundary = "boundary".to_string();
+        let expected = "--boundary\r\n\
+                        Content-Disposition: form-data; name=\"key1\"\r\n\
+                        Content-Type: application/json\r\n\r\n\
+                        {\"a\":1}\r\n--boundary--\r\n";
+        let mut rt = runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .expect("new rt");
+        let body = form.stream().into_stream();
+        let s = body.map(|try_c| try_c.map(|r| r.to_vec())).try_concat();
+
+        let out = rt.block_on(s).unwrap();
+        assert_eq!(std::str::from_utf8(&out).unwrap(), expected);
+
+        let err = Form::new()
+            .text_with_mime("key1", "value1", "not a mime")
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+
+    #[test]
+    fn streaming_form_length() {
+        let mut form = Form::new().text("key1", "value1").text("key2", "value2");
+        assert!(!form.is_streaming());
+        let length = form.compute_length();
+        let body = form.stream();
+        assert_eq!(length, body.as_bytes().map(|bytes| bytes.len() as u64));
+
+        let mut form = Form::new().text("key1", "value1").part(
+            "reader",
+            Part::stream(Body::stream(stream::once(future::ready::<
+                Result<String, crate::Error>,
+            >(Ok(
+                "part".to_owned()
+            ))))),
+        );
+        assert!(form.is_streaming());
+        assert_eq!(form.compute_length(), None);
+        assert!(form.stream().as_bytes().is_none());
+    }
+
+    #[test]
+    fn stream_with_length_form_length() {
+        let mut form = Form::new().text("key1", "value1").part(
+            "reader",
+            Part::stream_with_length(
+                Body::stream(stream::once(future::ready::<Result<String, crate::Error>>(
+                    Ok("part".to_owned()),
+                ))),
+                4,
+            ),
+        );
+        assert!(form.is_streaming());
+        let length = form.compute_length().expect("known length");
+
+        let mut rt = runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .expect("new rt");
+        let body = form.stream().into_stream();
+        let out = rt
+            .block_on(body.map_ok(|chunk| chunk.to_vec()).try_concat())
+            .unwrap();
+        assert_eq!(length, out.len() as u64);
+    }
+
+    #[test]
+    fn with_boundary() {
+        let form = Form::new()
+            .text("key1", "value1")
+            .with_boundary("fixed-boundary")
+            .unwrap();
+        assert_eq!(form.boundary(), "fixed-boundary");
+
+        let expected = "--fixed-boundary\r\n\
+                        Content-Disposition: form-data; name=\"key1\"\r\n\r\n\
+                        value1\r\n\
+                        --fixed-boundary--\r\n";
+        let mut rt = runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .expect("new rt");
+        let body = form.stream().into_stream();
+        let s = body.map(|try_c| try_c.map(|r| r.to_vec())).try_concat();
+
+        let out = rt.block_on(s).unwrap();
+        assert_eq!(std::str::from_utf8(&out).unwrap(), expected);
+    }
+
+    #[test]
+    fn with_boundary_invalid() {
+        assert!(Form::new().with_boundary("").is_err());
+        assert!(Form::new().with_boundary("trailing ").is_err());
+        assert!(Form::new().with_boundary("bad\r\nboundary").is_err());
+        assert!(Form::new().with_boundary("x".repeat(71)).is_err());
+
+        let form = Form::new().text("key1", "contains fixed-boundary");
+        assert!(form.with_boundary("fixed-boundary").is_err());
+    }
+
     #[test]
     fn stream_to_end_with_header() {
         let mut part = Part::text("value2").mime(mime::IMAGE_BMP);
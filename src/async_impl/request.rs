@@ -2,9 +2,13 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::future::Future;
 use std::io::Write;
-use std::time::Duration;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use base64::write::EncoderWriter as Base64Encoder;
+#[cfg(feature = "stream")]
+use bytes::Bytes;
 use serde::Serialize;
 #[cfg(feature = "json")]
 use serde_json;
@@ -13,8 +17,11 @@ use super::body::Body;
 use super::client::{Client, Pending};
 use super::multipart;
 use super::response::Response;
-use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
-use crate::{Method, Url};
+use crate::header::{
+    HeaderMap, HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, IF_MATCH, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH,
+};
+use crate::{Method, StatusCode, Url};
 use http::{Request as HttpRequest, request::Parts};
 
 /// A request which can be executed with `Client::execute()`.
@@ -24,6 +31,32 @@ pub struct Request {
     headers: HeaderMap,
     body: Option<Body>,
     timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    auto_decompress: bool,
+    extensions: http::Extensions,
+    extension_clones: Vec<fn(&http::Extensions, &mut http::Extensions)>,
+}
+
+/// The parts of a `Request` other than its body.
+///
+/// This is returned by `Request::into_parts`, so that the head of a request
+/// can be inspected or changed separately from its body, and put back
+/// together with `Request::from_parts`.
+pub struct RequestHead {
+    /// The request's method.
+    pub method: Method,
+    /// The request's URL.
+    pub url: Url,
+    /// The request's headers.
+    pub headers: HeaderMap,
+    /// The request's timeout, if any.
+    pub timeout: Option<Duration>,
+    /// The request's connect timeout, if any.
+    pub connect_timeout: Option<Duration>,
+    /// The request's extensions.
+    pub extensions: http::Extensions,
+    auto_decompress: bool,
+    extension_clones: Vec<fn(&http::Extensions, &mut http::Extensions)>,
 }
 
 /// A builder to construct the properties of a `Request`.
@@ -41,7 +74,11 @@ impl Request {
             url,
             headers: HeaderMap::new(),
             body: None,
-            timeout: None
+            timeout: None,
+            connect_timeout: None,
+            auto_decompress: true,
+            extensions: http::Extensions::new(),
+            extension_clones: Vec::new(),
         }
     }
 
@@ -81,6 +118,23 @@ impl Request {
         &mut self.headers
     }
 
+    /// Set the headers, replacing any existing values with the same names.
+    pub fn set_headers(&mut self, headers: HeaderMap) {
+        crate::util::replace_headers(&mut self.headers, headers);
+    }
+
+    /// Get the extensions.
+    #[inline]
+    pub fn extensions(&self) -> &http::Extensions {
+        &self.extensions
+    }
+
+    /// Get a mutable reference to the extensions.
+    #[inline]
+    pub fn extensions_mut(&mut self) -> &mut http::Extensions {
+        &mut self.extensions
+    }
+
     /// Get the body.
     #[inline]
     pub fn body(&self) -> Option<&Body> {
@@ -105,23 +159,136 @@ impl Request {
         &mut self.timeout
     }
 
+    /// Get the connect timeout.
+    #[inline]
+    pub fn connect_timeout(&self) -> Option<&Duration> {
+        self.connect_timeout.as_ref()
+    }
+
+    /// Get a mutable reference to the connect timeout.
+    #[inline]
+    pub fn connect_timeout_mut(&mut self) -> &mut Option<Duration> {
+        &mut self.connect_timeout
+    }
+
+    pub(crate) fn auto_decompress_mut(&mut self) -> &mut bool {
+        &mut self.auto_decompress
+    }
+
     /// Attempt to clone the request.
     ///
     /// `None` is returned if the request can not be cloned, i.e. if the body is a stream.
+    ///
+    /// Only extensions added with `RequestBuilder::extension` are cloned.
     pub fn try_clone(&self) -> Option<Request> {
         let body = match self.body.as_ref() {
             Some(ref body) => Some(body.try_clone()?),
             None => None,
         };
+        let mut req = self.clone_without_body();
+        req.body = body;
+        Some(req)
+    }
+
+    /// Clone the request, with `body` in place of its body.
+    ///
+    /// Unlike [`try_clone`](Request::try_clone), this works when the body
+    /// is a stream, for instance to retry a streaming upload with a fresh
+    /// stream. A `Content-Length` header is removed, since it may not match
+    /// the new body.
+    ///
+    /// Only extensions added with `RequestBuilder::extension` are cloned.
+    pub fn clone_with_body<T: Into<Body>>(&self, body: T) -> crate::Result<Request> {
+        let mut req = self.clone_without_body();
+        req.headers.remove(CONTENT_LENGTH);
+        req.body = Some(body.into());
+        Ok(req)
+    }
+
+    /// Split the request into its head and its body.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use reqwest::{Method, Request, Url};
+    /// let url = Url::parse("https://hyper.rs")?;
+    /// let req = Request::new(Method::GET, url);
+    ///
+    /// let (head, body) = req.into_parts();
+    /// println!("{} {}", head.method, head.url);
+    /// let req = Request::from_parts(head, body);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn into_parts(self) -> (RequestHead, Option<Body>) {
+        let head = RequestHead {
+            method: self.method,
+            url: self.url,
+            headers: self.headers,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            extensions: self.extensions,
+            auto_decompress: self.auto_decompress,
+            extension_clones: self.extension_clones,
+        };
+        (head, self.body)
+    }
+
+    /// Put a request back together from a head and a body.
+    pub fn from_parts(head: RequestHead, body: Option<Body>) -> Request {
+        Request {
+            method: head.method,
+            url: head.url,
+            headers: head.headers,
+            body,
+            timeout: head.timeout,
+            connect_timeout: head.connect_timeout,
+            auto_decompress: head.auto_decompress,
+            extensions: head.extensions,
+            extension_clones: head.extension_clones,
+        }
+    }
+
+    pub(crate) fn clone_without_body(&self) -> Request {
         let mut req = Request::new(self.method().clone(), self.url().clone());
         *req.timeout_mut() = self.timeout().cloned();
+        *req.connect_timeout_mut() = self.connect_timeout().cloned();
         *req.headers_mut() = self.headers().clone();
-        req.body = body;
-        Some(req)
+        req.auto_decompress = self.auto_decompress;
+        for clone in &self.extension_clones {
+            clone(&self.extensions, &mut req.extensions);
+        }
+        req.extension_clones = self.extension_clones.clone();
+        req
     }
 
-    pub(super) fn pieces(self) -> (Method, Url, HeaderMap, Option<Body>, Option<Duration>) {
-        (self.method, self.url, self.headers, self.body, self.timeout)
+    pub(crate) fn insert_extension<T>(&mut self, val: T)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.extensions.insert(val);
+        self.extension_clones.push(clone_extension::<T>);
+    }
+
+    pub(super) fn pieces(
+        self,
+    ) -> (
+        Method,
+        Url,
+        HeaderMap,
+        Option<Body>,
+        Option<Duration>,
+        Option<Duration>,
+        bool,
+    ) {
+        (
+            self.method,
+            self.url,
+            self.headers,
+            self.body,
+            self.timeout,
+            self.connect_timeout,
+            self.auto_decompress,
+        )
     }
 }
 
@@ -153,6 +320,22 @@ impl RequestBuilder {
         self.header_sensitive(key, value, false)
     }
 
+    /// Add a `Header` to this Request if `value` is `Some`.
+    ///
+    /// When `value` is `None`, the builder is returned unchanged.
+    pub fn header_opt<K, V>(self, key: K, value: Option<V>) -> RequestBuilder
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        match value {
+            Some(value) => self.header(key, value),
+            None => self,
+        }
+    }
+
     /// Add a `Header` to this Request with ability to define if header_value is sensitive.
     fn header_sensitive<K, V>(mut self, key: K, value: V, sensitive: bool) -> RequestBuilder
     where
@@ -190,6 +373,43 @@ impl RequestBuilder {
         self
     }
 
+    /// Add each header of `iter` to this Request.
+    ///
+    /// Unlike [`headers`](RequestBuilder::headers), the values are appended
+    /// to any already set, and the pairs only need to convert into header
+    /// names and values, as with [`header`](RequestBuilder::header). The
+    /// first pair that doesn't convert is the error of the builder.
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let token = String::from("abc");
+    /// let headers = vec![("x-token", token), ("x-tenant", "acme".to_owned())];
+    ///
+    /// let client = reqwest::Client::new();
+    /// let req = client
+    ///     .get("http://httpbin.org/get")
+    ///     .header_iter(headers)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn header_iter<K, V, I>(mut self, iter: I) -> RequestBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        for (key, value) in iter {
+            if self.request.is_err() {
+                break;
+            }
+            self = self.header(key, value);
+        }
+        self
+    }
+
     /// Enable HTTP basic authentication.
     pub fn basic_auth<U, P>(self, username: U, password: Option<P>) -> RequestBuilder
     where
@@ -209,6 +429,39 @@ impl RequestBuilder {
         self.header_sensitive(crate::header::AUTHORIZATION, header_value, true)
     }
 
+    /// Enable HTTP basic authentication, encoding the credentials in
+    /// `charset` instead of UTF-8.
+    ///
+    /// Some legacy servers expect the credentials in another charset,
+    /// usually ISO-8859-1. `charset` is an encoding label, such as
+    /// `"iso-8859-1"` or `"utf-8"`. For more information about the possible
+    /// labels, please go to [`encoding_rs`] docs.
+    ///
+    /// The request fails to build if the label is unknown, or the
+    /// credentials can't be represented in that charset.
+    ///
+    /// [`encoding_rs`]: https://docs.rs/encoding_rs/0.8/encoding_rs/#relationship-with-windows-code-pages
+    pub fn basic_auth_with_charset<U, P>(
+        mut self,
+        username: U,
+        password: Option<P>,
+        charset: &str,
+    ) -> RequestBuilder
+    where
+        U: fmt::Display,
+        P: fmt::Display,
+    {
+        match basic_auth_charset(username, password, charset) {
+            Ok(header_value) => {
+                self.header_sensitive(crate::header::AUTHORIZATION, header_value, true)
+            }
+            Err(err) => {
+                self.request = Err(err);
+                self
+            }
+        }
+    }
+
     /// Enable HTTP bearer authentication.
     pub fn bearer_auth<T>(self, token: T) -> RequestBuilder
     where
@@ -218,7 +471,64 @@ impl RequestBuilder {
         self.header_sensitive(crate::header::AUTHORIZATION, header_value, true)
     }
 
+    /// Make the request conditional on the resource being modified after
+    /// `time`, by setting the `If-Modified-Since` header. An earlier value
+    /// of the header is replaced.
+    ///
+    /// The time is sent as an HTTP-date, in seconds. The request fails to
+    /// build if `time` is before 1970 or after 9999.
+    pub fn if_modified_since(mut self, time: SystemTime) -> RequestBuilder {
+        let value = http_date(time);
+        header_result(
+            &mut self.request,
+            Request::headers_mut,
+            IF_MODIFIED_SINCE,
+            value,
+            true,
+        );
+        self
+    }
+
+    /// Make the request conditional on the resource not matching `etag`,
+    /// by adding it to the `If-None-Match` header.
+    ///
+    /// `etag` can be given with or without its quotes, and as a weak tag
+    /// such as `W/"xyzzy"`. `*` matches any current representation. The
+    /// request fails to build if `etag` isn't a valid entity tag.
+    pub fn if_none_match(mut self, etag: &str) -> RequestBuilder {
+        let value = entity_tag(etag);
+        header_result(
+            &mut self.request,
+            Request::headers_mut,
+            IF_NONE_MATCH,
+            value,
+            false,
+        );
+        self
+    }
+
+    /// Make the request conditional on the resource matching `etag`, by
+    /// adding it to the `If-Match` header.
+    ///
+    /// `etag` is formatted as with
+    /// [`if_none_match`](RequestBuilder::if_none_match).
+    pub fn if_match(mut self, etag: &str) -> RequestBuilder {
+        let value = entity_tag(etag);
+        header_result(
+            &mut self.request,
+            Request::headers_mut,
+            IF_MATCH,
+            value,
+            false,
+        );
+        self
+    }
+
     /// Set the request body.
+    ///
+    /// If the body carries a content type, set with
+    /// `Body::with_content_type`, it is sent as the `Content-Type` header
+    /// unless the request sets that header itself.
     pub fn body<T: Into<Body>>(mut self, body: T) -> RequestBuilder {
         if let Ok(ref mut req) = self.request {
             *req.body_mut() = Some(body.into());
@@ -226,9 +536,46 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the request body from a `Stream` of chunks.
+    ///
+    /// This is a shorthand for `body(Body::wrap_stream(stream))`.
+    ///
+    /// Since the length of a stream isn't known up front, the request is
+    /// sent without a `Content-Length`, using chunked transfer encoding
+    /// for HTTP/1. A streaming body also can't be cloned, so
+    /// `try_clone` on the builder returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("hello"), Ok(" "), Ok("world")];
+    ///
+    /// let res = reqwest::Client::new()
+    ///     .post("http://httpbin.org/post")
+    ///     .stream_body(futures_util::stream::iter(chunks))
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the `stream` feature to be enabled.
+    #[cfg(feature = "stream")]
+    pub fn stream_body<S>(self, stream: S) -> RequestBuilder
+    where
+        S: futures_core::stream::TryStream + Send + Sync + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        Bytes: From<S::Ok>,
+    {
+        self.body(Body::stream(stream))
+    }
+
     /// Enables a request timeout.
     ///
-    /// The timeout is applied from the when the request starts connecting
+    /// The timeout is applied from when the request starts connecting
     /// until the response body has finished. It affects only this request
     /// and overrides the timeout configured using `ClientBuilder::timeout()`.
     pub fn timeout(mut self, timeout: Duration) -> RequestBuilder {
@@ -238,6 +585,65 @@ impl RequestBuilder {
         self
     }
 
+    /// Set a timeout for only the connect phase of this request.
+    ///
+    /// This overrides the timeout configured using
+    /// `ClientBuilder::connect_timeout()` for any new connection this request
+    /// waits for. A pooled connection that is reused is not affected. When
+    /// several requests to the same host set a connect timeout, the shortest
+    /// applies to the connections opened while they wait.
+    pub fn connect_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.connect_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Attach a typed value to the request's extensions.
+    ///
+    /// The value can be read back with `Request::extensions`, which lets
+    /// code handling the request see context such as a request id without
+    /// putting it in a header. It replaces any earlier value of the same
+    /// type, and is kept when the request is cloned with `try_clone`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// #[derive(Clone)]
+    /// struct RequestId(u64);
+    ///
+    /// let req = reqwest::Client::new()
+    ///     .get("https://hyper.rs")
+    ///     .extension(RequestId(7))
+    ///     .build()?;
+    /// assert_eq!(req.extensions().get::<RequestId>().unwrap().0, 7);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extension<T>(mut self, val: T) -> RequestBuilder
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        if let Ok(ref mut req) = self.request {
+            req.insert_extension(val);
+        }
+        self
+    }
+
+    /// Disable automatic decompression of the response to this request.
+    ///
+    /// The response body will be returned as it was received, with any
+    /// `Content-Encoding` header left intact. Unlike `ClientBuilder::gzip`
+    /// and `ClientBuilder::brotli`, this only affects this request, and the
+    /// client's `Accept-Encoding` header is still sent.
+    pub fn no_auto_decompress(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.auto_decompress_mut() = false;
+        }
+        self
+    }
+
     /// Sends a multipart/form-data body.
     ///
     /// ```
@@ -257,21 +663,36 @@ impl RequestBuilder {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn multipart(self, mut multipart: multipart::Form) -> RequestBuilder {
-        let mut builder = self.header(
-            CONTENT_TYPE,
-            format!("multipart/form-data; boundary={}", multipart.boundary()).as_str(),
-        );
-
-        builder = match multipart.compute_length() {
-            Some(length) => builder.header(CONTENT_LENGTH, length),
-            None => builder,
-        };
-
-        if let Ok(ref mut req) = builder.request {
+    ///
+    /// Any `Content-Type` header already set on the request is replaced.
+    ///
+    /// If the form has a streaming part (see `Form::is_streaming`), the
+    /// request can't be cloned or retried automatically, and it is sent
+    /// without a `Content-Length` unless every stream's length is known.
+    ///
+    /// # Errors
+    ///
+    /// The builder errors if the form holds more bytes in memory than its
+    /// `Form::max_in_memory` limit.
+    pub fn multipart(mut self, mut multipart: multipart::Form) -> RequestBuilder {
+        if let Err(err) = multipart.check_in_memory() {
+            if self.request.is_ok() {
+                self.request = Err(err);
+            }
+            return self;
+        }
+        if let Ok(ref mut req) = self.request {
+            let content_type = format!("multipart/form-data; boundary={}", multipart.boundary());
+            req.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_str(&content_type).expect("boundary is a valid header value"),
+            );
+            if let Some(length) = multipart.compute_length() {
+                req.headers_mut().insert(CONTENT_LENGTH, length.into());
+            }
             *req.body_mut() = Some(multipart.stream())
         }
-        builder
+        self
     }
 
     /// Modify the query string of the URL.
@@ -314,19 +735,74 @@ impl RequestBuilder {
         self
     }
 
+    /// Modify the query string of the URL, replacing existing parameters.
+    ///
+    /// Like [`query`](RequestBuilder::query), but every key in `query` first
+    /// has its existing occurrences removed from the URL. Other parameters
+    /// are kept in order, with the new ones appended after them.
+    /// On `"https://example.com/?page=1&q=rust"`, calling
+    /// `.query_replace(&[("page", 2)])` gives `"q=rust&page=2"`.
+    ///
+    /// Since the remaining parameters are parsed and serialized again, their
+    /// encoding may be normalized, e.g. `%20` becomes `+`.
+    ///
+    /// # Errors
+    /// This method will fail if the object you provide cannot be serialized
+    /// into a query string.
+    pub fn query_replace<T: Serialize + ?Sized>(mut self, query: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            error = replace_query(req.url_mut(), query).err();
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
     /// Send a form body.
-    pub fn form<T: Serialize + ?Sized>(mut self, form: &T) -> RequestBuilder {
+    pub fn form<T: Serialize + ?Sized>(self, form: &T) -> RequestBuilder {
+        self.form_with_content_type(
+            form,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        )
+    }
+
+    /// Send a form body with a custom `Content-Type`.
+    ///
+    /// This works like `form`, but sets the given content type instead of
+    /// `application/x-www-form-urlencoded`, for servers that expect
+    /// something like `application/x-www-form-urlencoded; charset=UTF-8`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the passed value cannot be serialized into
+    /// url encoded format, or if the content type is not a valid MIME type.
+    pub fn form_with_content_type<T, V>(mut self, form: &T, content_type: V) -> RequestBuilder
+    where
+        T: Serialize + ?Sized,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
         let mut error = None;
         if let Ok(ref mut req) = self.request {
-            match serde_urlencoded::to_string(form) {
-                Ok(body) => {
-                    req.headers_mut().insert(
-                        CONTENT_TYPE,
-                        HeaderValue::from_static("application/x-www-form-urlencoded"),
-                    );
+            let content_type = <HeaderValue as TryFrom<V>>::try_from(content_type)
+                .map_err(|e| crate::error::builder(e.into()))
+                .and_then(|value| {
+                    value
+                        .to_str()
+                        .map_err(crate::error::builder)?
+                        .parse::<mime::Mime>()
+                        .map_err(crate::error::builder)?;
+                    Ok(value)
+                });
+            match (content_type, serde_urlencoded::to_string(form)) {
+                (Ok(content_type), Ok(body)) => {
+                    req.headers_mut().insert(CONTENT_TYPE, content_type);
                     *req.body_mut() = Some(body.into());
                 }
-                Err(err) => error = Some(crate::error::builder(err)),
+                (Err(err), _) => error = Some(err),
+                (_, Err(err)) => error = Some(crate::error::builder(err)),
             }
         }
         if let Some(err) = error {
@@ -377,6 +853,33 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the cache mode used when fetching the request.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request cache mode][mdn] will be set to the given mode.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache(self, _mode: crate::CacheMode) -> RequestBuilder {
+        self
+    }
+
+    /// Set how redirects are handled when fetching the request.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target. Native
+    /// clients use the `redirect::Policy` of the `Client` instead.
+    ///
+    /// The [request redirect mode][mdn] will be set to the given mode.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/redirect
+    pub fn fetch_redirect(self, _mode: crate::FetchRedirect) -> RequestBuilder {
+        self
+    }
+
     /// Build a `Request`, which can be inspected, modified and executed with
     /// `Client::execute()`.
     pub fn build(self) -> crate::Result<Request> {
@@ -405,10 +908,69 @@ impl RequestBuilder {
     /// # }
     /// ```
     pub fn send(self) -> impl Future<Output = Result<Response, crate::Error>> {
-        match self.request {
+        self.into_future()
+    }
+
+    /// Constructs the Request and sends it to the target URL, returning a
+    /// [`ResponseFuture`].
+    ///
+    /// This is the same as [`send`](RequestBuilder::send), except the
+    /// returned future has a type that can be named, for instance to store
+    /// it in a struct field or a collection.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use reqwest::ResponseFuture;
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let client = reqwest::Client::new();
+    /// let pending: Vec<ResponseFuture> = vec![
+    ///     client.get("https://hyper.rs").into_future(),
+    ///     client.get("https://www.rust-lang.org").into_future(),
+    /// ];
+    /// for fut in pending {
+    ///     let response = fut.await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_future(self) -> ResponseFuture {
+        let inner = match self.request {
             Ok(req) => self.client.execute_request(req),
             Err(err) => Pending::new_err(err),
-        }
+        };
+        ResponseFuture::new(inner)
+    }
+
+    /// Sends the request, reads and discards the response body, and returns
+    /// the response status.
+    ///
+    /// Reading the body to the end allows the connection to be returned to
+    /// the pool and reused, which is useful for requests such as health
+    /// checks where only the status matters.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if there was an error while sending the request,
+    /// or while reading the response body.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use reqwest::Error;
+    /// #
+    /// # async fn run() -> Result<(), Error> {
+    /// let status = reqwest::Client::new()
+    ///     .get("https://hyper.rs/health")
+    ///     .send_and_discard()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_and_discard(self) -> crate::Result<StatusCode> {
+        let mut res = self.send().await?;
+        while res.chunk().await?.is_some() {}
+        Ok(res.status())
     }
 
     /// Attempt to clone the RequestBuilder.
@@ -442,12 +1004,54 @@ impl RequestBuilder {
     }
 }
 
+/// A `Future` that will resolve to the `Response` of a sent request.
+///
+/// Returned by [`RequestBuilder::into_future`], and by `Client` when it is
+/// called as a `tower::Service`.
+pub struct ResponseFuture {
+    inner: Pending,
+}
+
+impl ResponseFuture {
+    pub(super) fn new(inner: Pending) -> ResponseFuture {
+        ResponseFuture { inner }
+    }
+
+    fn inner(self: Pin<&mut Self>) -> Pin<&mut Pending> {
+        unsafe { Pin::map_unchecked_mut(self, |x| &mut x.inner) }
+    }
+}
+
+impl Future for ResponseFuture {
+    type Output = Result<Response, crate::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner().poll(cx)
+    }
+}
+
+impl fmt::Debug for ResponseFuture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ResponseFuture").field(&self.inner).finish()
+    }
+}
+
 impl fmt::Debug for Request {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt_request_fields(&mut f.debug_struct("Request"), self).finish()
     }
 }
 
+impl fmt::Debug for RequestHead {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RequestHead")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
 impl fmt::Debug for RequestBuilder {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut builder = f.debug_struct("RequestBuilder");
@@ -468,6 +1072,134 @@ fn fmt_request_fields<'a, 'b>(
 }
 
 
+/// Build a basic `Authorization` header value, with the credentials encoded
+/// in the charset of the `charset` label.
+pub(crate) fn basic_auth_charset<U, P>(
+    username: U,
+    password: Option<P>,
+    charset: &str,
+) -> crate::Result<Vec<u8>>
+where
+    U: fmt::Display,
+    P: fmt::Display,
+{
+    let encoding = encoding_rs::Encoding::for_label(charset.as_bytes())
+        .ok_or_else(|| crate::error::builder(format!("unknown charset: {:?}", charset)))?;
+
+    let credentials = match password {
+        Some(password) => format!("{}:{}", username, password),
+        None => format!("{}:", username),
+    };
+    let (bytes, _, unmappable) = encoding.encode(&credentials);
+    if unmappable {
+        return Err(crate::error::builder(format!(
+            "credentials can not be encoded as {}",
+            encoding.name()
+        )));
+    }
+
+    let mut header_value = b"Basic ".to_vec();
+    header_value.extend_from_slice(base64::encode(&bytes).as_bytes());
+    Ok(header_value)
+}
+
+/// Adds `value` to the `key` header of `request`, replacing its earlier
+/// values if `replace` is set, or fails the request if `value` couldn't be
+/// built.
+pub(crate) fn header_result<R>(
+    request: &mut crate::Result<R>,
+    headers_mut: fn(&mut R) -> &mut HeaderMap,
+    key: HeaderName,
+    value: crate::Result<HeaderValue>,
+    replace: bool,
+) {
+    match value {
+        Ok(value) => {
+            if let Ok(ref mut req) = *request {
+                if replace {
+                    headers_mut(req).insert(key, value);
+                } else {
+                    headers_mut(req).append(key, value);
+                }
+            }
+        }
+        Err(err) => {
+            if request.is_ok() {
+                *request = Err(err);
+            }
+        }
+    }
+}
+
+/// Format `time` as an HTTP-date header value.
+pub(crate) fn http_date(time: SystemTime) -> crate::Result<HeaderValue> {
+    // `fmt_http_date` panics outside of the years 1970 to 9999.
+    let max = UNIX_EPOCH + Duration::from_secs(253_402_300_799);
+    if time < UNIX_EPOCH || time > max {
+        return Err(crate::error::builder(
+            "HTTP-date must be between 1970 and 9999",
+        ));
+    }
+    let date = httpdate::fmt_http_date(time);
+    HeaderValue::from_str(&date).map_err(crate::error::builder)
+}
+
+/// Format `etag` as an entity tag header value, adding the quotes if it
+/// doesn't have them.
+pub(crate) fn entity_tag(etag: &str) -> crate::Result<HeaderValue> {
+    if etag == "*" {
+        return Ok(HeaderValue::from_static("*"));
+    }
+    let (weak, tag) = if etag.starts_with("W/") {
+        etag.split_at(2)
+    } else {
+        ("", etag)
+    };
+    let opaque = if tag.len() >= 2 && tag.starts_with('"') && tag.ends_with('"') {
+        &tag[1..tag.len() - 1]
+    } else {
+        tag
+    };
+    if !is_opaque_tag(opaque) {
+        return Err(crate::error::builder(format!(
+            "invalid entity tag: {:?}",
+            etag
+        )));
+    }
+    let value = format!("{}\"{}\"", weak, opaque);
+    HeaderValue::from_str(&value).map_err(crate::error::builder)
+}
+
+/// Whether `opaque` is valid between the quotes of an entity tag.
+pub(crate) fn is_opaque_tag(opaque: &str) -> bool {
+    // etagc = %x21 / %x23-7E / obs-text
+    opaque
+        .bytes()
+        .all(|b| b == 0x21 || (b >= 0x23 && b != 0x7F))
+}
+
+/// Serialize `query` into the query string of `url`, removing existing
+/// parameters with the same keys first.
+pub(crate) fn replace_query<T: Serialize + ?Sized>(url: &mut Url, query: &T) -> crate::Result<()> {
+    let replacement = serde_urlencoded::to_string(query).map_err(crate::error::builder)?;
+    let replacement = url::form_urlencoded::parse(replacement.as_bytes()).collect::<Vec<_>>();
+
+    let kept = url
+        .query_pairs()
+        .filter(|(key, _)| !replacement.iter().any(|(replaced, _)| replaced == key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(kept)
+        .extend_pairs(replacement);
+    if let Some("") = url.query() {
+        url.set_query(None);
+    }
+    Ok(())
+}
+
 /// Check the request URL for a "username:password" type authority, and if
 /// found, remove it from the URL and return it.
 pub(crate) fn extract_authority(url: &mut Url) -> Option<(String, Option<String>)> {
@@ -498,6 +1230,15 @@ pub(crate) fn extract_authority(url: &mut Url) -> Option<(String, Option<String>
     None
 }
 
+fn clone_extension<T>(from: &http::Extensions, to: &mut http::Extensions)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if let Some(val) = from.get::<T>() {
+        to.insert(val.clone());
+    }
+}
+
 impl<T> TryFrom<HttpRequest<T>> for Request where T:Into<Body>{
     type Error = crate::Error;
 
@@ -507,6 +1248,7 @@ impl<T> TryFrom<HttpRequest<T>> for Request where T:Into<Body>{
             method,
             uri,
             headers,
+            extensions,
             ..
         } = parts;
         let url = Url::parse(&uri.to_string())
@@ -517,18 +1259,61 @@ impl<T> TryFrom<HttpRequest<T>> for Request where T:Into<Body>{
             headers,
             body: Some(body.into()),
             timeout: None,
+            connect_timeout: None,
+            auto_decompress: true,
+            extensions,
+            extension_clones: Vec::new(),
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Client, HttpRequest, Request};
+    use super::{multipart, Client, HttpRequest, Request, CONTENT_LENGTH, CONTENT_TYPE};
     use crate::Method;
     use serde::Serialize;
     use std::collections::BTreeMap;
     use std::convert::TryFrom;
 
+    #[test]
+    fn into_parts_roundtrip() {
+        let client = Client::new();
+        let req = client
+            .post("https://localhost/upload")
+            .header("x-trace", "1")
+            .timeout(std::time::Duration::from_secs(3))
+            .body("hello")
+            .build()
+            .unwrap();
+
+        let (mut head, body) = req.into_parts();
+        assert_eq!(head.method, Method::POST);
+        assert_eq!(head.url.as_str(), "https://localhost/upload");
+        head.headers.insert("x-logged", "yes".parse().unwrap());
+
+        let req = Request::from_parts(head, body);
+        assert_eq!(req.headers()["x-trace"], "1");
+        assert_eq!(req.headers()["x-logged"], "yes");
+        assert_eq!(req.timeout(), Some(&std::time::Duration::from_secs(3)));
+        assert_eq!(req.body().and_then(|b| b.as_bytes()), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn into_future_is_nameable() {
+        struct Pipeline {
+            pending: Vec<super::ResponseFuture>,
+        }
+
+        let client = Client::new();
+        let pipeline = Pipeline {
+            pending: vec![client.get("http://example.com/a").into_future()],
+        };
+
+        let debug = format!("{:?}", pipeline.pending[0]);
+        assert!(debug.starts_with("ResponseFuture"), "{}", debug);
+        assert!(debug.contains("example.com"), "{}", debug);
+    }
+
     #[test]
     fn add_query_append() {
         let client = Client::new();
@@ -593,6 +1378,27 @@ mod tests {
         assert_eq!(req.url().query(), Some("foo=bar&qux=three"));
     }
 
+    #[test]
+    fn add_query_replace() {
+        let client = Client::new();
+        let some_url = "https://google.com/?page=1&q=rust&page=0";
+        let r = client.get(some_url);
+
+        let r = r.query_replace(&[("page", 2)]);
+        let r = r.query_replace(&[("q", "hyper"), ("q", "tokio")]);
+
+        let req = r.build().expect("request is valid");
+        assert_eq!(req.url().query(), Some("page=2&q=hyper&q=tokio"));
+
+        let empty_query: &[(&str, &str)] = &[];
+        let req = client
+            .get("https://google.com/")
+            .query_replace(empty_query)
+            .build()
+            .expect("request is valid");
+        assert_eq!(req.url().query(), None);
+    }
+
     #[test]
     fn test_replace_headers() {
         use http::HeaderMap;
@@ -710,6 +1516,49 @@ mod tests {
         assert_eq!(req.headers()["authorization"].is_sensitive(), true);
     }
 
+    #[test]
+    fn test_basic_auth_with_charset() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let req = client
+            .get(some_url)
+            .basic_auth_with_charset("Aladdin", Some("öpen sesame"), "iso-8859-1")
+            .build()
+            .expect("request build");
+
+        assert_eq!(
+            req.headers()["authorization"],
+            "Basic QWxhZGRpbjr2cGVuIHNlc2FtZQ=="
+        );
+        assert!(req.headers()["authorization"].is_sensitive());
+
+        let req = client
+            .get(some_url)
+            .basic_auth_with_charset("Aladdin", Some("öpen sesame"), "utf-8")
+            .build()
+            .expect("request build");
+
+        assert_eq!(
+            req.headers()["authorization"],
+            "Basic QWxhZGRpbjrDtnBlbiBzZXNhbWU="
+        );
+
+        let err = client
+            .get(some_url)
+            .basic_auth_with_charset("Aladdin", Some("☃"), "iso-8859-1")
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+
+        let err = client
+            .get(some_url)
+            .basic_auth_with_charset("Aladdin", None::<&str>, "not-a-charset")
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+
     #[test]
     fn test_bearer_auth_sensitive_header() {
         let client = Client::new();
@@ -726,7 +1575,193 @@ mod tests {
         assert_eq!(req.headers()["authorization"].is_sensitive(), true);
     }
 
-    
+    #[test]
+    fn header_opt() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let req = client
+            .get(some_url)
+            .header_opt("x-some", Some("a"))
+            .header_opt("x-none", None::<&str>)
+            .build()
+            .expect("request build");
+
+        assert_eq!(req.headers()["x-some"], "a");
+        assert_eq!(req.headers().get("x-none"), None);
+    }
+
+    #[test]
+    fn header_iter() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let req = client
+            .get(some_url)
+            .header("x-multi", "a")
+            .header_iter(vec![("x-multi", "b".to_owned()), ("x-one", "c".to_owned())])
+            .build()
+            .expect("request build");
+
+        let multi = req.headers().get_all("x-multi").iter().collect::<Vec<_>>();
+        assert_eq!(multi, ["a", "b"]);
+        assert_eq!(req.headers()["x-one"], "c");
+
+        let err = client
+            .get(some_url)
+            .header_iter(vec![("x-ok", "a"), ("bad name", "b"), ("x-after", "c")])
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+
+    #[test]
+    fn conditional_headers() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let req = client
+            .get(some_url)
+            .if_modified_since(UNIX_EPOCH)
+            .if_modified_since(time)
+            .if_none_match("xyzzy")
+            .if_none_match("W/\"r2d2\"")
+            .if_match("*")
+            .build()
+            .expect("request build");
+
+        let headers = req.headers();
+        let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+        assert_eq!(headers.get_all("if-modified-since").iter().count(), 1);
+        assert_eq!(headers["if-modified-since"], date);
+        let none_match = headers.get_all("if-none-match").iter().collect::<Vec<_>>();
+        assert_eq!(none_match, ["\"xyzzy\"", "W/\"r2d2\""]);
+        assert_eq!(headers["if-match"], "*");
+
+        let err = client
+            .get(some_url)
+            .if_none_match("two words")
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+
+        let before_epoch = UNIX_EPOCH - Duration::from_secs(1);
+        let err = client
+            .get(some_url)
+            .if_modified_since(before_epoch)
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+
+    #[test]
+    fn set_headers_and_extensions() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let mut req = client
+            .get(some_url)
+            .header("x-one", "a")
+            .header("x-two", "b")
+            .build()
+            .expect("request build");
+
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-one", "c".parse().unwrap());
+        req.set_headers(headers);
+        assert_eq!(req.headers()["x-one"], "c");
+        assert_eq!(req.headers()["x-two"], "b");
+
+        req.extensions_mut().insert(42u32);
+        assert_eq!(req.extensions().get::<u32>(), Some(&42));
+    }
+
+    #[test]
+    fn extension_survives_try_clone() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let mut req = client
+            .get(some_url)
+            .extension(42u32)
+            .extension("tenant")
+            .build()
+            .expect("request build");
+        req.extensions_mut().insert(7u8);
+
+        let clone = req.try_clone().expect("clone");
+        assert_eq!(clone.extensions().get::<u32>(), Some(&42));
+        assert_eq!(clone.extensions().get::<&str>(), Some(&"tenant"));
+        assert_eq!(clone.extensions().get::<u8>(), None);
+    }
+
+    #[test]
+    fn clone_with_body_replaces_body() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+
+        let req = client
+            .put(some_url)
+            .header(CONTENT_LENGTH, "3")
+            .header("x-signature", "abc")
+            .body("old")
+            .build()
+            .expect("request build");
+
+        let clone = req.clone_with_body("fresh").expect("clone");
+        assert_eq!(clone.method(), Method::PUT);
+        assert_eq!(clone.url().as_str(), some_url);
+        assert_eq!(clone.headers()["x-signature"], "abc");
+        assert_eq!(clone.headers().get(CONTENT_LENGTH), None);
+        let body = clone.body().and_then(|body| body.as_bytes());
+        assert_eq!(body, Some(&b"fresh"[..]));
+    }
+
+    #[test]
+    fn multipart_max_in_memory() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+        let form = || {
+            (0..64).fold(multipart::Form::new(), |form, i| {
+                form.text(format!("field{}", i), "x".repeat(16 * 1024))
+            })
+        };
+
+        let err = client
+            .post(some_url)
+            .multipart(form().max_in_memory(512 * 1024))
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+
+        let req = client
+            .post(some_url)
+            .multipart(form().max_in_memory(2 * 1024 * 1024))
+            .build()
+            .expect("request build");
+        assert!(req.body().expect("body").as_bytes().is_some());
+    }
+
+    #[test]
+    fn multipart_replaces_content_type() {
+        let client = Client::new();
+        let some_url = "https://localhost/";
+        let form = multipart::Form::new().text("key", "value");
+        let content_type = format!("multipart/form-data; boundary={}", form.boundary());
+
+        let req = client
+            .post(some_url)
+            .header(CONTENT_TYPE, "application/json")
+            .multipart(form)
+            .build()
+            .expect("request build");
+
+        let values = req.headers().get_all(CONTENT_TYPE).iter().collect::<Vec<_>>();
+        assert_eq!(values, vec![&content_type]);
+    }
 
     #[test]
     fn convert_from_http_request() {
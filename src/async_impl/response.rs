@@ -1,8 +1,10 @@
 use std::borrow::Cow;
 use std::fmt;
 use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::{Duration, SystemTime};
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use encoding_rs::{Encoding, UTF_8};
 use futures_util::stream::StreamExt;
 use hyper::client::connect::HttpInfo;
@@ -12,13 +14,14 @@ use mime::Mime;
 use serde::de::DeserializeOwned;
 #[cfg(feature = "json")]
 use serde_json;
-use tokio::time::Delay;
 use url::Url;
 
 use super::body::Body;
 use super::decoder::{Accepts, Decoder};
+use super::upgrade::{PendingUpgrade, Upgraded};
 #[cfg(feature = "cookies")]
 use crate::cookie;
+use crate::TimeoutKind;
 
 /// A Response to a submitted `Request`.
 pub struct Response {
@@ -34,10 +37,10 @@ pub struct Response {
 
 impl Response {
     pub(super) fn new(
-        res: hyper::Response<hyper::Body>,
+        res: hyper::Response<Body>,
         url: Url,
         accepts: Accepts,
-        timeout: Option<Delay>,
+        max_size: Option<u64>,
     ) -> Response {
         let (parts, body) = res.into_parts();
         let status = parts.status;
@@ -45,7 +48,7 @@ impl Response {
         let extensions = parts.extensions;
 
         let mut headers = parts.headers;
-        let decoder = Decoder::detect(&mut headers, Body::response(body, timeout), accepts);
+        let decoder = Decoder::detect(&mut headers, body, accepts).max_size(max_size);
 
         Response {
             status,
@@ -63,6 +66,36 @@ impl Response {
         self.status
     }
 
+    /// Check if the status of this `Response` is informational (1xx).
+    #[inline]
+    pub fn is_informational(&self) -> bool {
+        self.status.is_informational()
+    }
+
+    /// Check if the status of this `Response` is successful (2xx).
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    /// Check if the status of this `Response` is a redirection (3xx).
+    #[inline]
+    pub fn is_redirection(&self) -> bool {
+        self.status.is_redirection()
+    }
+
+    /// Check if the status of this `Response` is a client error (4xx).
+    #[inline]
+    pub fn is_client_error(&self) -> bool {
+        self.status.is_client_error()
+    }
+
+    /// Check if the status of this `Response` is a server error (5xx).
+    #[inline]
+    pub fn is_server_error(&self) -> bool {
+        self.status.is_server_error()
+    }
+
     /// Get the HTTP `Version` of this `Response`.
     #[inline]
     pub fn version(&self) -> Version {
@@ -83,6 +116,9 @@ impl Response {
 
     /// Get the content-length of this response, if known.
     ///
+    /// The `content-length` header is used when present, otherwise this
+    /// falls back to the exact size hint of the body.
+    ///
     /// Reasons it may not be known:
     ///
     /// - The server didn't send a `content-length` header.
@@ -91,7 +127,40 @@ impl Response {
     pub fn content_length(&self) -> Option<u64> {
         use hyper::body::HttpBody;
 
-        HttpBody::size_hint(&self.body).exact()
+        self.headers
+            .get(crate::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .or_else(|| HttpBody::size_hint(&self.body).exact())
+    }
+
+    /// Get the time of the `Last-Modified` header, if it's a valid
+    /// HTTP-date.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.headers
+            .get(crate::header::LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| httpdate::parse_http_date(value).ok())
+    }
+
+    /// Get the entity tag of the `ETag` header, if it's valid.
+    ///
+    /// The tag keeps its quotes, and its `W/` prefix if it's weak, such as
+    /// `W/"xyzzy"`, so it can be passed back as is to
+    /// `RequestBuilder::if_none_match`.
+    pub fn etag(&self) -> Option<String> {
+        let value = self.headers.get(crate::header::ETAG)?.to_str().ok()?;
+        let tag = if value.starts_with("W/") {
+            value.split_at(2).1
+        } else {
+            value
+        };
+        let quoted = tag.len() >= 2 && tag.starts_with('"') && tag.ends_with('"');
+        if quoted && super::request::is_opaque_tag(&tag[1..tag.len() - 1]) {
+            Some(value.to_owned())
+        } else {
+            None
+        }
     }
 
     /// Retrieve the cookies contained in the response.
@@ -119,6 +188,21 @@ impl Response {
             .map(|info| info.remote_addr())
     }
 
+    /// Returns a reference to the associated extensions.
+    ///
+    /// These carry metadata about the connection the `Response` was received
+    /// on: the `hyper::client::connect::HttpInfo` used by `remote_addr`, and
+    /// a `TlsInfo` with the server's certificate and the ALPN protocol when
+    /// the connection was made with the `rustls` backend.
+    pub fn extensions(&self) -> &http::Extensions {
+        &self.extensions
+    }
+
+    /// Returns a mutable reference to the associated extensions.
+    pub fn extensions_mut(&mut self) -> &mut http::Extensions {
+        &mut self.extensions
+    }
+
     // body methods
 
     /// Get the full response text.
@@ -193,6 +277,49 @@ impl Response {
         }
     }
 
+    /// Get the full response text, detecting its encoding.
+    ///
+    /// The encoding is taken from the `charset` parameter of `Content-Type`
+    /// if it names a known encoding, then from a byte order mark at the
+    /// start of the body, and otherwise defaults to UTF-8. Malformed
+    /// sequences are replaced with the REPLACEMENT CHARACTER.
+    ///
+    /// Returns the text along with the name of the encoding that was used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let (content, encoding) = reqwest::get("http://httpbin.org/range/26")
+    ///     .await?
+    ///     .text_charset_detect()
+    ///     .await?;
+    ///
+    /// println!("text ({}): {:?}", encoding, content);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn text_charset_detect(self) -> crate::Result<(String, &'static str)> {
+        let declared = self
+            .headers
+            .get(crate::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok())
+            .and_then(|mime| {
+                mime.get_param("charset")
+                    .and_then(|charset| Encoding::for_label(charset.as_str().as_bytes()))
+            });
+
+        let full = self.bytes().await?;
+
+        let encoding = declared
+            .or_else(|| Encoding::for_bom(&full).map(|(encoding, _)| encoding))
+            .unwrap_or(UTF_8);
+
+        let (text, _) = encoding.decode_with_bom_removal(&full);
+        Ok((text.into_owned(), encoding.name()))
+    }
+
     /// Try to deserialize the response body as JSON.
     ///
     /// # Optional
@@ -241,8 +368,99 @@ impl Response {
         serde_json::from_slice(&full).map_err(crate::error::decode)
     }
 
+    /// Try to deserialize the response body as JSON, after stripping an
+    /// XSSI guard `prefix` from the front of it.
+    ///
+    /// Some APIs start their JSON responses with a string such as `)]}'`
+    /// or `while(1);`, so they can't be evaluated as a script.
+    ///
+    /// ```
+    /// # extern crate reqwest;
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let list: Vec<String> = reqwest::get("http://example.com/api/list")
+    ///     .await?
+    ///     .json_xssi(")]}'\n")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response body doesn't start with `prefix`,
+    /// or for the same reasons as [`json`](Response::json).
+    #[cfg(feature = "json")]
+    pub async fn json_xssi<T: DeserializeOwned>(self, prefix: &str) -> crate::Result<T> {
+        let full = self.bytes().await?;
+
+        if !full.starts_with(prefix.as_bytes()) {
+            return Err(crate::error::decode(format!(
+                "response body doesn't start with {:?}",
+                prefix
+            )));
+        }
+        serde_json::from_slice(&full[prefix.len()..]).map_err(crate::error::decode)
+    }
+
+    /// Try to deserialize the response body as JSON, after checking that
+    /// the `Content-Type` of the response is a JSON media type.
+    ///
+    /// Both `application/json` and types with a `+json` suffix, such as
+    /// `application/problem+json`, are accepted. This gives a clear error
+    /// when a server answers with something else, such as an HTML error
+    /// page, where [`json`](Response::json) would fail to parse it.
+    ///
+    /// ```
+    /// # extern crate reqwest;
+    /// # async fn run() -> Result<(), reqwest::Error> {
+    /// let list: Vec<String> = reqwest::get("http://example.com/api/list")
+    ///     .await?
+    ///     .json_strict()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has no JSON `Content-Type`, or for
+    /// the same reasons as [`json`](Response::json).
+    #[cfg(feature = "json")]
+    pub async fn json_strict<T: DeserializeOwned>(self) -> crate::Result<T> {
+        let content_type = self.headers.get(crate::header::CONTENT_TYPE);
+        let mime = content_type
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok());
+        match mime {
+            Some(ref mime) if mime.subtype() == mime::JSON || mime.suffix() == Some(mime::JSON) => {
+                self.json().await
+            }
+            Some(mime) => Err(crate::error::decode(format!(
+                "expected JSON, got {}/{}",
+                mime.type_(),
+                mime.subtype()
+            ))),
+            None => Err(crate::error::decode(match content_type {
+                Some(value) => format!("expected JSON, got {:?}", value),
+                None => "expected JSON, got no content type".to_string(),
+            })),
+        }
+    }
+
     /// Get the full response body as `Bytes`.
     ///
+    /// If part of the body was already read, such as with [`chunk`], only
+    /// the rest of it is returned.
+    ///
+    /// [`chunk`]: Response::chunk
+    ///
+    /// # Cancellation
+    ///
+    /// Dropping the returned future before it completes drops the body
+    /// along with it. A connection whose body was not read to the end is
+    /// closed instead of being returned to the pool, so later requests on
+    /// the same `Client` are unaffected.
+    ///
     /// # Example
     ///
     /// ```
@@ -256,8 +474,214 @@ impl Response {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn bytes(self) -> crate::Result<Bytes> {
-        hyper::body::to_bytes(self.body).await
+    pub async fn bytes(mut self) -> crate::Result<Bytes> {
+        let total = self.content_length();
+        self.collect(total).await
+    }
+
+    /// Reads the rest of the body, reserving room for `total` bytes.
+    async fn collect(&mut self, total: Option<u64>) -> crate::Result<Bytes> {
+        // Bodies that arrive in a single chunk are returned without copying.
+        let first = match self.chunk().await? {
+            Some(chunk) => chunk,
+            None => return Ok(Bytes::new()),
+        };
+        let second = match self.chunk().await? {
+            Some(chunk) => chunk,
+            None => return Ok(first),
+        };
+
+        let mut buf = BytesMut::with_capacity(capacity_hint(total, first.len() + second.len()));
+        buf.extend_from_slice(&first);
+        buf.extend_from_slice(&second);
+        while let Some(chunk) = self.chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(buf.freeze())
+    }
+
+    /// Get the rest of the response body as `Bytes`.
+    ///
+    /// Only the part of the body that wasn't read yet, such as with
+    /// [`chunk`], is returned. This is useful for protocols that start the
+    /// body with a header section, parsed chunk by chunk, followed by an
+    /// opaque payload.
+    ///
+    /// This is like [`bytes`], but the buffer is sized for the unread part
+    /// of the body rather than its full `Content-Length`.
+    ///
+    /// [`chunk`]: Response::chunk
+    /// [`bytes`]: Response::bytes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut res = reqwest::get("https://hyper.rs").await?;
+    ///
+    /// let first = res.chunk().await?;
+    /// let rest = res.remaining_bytes().await?;
+    ///
+    /// println!("first: {:?}, then {} bytes", first, rest.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remaining_bytes(mut self) -> crate::Result<Bytes> {
+        use hyper::body::HttpBody;
+
+        // Unlike `Content-Length`, the size hint shrinks as the body is read.
+        let remaining = HttpBody::size_hint(&self.body).exact();
+        self.collect(remaining).await
+    }
+
+    /// Get the full response body as `Bytes`, reporting progress as it is
+    /// received.
+    ///
+    /// After each chunk, `progress` is called with the number of bytes
+    /// received so far and the total length, if known from
+    /// [`content_length`](#method.content_length).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bytes = reqwest::get("http://httpbin.org/bytes/1024")
+    ///     .await?
+    ///     .bytes_with_progress(|received, total| {
+    ///         println!("received {} of {:?} bytes", received, total);
+    ///     })
+    ///     .await?;
+    ///
+    /// println!("bytes: {:?}", bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bytes_with_progress<F>(mut self, mut progress: F) -> crate::Result<Bytes>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let total = self.content_length();
+        let mut buf = BytesMut::with_capacity(capacity_hint(total, 0));
+
+        while let Some(chunk) = self.chunk().await? {
+            buf.extend_from_slice(&chunk);
+            progress(buf.len() as u64, total);
+        }
+
+        Ok(buf.freeze())
+    }
+
+    /// Get the full response body as `Bytes`, failing if reading it takes
+    /// longer than `timeout`.
+    ///
+    /// The deadline starts when this is called and only covers reading the
+    /// body. It applies in addition to any timeout set on the client or the
+    /// request, so a stalled download can be given up on sooner.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bytes = reqwest::get("http://httpbin.org/ip")
+    ///     .await?
+    ///     .bytes_timeout(Duration::from_secs(5))
+    ///     .await?;
+    ///
+    /// println!("bytes: {:?}", bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bytes_timeout(self, timeout: Duration) -> crate::Result<Bytes> {
+        let url = self.url.clone();
+        match tokio::time::timeout(timeout, self.bytes()).await {
+            Ok(res) => res,
+            Err(_elapsed) => {
+                let err = crate::error::TimedOut(TimeoutKind::Body);
+                Err(crate::error::body(err).with_url(*url))
+            }
+        }
+    }
+
+    /// Append the full response body to `buf`, returning the number of
+    /// bytes read.
+    ///
+    /// This allows reusing a buffer across responses, instead of allocating
+    /// new `Bytes` for each of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut buf = Vec::new();
+    /// let n = reqwest::get("http://httpbin.org/ip")
+    ///     .await?
+    ///     .read_to_end(&mut buf)
+    ///     .await?;
+    ///
+    /// println!("read {} bytes: {:?}", n, buf);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_to_end(mut self, buf: &mut Vec<u8>) -> crate::Result<usize> {
+        let start = buf.len();
+
+        while let Some(chunk) = self.chunk().await? {
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(buf.len() - start)
+    }
+
+    /// Get the full response body as `Bytes`, failing if it is larger than
+    /// `max` bytes.
+    ///
+    /// The body is read chunk by chunk, and reading stops as soon as the
+    /// limit is exceeded. The returned error reports `true` from
+    /// `Error::is_body_too_large`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bytes = reqwest::get("http://httpbin.org/ip")
+    ///     .await?
+    ///     .bytes_limited(64 * 1024)
+    ///     .await?;
+    ///
+    /// println!("bytes: {:?}", bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn bytes_limited(mut self, max: usize) -> crate::Result<Bytes> {
+        let mut buf = BytesMut::new();
+
+        while let Some(chunk) = self.chunk().await? {
+            if buf.len() + chunk.len() > max {
+                return Err(crate::error::body_too_large(max as u64));
+            }
+            buf.extend_from_slice(&chunk);
+        }
+
+        Ok(buf.freeze())
+    }
+
+    /// Take over the connection of an upgrade response.
+    ///
+    /// This works for `101 Switching Protocols` responses and successful
+    /// responses to a `CONNECT` request, returning the raw connection for
+    /// the new protocol.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response is not an upgrade, or the
+    /// connection could not be upgraded.
+    pub async fn upgrade(mut self) -> crate::Result<Upgraded> {
+        match self.extensions.remove::<PendingUpgrade>() {
+            Some(pending) => pending.wait().await,
+            None => Err(crate::error::upgrade("response is not an upgrade").with_url(*self.url)),
+        }
     }
 
     /// Stream a chunk of the response body.
@@ -284,6 +708,45 @@ impl Response {
         }
     }
 
+    /// Get the trailers sent after the response body, if any.
+    ///
+    /// Servers such as gRPC send metadata like `grpc-status` this way.
+    ///
+    /// Trailers only arrive after the whole body, so read the body first,
+    /// such as with [`chunk`], and then await this. Any part of the body
+    /// that wasn't read yet is read and discarded. Once the body was
+    /// handed off, such as by [`bytes_stream`], the trailers are no longer
+    /// reachable.
+    ///
+    /// Only HTTP/2 responses can have trailers. For HTTP/1 responses, this
+    /// returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut res = reqwest::get("https://hyper.rs").await?;
+    ///
+    /// while let Some(chunk) = res.chunk().await? {
+    ///     println!("Chunk: {:?}", chunk);
+    /// }
+    /// if let Some(trailers) = res.trailers().await? {
+    ///     println!("Trailers: {:?}", trailers);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`chunk`]: Response::chunk
+    /// [`bytes_stream`]: Response::bytes_stream
+    pub async fn trailers(&mut self) -> crate::Result<Option<HeaderMap>> {
+        use hyper::body::HttpBody;
+
+        while self.chunk().await?.is_some() {}
+        let body = &mut self.body;
+        futures_util::future::poll_fn(|cx| Pin::new(&mut *body).poll_trailers(cx)).await
+    }
+
     /// Convert the response into a `Stream` of `Bytes` from the body.
     ///
     /// # Example
@@ -415,6 +878,20 @@ impl<T: Into<Body>> From<http::Response<T>> for Response {
     }
 }
 
+/// A `Response` can be converted into an `http::Response`, keeping the
+/// final `Url` in its extensions.
+impl From<Response> for http::Response<Body> {
+    fn from(r: Response) -> http::Response<Body> {
+        let mut res = http::Response::new(Body::stream(r.body));
+        *res.status_mut() = r.status;
+        *res.version_mut() = r.version;
+        *res.headers_mut() = r.headers;
+        *res.extensions_mut() = r.extensions;
+        res.extensions_mut().insert(ResponseUrl(*r.url));
+        res
+    }
+}
+
 /// A `Response` can be piped as the `Body` of another request.
 impl From<Response> for Body {
     fn from(r: Response) -> Body {
@@ -440,9 +917,23 @@ impl ResponseBuilderExt for http::response::Builder {
     }
 }
 
+/// How many bytes to reserve up front when collecting a body of `total`
+/// length, given that `read` bytes have already arrived.
+///
+/// The `Content-Length` is only a hint from the peer, so the reservation is
+/// capped to avoid allocating huge buffers for bodies that never arrive.
+fn capacity_hint(total: Option<u64>, read: usize) -> usize {
+    const MAX_PREALLOCATE: u64 = 16 * 1024 * 1024;
+
+    match total {
+        Some(total) => total.min(MAX_PREALLOCATE).max(read as u64) as usize,
+        None => read,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Response, ResponseBuilderExt, ResponseUrl};
+    use super::{capacity_hint, Response, ResponseBuilderExt, ResponseUrl};
     use http::response::Builder;
     use url::Url;
 
@@ -474,4 +965,123 @@ mod tests {
         assert_eq!(response.status, 200);
         assert_eq!(response.url, Box::new(url));
     }
+
+    #[test]
+    fn test_last_modified_and_etag() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let response = |name: &str, value: &str| {
+            let response = Builder::new().header(name, value).body("").unwrap();
+            Response::from(response)
+        };
+
+        let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        assert_eq!(response("last-modified", date).last_modified(), Some(time));
+        assert_eq!(response("last-modified", "yesterday").last_modified(), None);
+
+        let etag = |value: &str| response("etag", value).etag();
+        for valid in &["\"xyzzy\"", "W/\"r2d2\"", "\"\""] {
+            assert_eq!(etag(valid), Some(valid.to_string()));
+        }
+        assert_eq!(etag("xyzzy"), None);
+        assert_eq!(etag("\"two words\""), None);
+        assert_eq!(response("x-foo", "bar").etag(), None);
+    }
+
+    #[test]
+    fn test_status_predicates() {
+        let response = |status: u16| {
+            let response = Builder::new().status(status).body("").unwrap();
+            Response::from(response)
+        };
+
+        assert!(response(101).is_informational());
+        assert!(response(204).is_success());
+        assert!(response(302).is_redirection());
+        assert!(response(404).is_client_error());
+        assert!(response(503).is_server_error());
+        assert!(!response(404).is_success());
+        assert!(!response(200).is_server_error());
+    }
+
+    #[test]
+    fn test_into_http_response() {
+        let url = Url::parse("http://example.com").unwrap();
+        let response = Builder::new()
+            .status(404)
+            .header("x-foo", "bar")
+            .url(url.clone())
+            .body("foo")
+            .unwrap();
+        let response = Response::from(response);
+
+        let response = http::Response::<super::Body>::from(response);
+        assert_eq!(response.status(), 404);
+        assert_eq!(response.headers()["x-foo"], "bar");
+
+        let response = Response::from(response);
+        assert_eq!(response.status, 404);
+        assert_eq!(response.url, Box::new(url));
+    }
+
+    #[tokio::test]
+    async fn test_trailers() {
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        struct WithTrailers(Option<bytes::Bytes>);
+
+        impl http_body::Body for WithTrailers {
+            type Data = bytes::Bytes;
+            type Error = std::io::Error;
+
+            fn poll_data(
+                mut self: Pin<&mut Self>,
+                _cx: &mut Context,
+            ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+                Poll::Ready(self.0.take().map(Ok))
+            }
+
+            fn poll_trailers(
+                self: Pin<&mut Self>,
+                _cx: &mut Context,
+            ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+                let mut trailers = http::HeaderMap::new();
+                trailers.insert("grpc-status", "0".parse().unwrap());
+                Poll::Ready(Ok(Some(trailers)))
+            }
+        }
+
+        let body = super::Body::response(WithTrailers(Some("hello".into())), None);
+        let mut response = Response::from(http::Response::new(body));
+
+        let trailers = response.trailers().await.unwrap().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+    }
+
+    #[tokio::test]
+    async fn test_remaining_bytes() {
+        let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("head\n"), Ok("pay"), Ok("load")];
+        let body = super::Body::stream(futures_util::stream::iter(chunks));
+        let mut response = Response::from(http::Response::new(body));
+
+        assert_eq!(response.chunk().await.unwrap().unwrap(), "head\n");
+        assert_eq!(response.remaining_bytes().await.unwrap(), "payload");
+
+        let chunks: Vec<Result<_, std::io::Error>> = vec![Ok("head\n"), Ok("payload")];
+        let body = super::Body::stream(futures_util::stream::iter(chunks));
+        let mut response = Response::from(http::Response::new(body));
+
+        assert_eq!(response.chunk().await.unwrap().unwrap(), "head\n");
+        assert_eq!(response.bytes().await.unwrap(), "payload");
+    }
+
+    #[test]
+    fn test_capacity_hint() {
+        assert_eq!(capacity_hint(None, 10), 10);
+        assert_eq!(capacity_hint(Some(1024), 10), 1024);
+        assert_eq!(capacity_hint(Some(4), 10), 10);
+        assert_eq!(capacity_hint(Some(!0), 10), 16 * 1024 * 1024);
+    }
 }
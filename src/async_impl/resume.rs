@@ -0,0 +1,208 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http::header::{
+    HeaderMap, HeaderValue, ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, ETAG, IF_RANGE,
+    LAST_MODIFIED, RANGE,
+};
+use http::{Method, StatusCode};
+use http_body::{Body as HttpBody, SizeHint};
+use hyper::client::ResponseFuture;
+use log::debug;
+use url::Url;
+
+use super::client::HyperClient;
+use super::Body;
+use crate::into_url::expect_uri;
+
+/// A response body that picks up where it left off after an error.
+///
+/// If reading the body fails before all of its `Content-Length` has been
+/// received, the rest is requested again with a `Range` request, and
+/// reading continues from its body.
+pub(super) struct Resume {
+    client: HyperClient,
+    url: Url,
+    headers: HeaderMap,
+    validator: Option<HeaderValue>,
+    total: u64,
+    received: u64,
+    // Whether the current body delivered any data, so a server that keeps
+    // failing without progress isn't asked again forever.
+    progressed: bool,
+    state: State,
+}
+
+enum State {
+    Reading(hyper::Body),
+    // `ResponseFuture` isn't `Sync`, which response bodies need to be.
+    Resuming(Mutex<ResponseFuture>, Option<hyper::Error>),
+}
+
+impl Resume {
+    /// Whether the body of `res`, the response to a `method` request, can
+    /// be resumed.
+    pub(super) fn is_resumable(method: &Method, res: &hyper::Response<hyper::Body>) -> bool {
+        let accepts_ranges = res
+            .headers()
+            .get_all(ACCEPT_RANGES)
+            .iter()
+            .any(|value| value == "bytes");
+
+        (*method == Method::GET || *method == Method::HEAD)
+            && res.status() == StatusCode::OK
+            && accepts_ranges
+            && content_length(res.headers()).unwrap_or(0) > 0
+    }
+
+    /// Wraps `body`, the body of a response with `res_headers` that
+    /// `is_resumable`, which was sent for a request with `headers`.
+    pub(super) fn new(
+        client: &HyperClient,
+        url: &Url,
+        headers: &HeaderMap,
+        res_headers: &HeaderMap,
+        body: hyper::Body,
+    ) -> Resume {
+        let validator = res_headers
+            .get(ETAG)
+            .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+            .or_else(|| res_headers.get(LAST_MODIFIED))
+            .cloned();
+        let mut headers = headers.clone();
+        headers.remove(RANGE);
+        headers.remove(IF_RANGE);
+
+        Resume {
+            client: client.clone(),
+            url: url.clone(),
+            headers,
+            validator,
+            total: content_length(res_headers).unwrap_or(0),
+            received: 0,
+            progressed: true,
+            state: State::Reading(body),
+        }
+    }
+
+    fn resume(&mut self) -> ResponseFuture {
+        let range = format!("bytes={}-", self.received);
+        let mut headers = self.headers.clone();
+        headers.insert(RANGE, HeaderValue::from_str(&range).expect("valid range"));
+        if let Some(ref validator) = self.validator {
+            headers.insert(IF_RANGE, validator.clone());
+        }
+
+        let mut req = hyper::Request::builder()
+            .method(Method::GET)
+            .uri(expect_uri(&self.url))
+            .body(Body::empty().into_stream())
+            .expect("valid request parts");
+        *req.headers_mut() = headers;
+
+        self.client.request(req)
+    }
+
+    fn is_continuation(&self, res: &hyper::Response<hyper::Body>) -> bool {
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            return false;
+        }
+
+        let start = format!("bytes {}-", self.received);
+        let end = format!("/{}", self.total);
+        let range = res
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok());
+        match range {
+            Some(range) => range.starts_with(&start) && range.ends_with(&end),
+            None => false,
+        }
+    }
+}
+
+impl HttpBody for Resume {
+    type Data = Bytes;
+    type Error = hyper::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        loop {
+            let next = match self.state {
+                State::Reading(ref mut body) => {
+                    match futures_core::ready!(Pin::new(body).poll_data(cx)) {
+                        Some(Ok(chunk)) => {
+                            self.received += chunk.len() as u64;
+                            self.progressed = true;
+                            return Poll::Ready(Some(Ok(chunk)));
+                        }
+                        Some(Err(err)) => {
+                            if self.received >= self.total || !self.progressed {
+                                return Poll::Ready(Some(Err(err)));
+                            }
+                            debug!(
+                                "resuming body of {} at byte {} after error: {}",
+                                self.url, self.received, err
+                            );
+                            State::Resuming(Mutex::new(self.resume()), Some(err))
+                        }
+                        None => return Poll::Ready(None),
+                    }
+                }
+                State::Resuming(ref mut fut, ref mut err) => {
+                    let fut = fut.get_mut().expect("lock poisoned");
+                    let res = futures_core::ready!(Pin::new(fut).poll(cx));
+                    let err = err.take().expect("polled after error");
+                    match res {
+                        Ok(res) => {
+                            if !self.is_continuation(&res) {
+                                debug!("server didn't resume body: {}", res.status());
+                                return Poll::Ready(Some(Err(err)));
+                            }
+                            self.progressed = false;
+                            State::Reading(res.into_body())
+                        }
+                        Err(resume_err) => {
+                            debug!("error resuming body: {}", resume_err);
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                    }
+                }
+            };
+            self.state = next;
+        }
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        match self.state {
+            State::Reading(ref mut body) => Pin::new(body).poll_trailers(cx),
+            State::Resuming(..) => Poll::Ready(Ok(None)),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match self.state {
+            State::Reading(ref body) => self.received >= self.total && body.is_end_stream(),
+            State::Resuming(..) => false,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.total.saturating_sub(self.received))
+    }
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
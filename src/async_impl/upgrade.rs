@@ -0,0 +1,65 @@
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use hyper::upgrade::OnUpgrade;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// An upgraded HTTP connection.
+///
+/// This is the raw byte stream left after a successful `CONNECT` or
+/// `101 Switching Protocols` response. See [`Client::connect`] and
+/// [`Response::upgrade`].
+///
+/// [`Client::connect`]: crate::Client::connect
+/// [`Response::upgrade`]: crate::Response::upgrade
+pub struct Upgraded {
+    inner: hyper::upgrade::Upgraded,
+}
+
+/// The pending upgrade of a response, kept in its extensions.
+pub(super) struct PendingUpgrade(pub(super) OnUpgrade);
+
+impl AsyncRead for Upgraded {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Upgraded {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Upgraded").finish()
+    }
+}
+
+impl PendingUpgrade {
+    pub(super) async fn wait(self) -> crate::Result<Upgraded> {
+        self.0
+            .await
+            .map(|inner| Upgraded { inner })
+            .map_err(crate::error::upgrade)
+    }
+}
@@ -1,13 +1,15 @@
+use std::borrow::Borrow;
 use std::fmt;
 use std::fs::File;
 use std::future::Future;
-use std::io::{self, Cursor, Read};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
 use std::mem::{self, MaybeUninit};
 use std::ptr;
 
 use bytes::Bytes;
 
 use crate::async_impl;
+use crate::header::HeaderValue;
 
 /// The body of a `Request`.
 ///
@@ -19,6 +21,7 @@ use crate::async_impl;
 #[derive(Debug)]
 pub struct Body {
     kind: Kind,
+    content_type: Option<HeaderValue>,
 }
 
 impl Body {
@@ -32,6 +35,11 @@ impl Body {
     /// request at the new location, the `Response` will be returned with
     /// the redirect status code set.
     ///
+    /// A `Body` made from a `File` with `Body::from` can be cloned with
+    /// `Request::try_clone` instead, since the file can be read again.
+    /// Each clone reads the file at its own position, so they can be sent
+    /// at the same time.
+    ///
     /// ```rust
     /// # use std::fs::File;
     /// # use reqwest::blocking::Body;
@@ -57,6 +65,7 @@ impl Body {
     pub fn new<R: Read + Send + 'static>(reader: R) -> Body {
         Body {
             kind: Kind::Reader(Box::from(reader), None),
+            content_type: None,
         }
     }
 
@@ -77,6 +86,91 @@ impl Body {
     pub fn sized<R: Read + Send + 'static>(reader: R, len: u64) -> Body {
         Body {
             kind: Kind::Reader(Box::from(reader), Some(len)),
+            content_type: None,
+        }
+    }
+
+    /// Create a streaming `Body` from a `Read`, calling `inspect` with
+    /// every chunk of bytes as it is read.
+    ///
+    /// This allows computing a checksum (or progress, or anything else) of
+    /// the data while it is uploaded, instead of reading the source twice.
+    /// Once the request body has been fully sent, `inspect` has seen every
+    /// byte exactly once. If `len` is `Some`, it is used as the
+    /// `Content-Length`, as with `Body::sized`.
+    ///
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use std::hash::Hasher;
+    /// # use std::sync::{Arc, Mutex};
+    /// # use reqwest::blocking::Body;
+    /// # fn run() -> Result<(), Box<std::error::Error>> {
+    /// let file = File::open("a_large_file.txt")?;
+    /// let file_size = file.metadata()?.len();
+    ///
+    /// let hasher = Arc::new(Mutex::new(std::collections::hash_map::DefaultHasher::new()));
+    /// let update = hasher.clone();
+    /// let body = Body::from_reader_inspected(file, Some(file_size), move |chunk| {
+    ///     update.lock().unwrap().write(chunk);
+    /// });
+    ///
+    /// let client = reqwest::blocking::Client::new();
+    /// client.put("http://httpbin.org/put").body(body).send()?;
+    /// let checksum = hasher.lock().unwrap().finish();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_reader_inspected<R, F>(reader: R, len: Option<u64>, inspect: F) -> Body
+    where
+        R: Read + Send + 'static,
+        F: FnMut(&[u8]) + Send + 'static,
+    {
+        Body {
+            kind: Kind::Reader(Box::new(Inspect { reader, inspect }), len),
+            content_type: None,
+        }
+    }
+
+    /// Attach a `Content-Type` to this `Body`.
+    ///
+    /// When the body is sent, the value is used as the request's
+    /// `Content-Type` header, unless the request already sets one.
+    ///
+    /// ```rust
+    /// # use reqwest::blocking::Body;
+    /// # use reqwest::header::HeaderValue;
+    /// let body = Body::from("<p>hi</p>")
+    ///     .with_content_type(HeaderValue::from_static("text/html"));
+    /// ```
+    pub fn with_content_type(mut self, content_type: HeaderValue) -> Body {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Returns the `Content-Type` attached to this `Body`, if any.
+    pub fn content_type(&self) -> Option<&HeaderValue> {
+        self.content_type.as_ref()
+    }
+
+    /// Create an empty `Body`.
+    ///
+    /// Unlike sending a request without a body, this states explicitly
+    /// that the body has no content, so methods like `POST` are sent with
+    /// `Content-Length: 0`.
+    pub fn empty() -> Body {
+        Body {
+            kind: Kind::Bytes(Bytes::new()),
+            content_type: None,
+        }
+    }
+
+    /// Returns whether this `Body` has no content.
+    ///
+    /// For bodies created from a reader this method returns `None`.
+    pub fn is_empty(&self) -> Option<bool> {
+        match self.kind {
+            Kind::Reader(..) | Kind::File(..) => None,
+            Kind::Bytes(ref bytes) => Some(bytes.is_empty()),
         }
     }
 
@@ -84,7 +178,7 @@ impl Body {
     /// memory. For streamed requests this method returns `None`.
     pub fn as_bytes(&self) -> Option<&[u8]> {
         match self.kind {
-            Kind::Reader(_, _) => None,
+            Kind::Reader(..) | Kind::File(..) => None,
             Kind::Bytes(ref bytes) => Some(bytes.as_ref()),
         }
     }
@@ -96,25 +190,28 @@ impl Body {
     /// Be aware that for large requests this method is expensive
     /// and may cause your program to run out of memory.
     pub fn buffer(&mut self) -> Result<&[u8], crate::Error> {
-        match self.kind {
-            Kind::Reader(ref mut reader, maybe_len) => {
-                let mut bytes = if let Some(len) = maybe_len {
-                    Vec::with_capacity(len as usize)
-                } else {
-                    Vec::new()
-                };
-                io::copy(reader, &mut bytes)
-                    .map_err(crate::error::builder)?;
-                self.kind = Kind::Bytes(bytes.into());
-                self.buffer()
-            },
-            Kind::Bytes(ref bytes) => Ok(bytes.as_ref()),
-        }
+        let mut file_reader;
+        let (reader, maybe_len): (&mut dyn Read, _) = match self.kind {
+            Kind::Reader(ref mut reader, maybe_len) => (reader, maybe_len),
+            Kind::File(ref file, start, maybe_len) => {
+                file_reader = FileReader::new(file, start);
+                (&mut file_reader, maybe_len)
+            }
+            Kind::Bytes(ref bytes) => return Ok(bytes.as_ref()),
+        };
+        let mut bytes = if let Some(len) = maybe_len {
+            Vec::with_capacity(len as usize)
+        } else {
+            Vec::new()
+        };
+        io::copy(reader, &mut bytes).map_err(crate::error::builder)?;
+        self.kind = Kind::Bytes(bytes.into());
+        self.buffer()
     }
 
     pub(crate) fn len(&self) -> Option<u64> {
         match self.kind {
-            Kind::Reader(_, len) => len,
+            Kind::Reader(_, len) | Kind::File(_, _, len) => len,
             Kind::Bytes(ref bytes) => Some(bytes.len() as u64),
         }
     }
@@ -122,34 +219,51 @@ impl Body {
     pub(crate) fn into_reader(self) -> Reader {
         match self.kind {
             Kind::Reader(r, _) => Reader::Reader(r),
+            Kind::File(file, start, _) => Reader::Reader(Box::new(FileReader::new(file, start))),
             Kind::Bytes(b) => Reader::Bytes(Cursor::new(b)),
         }
     }
 
     pub(crate) fn into_async(self) -> (Option<Sender>, async_impl::Body, Option<u64>) {
-        match self.kind {
-            Kind::Reader(read, len) => {
-                let (tx, rx) = hyper::Body::channel();
-                let tx = Sender {
-                    body: (read, len),
-                    tx,
-                };
-                (Some(tx), async_impl::Body::wrap(rx), len)
-            }
+        let (tx, body, len) = match self.kind {
+            Kind::Reader(read, len) => channel(read, len),
+            Kind::File(file, start, len) => channel(Box::new(FileReader::new(file, start)), len),
             Kind::Bytes(chunk) => {
                 let len = chunk.len() as u64;
                 (None, async_impl::Body::reusable(chunk), Some(len))
             }
+        };
+        match self.content_type {
+            Some(content_type) => (tx, body.with_content_type(content_type), len),
+            None => (tx, body, len),
         }
     }
 
     pub(crate) fn try_clone(&self) -> Option<Body> {
-        self.kind.try_clone().map(|kind| Body { kind })
+        self.kind.try_clone().map(|kind| Body {
+            kind,
+            content_type: self.content_type.clone(),
+        })
     }
 }
 
+fn channel(
+    read: Box<dyn Read + Send>,
+    len: Option<u64>,
+) -> (Option<Sender>, async_impl::Body, Option<u64>) {
+    let (tx, rx) = hyper::Body::channel();
+    let tx = Sender {
+        body: (read, len),
+        tx,
+    };
+    (Some(tx), async_impl::Body::wrap(rx), len)
+}
+
 enum Kind {
     Reader(Box<dyn Read + Send>, Option<u64>),
+    // A file, which is read from the `u64` offset it started at, so it can
+    // be cloned and read again.
+    File(File, u64, Option<u64>),
     Bytes(Bytes),
 }
 
@@ -157,6 +271,10 @@ impl Kind {
     fn try_clone(&self) -> Option<Kind> {
         match self {
             Kind::Reader(..) => None,
+            Kind::File(file, start, len) => file
+                .try_clone()
+                .ok()
+                .map(|file| Kind::File(file, *start, *len)),
             Kind::Bytes(v) => Some(Kind::Bytes(v.clone())),
         }
     }
@@ -167,6 +285,7 @@ impl From<Vec<u8>> for Body {
     fn from(v: Vec<u8>) -> Body {
         Body {
             kind: Kind::Bytes(v.into()),
+            content_type: None,
         }
     }
 }
@@ -183,6 +302,7 @@ impl From<&'static [u8]> for Body {
     fn from(s: &'static [u8]) -> Body {
         Body {
             kind: Kind::Bytes(Bytes::from_static(s)),
+            content_type: None,
         }
     }
 }
@@ -195,11 +315,19 @@ impl From<&'static str> for Body {
 }
 
 impl From<File> for Body {
+    // `Seek::stream_position` isn't available on the MSRV.
+    #[allow(clippy::seek_from_current)]
     #[inline]
-    fn from(f: File) -> Body {
+    fn from(mut f: File) -> Body {
         let len = f.metadata().map(|m| m.len()).ok();
+        let kind = match f.seek(SeekFrom::Current(0)) {
+            Ok(start) => Kind::File(f, start, len),
+            // not seekable, such as a pipe
+            Err(_) => Kind::Reader(Box::new(f), len),
+        };
         Body {
-            kind: Kind::Reader(Box::new(f), len),
+            kind,
+            content_type: None,
         }
     }
 }
@@ -211,6 +339,10 @@ impl fmt::Debug for Kind {
                 .debug_struct("Reader")
                 .field("length", &DebugLength(v))
                 .finish(),
+            Kind::File(_, _, ref v) => f
+                .debug_struct("File")
+                .field("length", &DebugLength(v))
+                .finish(),
             Kind::Bytes(ref v) => fmt::Debug::fmt(v, f),
         }
     }
@@ -227,6 +359,64 @@ impl<'a> fmt::Debug for DebugLength<'a> {
     }
 }
 
+struct Inspect<R, F> {
+    reader: R,
+    inspect: F,
+}
+
+impl<R: Read, F: FnMut(&[u8])> Read for Inspect<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        if n > 0 {
+            (self.inspect)(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// Reads a `File` from `start`.
+///
+/// Clones of a `File` share its offset, so reads are positioned at an
+/// offset tracked by each reader instead, and don't move the shared one.
+struct FileReader<F> {
+    file: F,
+    pos: u64,
+}
+
+impl<F: Borrow<File>> FileReader<F> {
+    fn new(file: F, start: u64) -> FileReader<F> {
+        FileReader { file, pos: start }
+    }
+}
+
+impl<F: Borrow<File>> Read for FileReader<F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = read_at(self.file.borrow(), buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+
+    file.read_at(buf, pos)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    use std::os::windows::fs::FileExt;
+
+    file.seek_read(buf, pos)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_at(mut file: &File, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+    file.seek(SeekFrom::Start(pos))?;
+    file.read(buf)
+}
+
 pub(crate) enum Reader {
     Reader(Box<dyn Read + Send>),
     Bytes(Cursor<Bytes>),
@@ -330,11 +520,42 @@ impl Sender {
 
 // useful for tests, but not publicly exposed
 #[cfg(test)]
-pub(crate) fn read_to_string(mut body: Body) -> io::Result<String> {
+pub(crate) fn read_to_string(body: Body) -> io::Result<String> {
     let mut s = String::new();
-    match body.kind {
-        Kind::Reader(ref mut reader, _) => reader.read_to_string(&mut s),
-        Kind::Bytes(ref mut bytes) => (&**bytes).read_to_string(&mut s),
+    body.into_reader().read_to_string(&mut s).map(|_| s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Body;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn file_clones_read_independently() {
+        let path = std::env::temp_dir().join("reqwest-test-body-file-clones.txt");
+        std::fs::File::create(&path)
+            .and_then(|mut file| file.write_all(b"hello world"))
+            .unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+
+        let body = Body::from(file);
+        let clone = body.try_clone().expect("file body is cloneable");
+        let mut a = body.into_reader();
+        let mut b = clone.into_reader();
+
+        // Interleaved reads, as when both are sent at the same time.
+        let (mut buf_a, mut buf_b) = ([0; 6], [0; 6]);
+        a.read_exact(&mut buf_a).unwrap();
+        b.read_exact(&mut buf_b).unwrap();
+        assert_eq!(&buf_a, b"hello ");
+        assert_eq!(&buf_b, b"hello ");
+
+        let (mut rest_a, mut rest_b) = (String::new(), String::new());
+        a.read_to_string(&mut rest_a).unwrap();
+        b.read_to_string(&mut rest_b).unwrap();
+        assert_eq!(rest_a, "world");
+        assert_eq!(rest_b, "world");
+
+        std::fs::remove_file(&path).ok();
     }
-    .map(|_| s)
 }
@@ -6,7 +6,8 @@ use std::any::Any;
 use std::convert::TryInto;
 use std::fmt;
 use std::future::Future;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
@@ -18,7 +19,7 @@ use tokio::sync::{mpsc, oneshot};
 use super::request::{Request, RequestBuilder};
 use super::response::Response;
 use super::wait;
-use crate::{async_impl, header, IntoUrl, Method, Proxy, redirect};
+use crate::{async_impl, header, ContentEncoding, IntoUrl, Method, Proxy, redirect, retry, TimeoutKind};
 #[cfg(feature = "__tls")]
 use crate::{Certificate, Identity};
 
@@ -127,8 +128,28 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.user_agent(value))
     }
 
+    /// Sends a `reqwest/<version>` `User-Agent` when none is otherwise set.
+    ///
+    /// The default is only used if neither [`user_agent`] nor
+    /// [`default_headers`] configured one, and a `User-Agent` set on an
+    /// individual request still takes precedence.
+    ///
+    /// Default is off, meaning no `User-Agent` is sent unless one is set.
+    ///
+    /// [`user_agent`]: ClientBuilder::user_agent
+    /// [`default_headers`]: ClientBuilder::default_headers
+    pub fn default_user_agent_if_unset(self) -> ClientBuilder {
+        self.with_inner(|inner| inner.default_user_agent_if_unset())
+    }
+
     /// Sets the default headers for every request.
     ///
+    /// A request that sets a header itself replaces the default values of
+    /// that header. See `default_headers_merge` to keep both.
+    ///
+    /// `Authorization`, `Cookie` and `Proxy-Authorization` values are marked
+    /// as sensitive.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -169,6 +190,26 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.default_headers(headers))
     }
 
+    /// Sets default headers that are merged with the headers of each request.
+    ///
+    /// Unlike `default_headers`, a request that sets one of these headers
+    /// itself doesn't replace the defaults: its values are appended after
+    /// the default values.
+    pub fn default_headers_merge(self, headers: header::HeaderMap) -> ClientBuilder {
+        self.with_inner(move |inner| inner.default_headers_merge(headers))
+    }
+
+    /// Send the headers of each request in the order they were added to it.
+    ///
+    /// Headers the client adds, such as default headers not already set on
+    /// the request, follow the request's own headers. This copies the
+    /// headers of every request into a new map before sending them.
+    ///
+    /// Default is `false`.
+    pub fn preserve_header_order(self, enable: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.preserve_header_order(enable))
+    }
+
     /// Enable a persistent cookie store for the client.
     ///
     /// Cookies received in responses will be preserved and included in
@@ -184,6 +225,45 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.cookie_store(enable))
     }
 
+    /// Use a shared cookie store for the client.
+    ///
+    /// This enables a persistent cookie store, like `cookie_store(true)`,
+    /// which is shared with every other client built with the same handle.
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let cookies = reqwest::cookie::CookieStoreHandle::new();
+    /// let a = reqwest::blocking::Client::builder()
+    ///     .cookie_store_handle(cookies.clone())
+    ///     .build()?;
+    /// let b = reqwest::blocking::Client::builder()
+    ///     .cookie_store_handle(cookies)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cookies` feature to be enabled.
+    #[cfg(feature = "cookies")]
+    pub fn cookie_store_handle(self, handle: crate::cookie::CookieStoreHandle) -> ClientBuilder {
+        self.with_inner(|inner| inner.cookie_store_handle(handle))
+    }
+
+    /// Bound the number of cookies kept by the cookie store.
+    ///
+    /// When more than `max` cookies are stored, the least recently used
+    /// ones are evicted. This has no effect unless a cookie store is enabled.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `cookies` feature to be enabled.
+    #[cfg(feature = "cookies")]
+    pub fn cookie_store_capacity(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.cookie_store_capacity(max))
+    }
+
     /// Enable auto gzip decompression by checking the `Content-Encoding` response header.
     ///
     /// If auto gzip decompresson is turned on:
@@ -194,6 +274,8 @@ impl ClientBuilder {
     /// - When receiving a response, if it's headers contain a `Content-Encoding` value that
     ///   equals to `gzip`, both values `Content-Encoding` and `Content-Length` are removed from the
     ///   headers' set. The response body is automatically decompressed.
+    /// - Responses to requests with a `Range` header are not decompressed, since
+    ///   a partial body can't be decoded on its own.
     ///
     /// If the `gzip` feature is turned on, the default option is enabled.
     ///
@@ -214,6 +296,37 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.no_gzip())
     }
 
+    /// Set the order in which encodings are advertised in `Accept-Encoding`.
+    ///
+    /// Encodings missing from `order` keep their default position after the
+    /// listed ones. Default is `gzip` before `br`.
+    pub fn accept_encoding_priority(self, order: Vec<ContentEncoding>) -> ClientBuilder {
+        self.with_inner(move |inner| inner.accept_encoding_priority(order))
+    }
+
+    /// Limit the size of response bodies.
+    ///
+    /// Reading a response body that is larger than `max` bytes, after any
+    /// automatic decompression, fails with an error for which
+    /// `Error::is_body_too_large()` returns `true`.
+    ///
+    /// Default is no limit.
+    pub fn max_response_size(self, max: u64) -> ClientBuilder {
+        self.with_inner(move |inner| inner.max_response_size(max))
+    }
+
+    /// Enable or disable resuming response bodies after a connection error.
+    ///
+    /// When enabled, if reading a response body fails partway, the rest of
+    /// it is requested with a `Range` request. This only applies to `GET`
+    /// and `HEAD` requests whose response has a `200 OK` status, a
+    /// `Content-Length` and an `Accept-Ranges: bytes` header.
+    ///
+    /// Default is `false`.
+    pub fn resume_downloads(self, enable: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.resume_downloads(enable))
+    }
+
     // Redirect options
 
     /// Set a `redirect::Policy` for this client.
@@ -223,6 +336,15 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.redirect(policy))
     }
 
+    // Retry options
+
+    /// Set a `retry::Policy` for this client.
+    ///
+    /// Default doesn't retry requests.
+    pub fn retry(self, policy: retry::Policy) -> ClientBuilder {
+        self.with_inner(move |inner| inner.retry(policy))
+    }
+
     /// Enable or disable automatic setting of the `Referer` header.
     ///
     /// Default is `true`.
@@ -230,13 +352,24 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.referer(enable))
     }
 
+    /// Enable or disable omitting the query string from the `Referer` header.
+    ///
+    /// When enabled, the `Referer` sent on a redirect only contains the
+    /// origin and path of the previous URL.
+    ///
+    /// Default is `false`.
+    pub fn referer_trim_query(self, enable: bool) -> ClientBuilder {
+        self.with_inner(|inner| inner.referer_trim_query(enable))
+    }
+
     // Proxy options
 
     /// Add a `Proxy` to the list of proxies the `Client` will use.
     ///
     /// # Note
     ///
-    /// Adding a proxy will disable the automatic usage of the "system" proxy.
+    /// Adding a proxy will disable the automatic usage of the "system" proxy,
+    /// unless it has been explicitly enabled with `system_proxy(true)`.
     pub fn proxy(self, proxy: Proxy) -> ClientBuilder {
         self.with_inner(move |inner| inner.proxy(proxy))
     }
@@ -248,6 +381,18 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.no_proxy())
     }
 
+    /// Enable or disable the "system" proxy, configured from environment
+    /// variables such as `HTTP_PROXY` and `HTTPS_PROXY`.
+    ///
+    /// When enabled, the system proxy is consulted after any proxies added
+    /// with `proxy()`, so it can be used as a fallback. When disabled, the
+    /// system proxy is not used even if no other proxy is configured.
+    ///
+    /// By default, the system proxy is used only if no proxy has been added.
+    pub fn system_proxy(self, enable: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.system_proxy(enable))
+    }
+
     #[doc(hidden)]
     #[deprecated(note = "the system proxy is used automatically")]
     pub fn use_sys_proxy(self) -> ClientBuilder {
@@ -258,6 +403,9 @@ impl ClientBuilder {
 
     /// Set a timeout for connect, read and write operations of a `Client`.
     ///
+    /// The timeout starts when the request is sent, so time spent waiting
+    /// for a pooled or new connection counts against it as well.
+    ///
     /// Default is 30 seconds.
     ///
     /// Pass `None` to disable timeout.
@@ -271,6 +419,12 @@ impl ClientBuilder {
 
     /// Set a timeout for only the connect phase of a `Client`.
     ///
+    /// The timeout bounds the whole connect phase, including DNS resolution,
+    /// every address tried when a host resolves to several (IPv6 and IPv4
+    /// attempts race using Happy Eyeballs), and any TLS handshake. It is not
+    /// applied to each address attempt separately. The request `timeout`, if
+    /// set, still bounds the request as a whole.
+    ///
     /// Default is `None`.
     pub fn connect_timeout<T>(self, timeout: T) -> ClientBuilder
     where
@@ -288,6 +442,9 @@ impl ClientBuilder {
     ///
     /// Enabling this option will emit [log][] messages at the `TRACE` level
     /// for read and write operations on connections.
+    /// It also logs, at the `DEBUG` level, the HTTP version negotiated for
+    /// each request, which can be used to confirm HTTP/2 was used.
+    /// The version is available afterwards from `Response::version`.
     ///
     /// [log]: https://crates.io/crates/log
     pub fn connection_verbose(self, verbose: bool) -> ClientBuilder {
@@ -313,6 +470,28 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.pool_max_idle_per_host(max))
     }
 
+    /// Sets the maximum number of requests in flight to any single host.
+    ///
+    /// Requests over the limit wait for an earlier request to the same host
+    /// and port to finish before they are sent. A request is in flight until
+    /// its response body has been read or dropped.
+    ///
+    /// Default is no limit. Building the `Client` fails if `max` is zero.
+    pub fn concurrency_limit_per_host(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.concurrency_limit_per_host(max))
+    }
+
+    /// Sets the maximum number of requests a connection serves before it's
+    /// closed.
+    ///
+    /// A connection is closed once the body of its last response has been
+    /// read or dropped. Only HTTP/1 connections are limited.
+    ///
+    /// Default is no limit. Building the `Client` fails if `max` is zero.
+    pub fn connection_reuse_max_requests(self, max: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.connection_reuse_max_requests(max))
+    }
+
     #[doc(hidden)]
     #[deprecated(note = "use pool_max_idle_per_host instead")]
     pub fn max_idle_per_host(self, max: usize) -> ClientBuilder {
@@ -324,11 +503,27 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.http1_title_case_headers())
     }
 
+    /// Set whether HTTP/1 connections should try to use vectored writes,
+    /// or always flatten into a single buffer.
+    ///
+    /// Default is to let hyper guess which mode to use.
+    pub fn http1_writev(self, enable: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.http1_writev(enable))
+    }
+
     /// Only use HTTP/2.
     pub fn http2_prior_knowledge(self) -> ClientBuilder {
         self.with_inner(|inner| inner.http2_prior_knowledge())
     }
 
+    /// Only use HTTP/2 for `https` URLs, and HTTP/1 for `http` URLs.
+    ///
+    /// Unlike `http2_prior_knowledge`, this doesn't use cleartext HTTP/2
+    /// (`h2c`), which few servers accept.
+    pub fn http2_prior_knowledge_https_only(self) -> ClientBuilder {
+        self.with_inner(|inner| inner.http2_prior_knowledge_https_only())
+    }
+
     /// Sets the `SETTINGS_INITIAL_WINDOW_SIZE` option for HTTP2 stream-level flow control.
     ///
     /// Default is currently 65,535 but may change internally to optimize for common uses.
@@ -364,6 +559,23 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.tcp_nodelay_(enabled))
     }
 
+    /// Set `TCP_USER_TIMEOUT` on sockets of new connections.
+    ///
+    /// This bounds how long transmitted data may remain unacknowledged
+    /// before the connection is dropped. Unlike the request timeouts it
+    /// applies to the connection itself, and it complements TCP keepalive
+    /// in detecting dead peers on long-lived connections.
+    ///
+    /// Default is `None`, which leaves the system default in place.
+    ///
+    /// # Platform
+    ///
+    /// This is only supported on Linux. On other platforms, setting it
+    /// causes `build()` to return an error.
+    pub fn tcp_user_timeout(self, timeout: Duration) -> ClientBuilder {
+        self.with_inner(move |inner| inner.tcp_user_timeout(timeout))
+    }
+
     /// Bind to a local IP Address.
     ///
     /// # Example
@@ -382,6 +594,20 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.local_address(addr))
     }
 
+    /// Bind to a local IP Address for each address family.
+    ///
+    /// Connections to IPv4 addresses are bound to `ipv4`, and connections
+    /// to IPv6 addresses to `ipv6`. A family without an address isn't bound.
+    ///
+    /// This replaces any address set with `local_address`.
+    pub fn local_addresses(
+        self,
+        ipv4: Option<Ipv4Addr>,
+        ipv6: Option<Ipv6Addr>,
+    ) -> ClientBuilder {
+        self.with_inner(move |inner| inner.local_addresses(ipv4, ipv6))
+    }
+
     // TLS options
 
     /// Add a custom root certificate.
@@ -437,10 +663,14 @@ impl ClientBuilder {
     /// site will be trusted for use from any other. This introduces a
     /// significant vulnerability to man-in-the-middle attacks.
     ///
+    /// The certificate chain is still verified, unless
+    /// `danger_accept_invalid_certs` is also used.
+    ///
     /// # Optional
     ///
-    /// This requires the optional `native-tls` feature to be enabled.
-    #[cfg(feature = "native-tls")]
+    /// This requires the optional `native-tls` or `rustls-tls` feature to be
+    /// enabled.
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
     pub fn danger_accept_invalid_hostnames(self, accept_invalid_hostname: bool) -> ClientBuilder {
         self.with_inner(|inner| inner.danger_accept_invalid_hostnames(accept_invalid_hostname))
     }
@@ -461,6 +691,23 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.danger_accept_invalid_certs(accept_invalid_certs))
     }
 
+    /// Skip certificate validation for connections to `hosts` only.
+    ///
+    /// Certificates of all other hosts are still validated.
+    ///
+    /// # Warning
+    ///
+    /// Any certificate is trusted for the listed hosts. This only works
+    /// with the `rustls` backend.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `rustls-tls` feature to be enabled.
+    #[cfg(feature = "rustls-tls")]
+    pub fn danger_accept_invalid_certs_for(self, hosts: Vec<String>) -> ClientBuilder {
+        self.with_inner(move |inner| inner.danger_accept_invalid_certs_for(hosts))
+    }
+
     /// Force using the native TLS backend.
     ///
     /// Since multiple TLS backends can be optionally enabled, this option will
@@ -487,6 +734,32 @@ impl ClientBuilder {
         self.with_inner(move |inner| inner.use_rustls_tls())
     }
 
+    /// Set how many TLS sessions are kept for resumption.
+    ///
+    /// Passing `0` disables session resumption. Default is a cache of 32
+    /// sessions.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `rustls-tls` feature to be enabled.
+    #[cfg(feature = "rustls-tls")]
+    pub fn tls_session_cache(self, capacity: usize) -> ClientBuilder {
+        self.with_inner(move |inner| inner.tls_session_cache(capacity))
+    }
+
+    /// Enable sending TLS 1.3 early data (0-RTT) on resumed sessions.
+    ///
+    /// Only `GET` and `HEAD` requests are sent as early data. Default is
+    /// `false`.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `rustls-tls` feature to be enabled.
+    #[cfg(feature = "rustls-tls")]
+    pub fn tls_early_data(self, enable: bool) -> ClientBuilder {
+        self.with_inner(move |inner| inner.tls_early_data(enable))
+    }
+
     /// Use a preconfigured TLS backend.
     ///
     /// If the passed `Any` argument is not a TLS backend that reqwest
@@ -534,6 +807,22 @@ impl ClientBuilder {
         self.with_inner(|inner| inner.no_trust_dns())
     }
 
+    /// Override DNS resolution for a single domain to the given address.
+    ///
+    /// Overrides set here take precedence over entries loaded with
+    /// `resolve_from_hosts_file`.
+    pub fn resolve(self, domain: &str, addr: IpAddr) -> ClientBuilder {
+        self.with_inner(move |inner| inner.resolve(domain, addr))
+    }
+
+    /// Override DNS resolution with the entries of a hosts file.
+    ///
+    /// The file is read when the client is built, and `build()` fails if it
+    /// can't be read or parsed.
+    pub fn resolve_from_hosts_file(self, path: PathBuf) -> ClientBuilder {
+        self.with_inner(move |inner| inner.resolve_from_hosts_file(path))
+    }
+
     // private
 
     fn with_inner<F>(mut self, func: F) -> ClientBuilder
@@ -663,6 +952,31 @@ impl Client {
     pub fn execute(&self, request: Request) -> crate::Result<Response> {
         self.inner.execute_request(request)
     }
+
+    /// Returns a snapshot of the configuration this `Client` was built with.
+    ///
+    /// Unlike the async `Client`, the reported `timeout` defaults to 30
+    /// seconds.
+    pub fn config(&self) -> &crate::ClientConfig {
+        &self.inner.config
+    }
+
+    /// Stops the background runtime of this `Client`, waiting at most
+    /// `timeout` for it to finish.
+    ///
+    /// Dropping the last `Client` stops the runtime as well, but waits for
+    /// it without a limit, which can block if a request is stuck (such as
+    /// in DNS resolution). If the runtime thread does not finish within
+    /// `timeout`, it is abandoned and left to finish on its own.
+    ///
+    /// The runtime can only be stopped once no clone of this `Client`, nor
+    /// any `Response` it returned, is still alive. Otherwise this simply
+    /// drops the `Client`.
+    ///
+    /// Returns `true` if the runtime thread finished in time.
+    pub fn shutdown_timeout(self, timeout: Duration) -> bool {
+        self.inner.shutdown_timeout(timeout)
+    }
 }
 
 impl fmt::Debug for Client {
@@ -684,6 +998,7 @@ impl fmt::Debug for ClientBuilder {
 #[derive(Clone)]
 struct ClientHandle {
     timeout: Timeout,
+    config: async_impl::ClientConfig,
     inner: Arc<InnerClientHandle>,
 }
 
@@ -693,20 +1008,38 @@ type ThreadSender = mpsc::UnboundedSender<(async_impl::Request, OneshotResponse)
 struct InnerClientHandle {
     tx: Option<ThreadSender>,
     thread: Option<thread::JoinHandle<()>>,
+    done: Option<oneshot::Receiver<()>>,
 }
 
-impl Drop for InnerClientHandle {
-    fn drop(&mut self) {
-        let id = self.thread
-            .as_ref()
-            .map(|h| h.thread().id())
-            .expect("thread not dropped yet");
+impl InnerClientHandle {
+    fn shutdown(&mut self, timeout: Option<Duration>) -> bool {
+        let thread = match self.thread.take() {
+            Some(thread) => thread,
+            None => return true,
+        };
+        let id = thread.thread().id();
 
         trace!("closing runtime thread ({:?})", id);
         self.tx.take();
         trace!("signaled close for runtime thread ({:?})", id);
-        self.thread.take().map(|h| h.join());
+
+        if let (Some(timeout), Some(done)) = (timeout, self.done.take()) {
+            // The sender is dropped when the thread is about to exit.
+            if let Err(wait::Waited::TimedOut) = wait::timeout(done, Some(timeout)) {
+                trace!("abandoned runtime thread ({:?})", id);
+                return false;
+            }
+        }
+
+        let _ = thread.join();
         trace!("closed runtime thread ({:?})", id);
+        true
+    }
+}
+
+impl Drop for InnerClientHandle {
+    fn drop(&mut self) {
+        self.shutdown(None);
     }
 }
 
@@ -715,10 +1048,12 @@ impl ClientHandle {
         let timeout = builder.timeout;
         let builder = builder.inner;
         let (tx, rx) = mpsc::unbounded_channel::<(async_impl::Request, OneshotResponse)>();
-        let (spawn_tx, spawn_rx) = oneshot::channel::<crate::Result<()>>();
+        let (spawn_tx, spawn_rx) = oneshot::channel::<crate::Result<async_impl::ClientConfig>>();
+        let (done_tx, done_rx) = oneshot::channel::<()>();
         let handle = thread::Builder::new()
             .name("reqwest-internal-sync-runtime".into())
             .spawn(move || {
+                let _done = done_tx;
                 use tokio::runtime;
                 let mut rt = match runtime::Builder::new().basic_scheduler().enable_all().build().map_err(crate::error::builder) {
                     Err(e) => {
@@ -740,7 +1075,7 @@ impl ClientHandle {
                         }
                         Ok(v) => v,
                     };
-                    if let Err(e) = spawn_tx.send(Ok(())) {
+                    if let Err(e) = spawn_tx.send(Ok(client.config().clone())) {
                         error!("Failed to communicate successful startup: {:?}", e);
                         return;
                     }
@@ -764,23 +1099,32 @@ impl ClientHandle {
             .map_err(crate::error::builder)?;
 
         // Wait for the runtime thread to start up...
-        match wait::timeout(spawn_rx, None) {
-            Ok(Ok(())) => (),
+        let config = match wait::timeout(spawn_rx, None) {
+            Ok(Ok(config)) => config.with_timeout(timeout.0),
             Ok(Err(err)) => return Err(err),
             Err(_canceled) => event_loop_panicked(),
-        }
+        };
 
         let inner_handle = Arc::new(InnerClientHandle {
             tx: Some(tx),
             thread: Some(handle),
+            done: Some(done_rx),
         });
 
         Ok(ClientHandle {
             timeout,
+            config,
             inner: inner_handle,
         })
     }
 
+    fn shutdown_timeout(self, timeout: Duration) -> bool {
+        match Arc::try_unwrap(self.inner) {
+            Ok(mut inner) => inner.shutdown(Some(timeout)),
+            Err(_) => false,
+        }
+    }
+
     fn execute_request(&self, req: Request) -> crate::Result<Response> {
         let (tx, rx) = oneshot::channel();
         let (req, body) = req.into_async();
@@ -815,7 +1159,10 @@ impl ClientHandle {
                 self.timeout.0,
                 KeepCoreThreadAlive(Some(self.inner.clone())),
             )),
-            Err(wait::Waited::TimedOut(e)) => Err(crate::error::request(e).with_url(url)),
+            Err(wait::Waited::TimedOut) => {
+                let err = crate::error::TimedOut(TimeoutKind::Overall);
+                Err(crate::error::request(err).with_url(url))
+            }
             Err(wait::Waited::Inner(err)) => Err(err.with_url(url)),
         }
     }
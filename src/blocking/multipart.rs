@@ -78,6 +78,31 @@ impl Form {
         self.inner.boundary()
     }
 
+    /// Returns whether any part of this form is read from a file or reader.
+    ///
+    /// A request with such a form is streamed, so it can't be cloned, and it
+    /// won't be repeated on redirects. A form of only text and bytes parts
+    /// is buffered and doesn't have these limitations.
+    pub fn is_streaming(&self) -> bool {
+        self.inner.is_streaming()
+    }
+
+    /// Use a fixed boundary instead of the randomly generated one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the boundary is not 1 to 70 characters allowed by RFC 2046,
+    /// or if it appears in the value of a part already added to the form.
+    /// Parts whose value is a reader cannot be checked.
+    pub fn with_boundary<T>(self, boundary: T) -> crate::Result<Form>
+    where
+        T: Into<String>,
+    {
+        Ok(Form {
+            inner: self.inner.with_boundary(boundary.into())?,
+        })
+    }
+
     /// Add a data field with supplied name and value.
     ///
     /// # Examples
@@ -95,6 +120,33 @@ impl Form {
         self.part(name, Part::text(value))
     }
 
+    /// Add a data field with supplied name, value and mime type.
+    ///
+    /// This is useful for fields that must declare their content type, such
+    /// as `text/plain; charset=utf-8` or `application/json`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if `mime` can't be parsed as a mime type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let form = reqwest::blocking::multipart::Form::new()
+    ///     .text("username", "seanmonstar")
+    ///     .text_with_mime("profile", r#"{"age":30}"#, "application/json")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn text_with_mime<T, U>(self, name: T, value: U, mime: &str) -> crate::Result<Form>
+    where
+        T: Into<Cow<'static, str>>,
+        U: Into<Cow<'static, str>>,
+    {
+        Ok(self.part(name, Part::text(value).mime_str(mime)?))
+    }
+
     /// Adds a file field.
     ///
     /// The path will be used to try to guess the filename and mime.
@@ -143,6 +195,22 @@ impl Form {
         self.with_inner(|inner| inner.percent_encode_noop())
     }
 
+    /// Limit the total length of the text and bytes parts of this `Form`.
+    ///
+    /// These parts are held in memory. If their total length exceeds
+    /// `bytes`, `RequestBuilder::multipart` fails with a builder error
+    /// rather than building the body. File and reader parts aren't counted.
+    ///
+    /// By default, there is no limit.
+    pub fn max_in_memory(self, bytes: usize) -> Form {
+        self.with_inner(|inner| inner.max_in_memory(bytes as u64))
+    }
+
+    /// Fails if the parts held in memory exceed `max_in_memory`.
+    pub(crate) fn check_in_memory(&self) -> crate::Result<()> {
+        self.inner.check_in_memory()
+    }
+
     pub(crate) fn reader(self) -> Reader {
         Reader::new(self)
     }
@@ -281,6 +349,10 @@ impl PartProps for Part {
         self.value.len()
     }
 
+    fn value_bytes(&self) -> Option<&[u8]> {
+        self.value.as_bytes()
+    }
+
     fn metadata(&self) -> &PartMetadata {
         &self.meta
     }
@@ -1,6 +1,7 @@
 use std::fmt;
 use std::convert::TryFrom;
-use std::time::Duration;
+use std::io::Read;
+use std::time::{Duration, SystemTime};
 
 use base64::encode;
 use http::{Request as HttpRequest, request::Parts};
@@ -12,7 +13,9 @@ use serde_urlencoded;
 use super::body::{self, Body};
 use super::multipart;
 use super::Client;
-use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use crate::header::{
+    HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, IF_MATCH, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+};
 use crate::{async_impl, Method, Url};
 
 /// A request which can be executed with `Client::execute()`.
@@ -74,6 +77,23 @@ impl Request {
         self.inner.headers_mut()
     }
 
+    /// Set the headers, replacing any existing values with the same names.
+    pub fn set_headers(&mut self, headers: HeaderMap) {
+        self.inner.set_headers(headers)
+    }
+
+    /// Get the extensions.
+    #[inline]
+    pub fn extensions(&self) -> &http::Extensions {
+        self.inner.extensions()
+    }
+
+    /// Get a mutable reference to the extensions.
+    #[inline]
+    pub fn extensions_mut(&mut self) -> &mut http::Extensions {
+        self.inner.extensions_mut()
+    }
+
     /// Get the body.
     #[inline]
     pub fn body(&self) -> Option<&Body> {
@@ -98,10 +118,24 @@ impl Request {
         self.inner.timeout_mut()
     }
 
+    /// Get the connect timeout.
+    #[inline]
+    pub fn connect_timeout(&self) -> Option<&Duration> {
+        self.inner.connect_timeout()
+    }
+
+    /// Get a mutable reference to the connect timeout.
+    #[inline]
+    pub fn connect_timeout_mut(&mut self) -> &mut Option<Duration> {
+        self.inner.connect_timeout_mut()
+    }
+
     /// Attempts to clone the `Request`.
     ///
     /// None is returned if a body is which can not be cloned. This can be because the body is a
     /// stream.
+    ///
+    /// Only extensions added with `RequestBuilder::extension` are cloned.
     pub fn try_clone(&self) -> Option<Request> {
         let body = if let Some(ref body) = self.body.as_ref() {
             if let Some(body) = body.try_clone() {
@@ -112,10 +146,27 @@ impl Request {
         } else {
             None
         };
-        let mut req = Request::new(self.method().clone(), self.url().clone());
-        *req.headers_mut() = self.headers().clone();
-        req.body = body;
-        Some(req)
+        Some(Request {
+            inner: self.inner.try_clone()?,
+            body,
+        })
+    }
+
+    /// Clone the `Request`, with `body` in place of its body.
+    ///
+    /// Unlike `try_clone`, this works when the body is a reader, for
+    /// instance to retry an upload by opening the file again. A
+    /// `Content-Length` header is removed, since it may not match the new
+    /// body.
+    ///
+    /// Only extensions added with `RequestBuilder::extension` are cloned.
+    pub fn clone_with_body<T: Into<Body>>(&self, body: T) -> crate::Result<Request> {
+        let mut inner = self.inner.clone_without_body();
+        inner.headers_mut().remove(crate::header::CONTENT_LENGTH);
+        Ok(Request {
+            inner,
+            body: Some(body.into()),
+        })
     }
 
     pub(crate) fn into_async(self) -> (async_impl::Request, Option<body::Sender>) {
@@ -174,6 +225,22 @@ impl RequestBuilder {
         self.header_sensitive(key, value, false)
     }
 
+    /// Add a `Header` to this Request if `value` is `Some`.
+    ///
+    /// When `value` is `None`, the builder is returned unchanged.
+    pub fn header_opt<K, V>(self, key: K, value: Option<V>) -> RequestBuilder
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        match value {
+            Some(value) => self.header(key, value),
+            None => self,
+        }
+    }
+
     /// Add a `Header` to this Request with ability to define if header_value is sensitive.
     fn header_sensitive<K, V>(mut self, key: K, value: V, sensitive: bool) -> RequestBuilder
     where
@@ -233,6 +300,43 @@ impl RequestBuilder {
         self
     }
 
+    /// Add each header of `iter` to this Request.
+    ///
+    /// Unlike [`headers`](RequestBuilder::headers), the values are appended
+    /// to any already set, and the pairs only need to convert into header
+    /// names and values, as with [`header`](RequestBuilder::header). The
+    /// first pair that doesn't convert is the error of the builder.
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let token = String::from("abc");
+    /// let headers = vec![("x-token", token), ("x-tenant", "acme".to_owned())];
+    ///
+    /// let client = reqwest::blocking::Client::new();
+    /// let req = client
+    ///     .get("http://httpbin.org/get")
+    ///     .header_iter(headers)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn header_iter<K, V, I>(mut self, iter: I) -> RequestBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        for (key, value) in iter {
+            if self.request.is_err() {
+                break;
+            }
+            self = self.header(key, value);
+        }
+        self
+    }
+
     /// Enable HTTP basic authentication.
     ///
     /// ```rust
@@ -257,6 +361,49 @@ impl RequestBuilder {
         self.header_sensitive(crate::header::AUTHORIZATION, &*header_value, true)
     }
 
+    /// Enable HTTP basic authentication, encoding the credentials in
+    /// `charset` instead of UTF-8.
+    ///
+    /// Some legacy servers expect the credentials in another charset,
+    /// usually ISO-8859-1. `charset` is an encoding label, such as
+    /// `"iso-8859-1"` or `"utf-8"`. For more information about the possible
+    /// labels, please go to [`encoding_rs`] docs.
+    ///
+    /// The request fails to build if the label is unknown, or the
+    /// credentials can't be represented in that charset.
+    ///
+    /// [`encoding_rs`]: https://docs.rs/encoding_rs/0.8/encoding_rs/#relationship-with-windows-code-pages
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), Box<std::error::Error>> {
+    /// let client = reqwest::blocking::Client::new();
+    /// let resp = client.delete("http://httpbin.org/delete")
+    ///     .basic_auth_with_charset("admin", Some("très secret"), "iso-8859-1")
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn basic_auth_with_charset<U, P>(
+        mut self,
+        username: U,
+        password: Option<P>,
+        charset: &str,
+    ) -> RequestBuilder
+    where
+        U: fmt::Display,
+        P: fmt::Display,
+    {
+        match async_impl::request::basic_auth_charset(username, password, charset) {
+            Ok(header_value) => {
+                self.header_sensitive(crate::header::AUTHORIZATION, &*header_value, true)
+            }
+            Err(err) => {
+                self.request = Err(err);
+                self
+            }
+        }
+    }
+
     /// Enable HTTP bearer authentication.
     ///
     /// ```rust
@@ -276,8 +423,73 @@ impl RequestBuilder {
         self.header_sensitive(crate::header::AUTHORIZATION, &*header_value, true)
     }
 
+    /// Make the request conditional on the resource being modified after
+    /// `time`, by setting the `If-Modified-Since` header. An earlier value
+    /// of the header is replaced.
+    ///
+    /// The request fails to build if `time` is before 1970 or after 9999.
+    ///
+    /// ```rust
+    /// # use std::time::{Duration, UNIX_EPOCH};
+    /// # fn run() -> Result<(), Box<std::error::Error>> {
+    /// let fetched = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    /// let client = reqwest::blocking::Client::new();
+    /// let resp = client.get("http://httpbin.org/cache")
+    ///     .if_modified_since(fetched)
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn if_modified_since(mut self, time: SystemTime) -> RequestBuilder {
+        let value = async_impl::request::http_date(time);
+        async_impl::request::header_result(
+            &mut self.request,
+            Request::headers_mut,
+            IF_MODIFIED_SINCE,
+            value,
+            true,
+        );
+        self
+    }
+
+    /// Make the request conditional on the resource not matching `etag`,
+    /// by adding it to the `If-None-Match` header.
+    ///
+    /// `etag` can be given with or without its quotes, and as a weak tag
+    /// such as `W/"xyzzy"`. The request fails to build if `etag` isn't a
+    /// valid entity tag.
+    pub fn if_none_match(mut self, etag: &str) -> RequestBuilder {
+        let value = async_impl::request::entity_tag(etag);
+        async_impl::request::header_result(
+            &mut self.request,
+            Request::headers_mut,
+            IF_NONE_MATCH,
+            value,
+            false,
+        );
+        self
+    }
+
+    /// Make the request conditional on the resource matching `etag`, by
+    /// adding it to the `If-Match` header.
+    pub fn if_match(mut self, etag: &str) -> RequestBuilder {
+        let value = async_impl::request::entity_tag(etag);
+        async_impl::request::header_result(
+            &mut self.request,
+            Request::headers_mut,
+            IF_MATCH,
+            value,
+            false,
+        );
+        self
+    }
+
     /// Set the request body.
     ///
+    /// If the body carries a content type, set with
+    /// `Body::with_content_type`, it is sent as the `Content-Type` header
+    /// unless the request sets that header itself.
+    ///
     /// # Examples
     ///
     /// Using a string:
@@ -326,9 +538,42 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the request body from a reader, with an optional known length.
+    ///
+    /// This is a shorthand for [`Body::new`] and [`Body::sized`]. When `len`
+    /// is `Some`, the `Content-Length` header is set and the reader is
+    /// streamed, otherwise the body is sent using chunked encoding.
+    ///
+    /// [`Body::new`]: ./struct.Body.html#method.new
+    /// [`Body::sized`]: ./struct.Body.html#method.sized
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), Box<std::error::Error>> {
+    /// let compressed: Vec<u8> = vec![1, 10, 100];
+    /// let len = compressed.len() as u64;
+    /// let client = reqwest::blocking::Client::new();
+    /// let res = client.post("http://httpbin.org/post")
+    ///     .body_reader(std::io::Cursor::new(compressed), Some(len))
+    ///     .send()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn body_reader<R>(self, reader: R, len: Option<u64>) -> RequestBuilder
+    where
+        R: Read + Send + 'static,
+    {
+        let body = match len {
+            Some(len) => Body::sized(reader, len),
+            None => Body::new(reader),
+        };
+        self.body(body)
+    }
+
     /// Enables a request timeout.
     ///
-    /// The timeout is applied from the when the request starts connecting
+    /// The timeout is applied from when the request starts connecting
     /// until the response body has finished. It affects only this request
     /// and overrides the timeout configured using `ClientBuilder::timeout()`.
     pub fn timeout(mut self, timeout: Duration) -> RequestBuilder {
@@ -338,6 +583,64 @@ impl RequestBuilder {
         self
     }
 
+    /// Set a timeout for only the connect phase of this request.
+    ///
+    /// This overrides the timeout configured using
+    /// `ClientBuilder::connect_timeout()` for any new connection this request
+    /// waits for. A pooled connection that is reused is not affected. When
+    /// several requests to the same host set a connect timeout, the shortest
+    /// applies to the connections opened while they wait.
+    pub fn connect_timeout(mut self, timeout: Duration) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.connect_timeout_mut() = Some(timeout);
+        }
+        self
+    }
+
+    /// Attach a typed value to the request's extensions.
+    ///
+    /// The value can be read back with `Request::extensions`. It replaces
+    /// any earlier value of the same type, and is kept when the request is
+    /// cloned with `try_clone`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// #[derive(Clone)]
+    /// struct RequestId(u64);
+    ///
+    /// let req = reqwest::blocking::Client::new()
+    ///     .get("https://hyper.rs")
+    ///     .extension(RequestId(7))
+    ///     .build()?;
+    /// assert_eq!(req.extensions().get::<RequestId>().unwrap().0, 7);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extension<T>(mut self, val: T) -> RequestBuilder
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        if let Ok(ref mut req) = self.request {
+            req.inner.insert_extension(val);
+        }
+        self
+    }
+
+    /// Disable automatic decompression of the response to this request.
+    ///
+    /// The response body will be returned as it was received, with any
+    /// `Content-Encoding` header left intact. Unlike `ClientBuilder::gzip`
+    /// and `ClientBuilder::brotli`, this only affects this request, and the
+    /// client's `Accept-Encoding` header is still sent.
+    pub fn no_auto_decompress(mut self) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            *req.inner.auto_decompress_mut() = false;
+        }
+        self
+    }
+
     /// Modify the query string of the URL.
     ///
     /// Modifies the URL of this request, adding the parameters provided.
@@ -390,6 +693,26 @@ impl RequestBuilder {
         self
     }
 
+    /// Modify the query string of the URL, replacing existing parameters.
+    ///
+    /// Like `query`, but every key in `query` first has its existing
+    /// occurrences removed from the URL. Other parameters are kept in order,
+    /// with the new ones appended after them.
+    ///
+    /// # Errors
+    /// This method will fail if the object you provide cannot be serialized
+    /// into a query string.
+    pub fn query_replace<T: Serialize + ?Sized>(mut self, query: &T) -> RequestBuilder {
+        let mut error = None;
+        if let Ok(ref mut req) = self.request {
+            error = async_impl::request::replace_query(req.url_mut(), query).err();
+        }
+        if let Some(err) = error {
+            self.request = Err(err);
+        }
+        self
+    }
+
     /// Send a form body.
     ///
     /// Sets the body to the url encoded serialization of the passed value,
@@ -416,18 +739,48 @@ impl RequestBuilder {
     ///
     /// This method fails if the passed value cannot be serialized into
     /// url encoded format
-    pub fn form<T: Serialize + ?Sized>(mut self, form: &T) -> RequestBuilder {
+    pub fn form<T: Serialize + ?Sized>(self, form: &T) -> RequestBuilder {
+        self.form_with_content_type(
+            form,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        )
+    }
+
+    /// Send a form body with a custom `Content-Type`.
+    ///
+    /// This works like `form`, but sets the given content type instead of
+    /// `application/x-www-form-urlencoded`, for servers that expect
+    /// something like `application/x-www-form-urlencoded; charset=UTF-8`.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the passed value cannot be serialized into
+    /// url encoded format, or if the content type is not a valid MIME type.
+    pub fn form_with_content_type<T, V>(mut self, form: &T, content_type: V) -> RequestBuilder
+    where
+        T: Serialize + ?Sized,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
         let mut error = None;
         if let Ok(ref mut req) = self.request {
-            match serde_urlencoded::to_string(form) {
-                Ok(body) => {
-                    req.headers_mut().insert(
-                        CONTENT_TYPE,
-                        HeaderValue::from_static("application/x-www-form-urlencoded"),
-                    );
+            let content_type = <HeaderValue as TryFrom<V>>::try_from(content_type)
+                .map_err(|e| crate::error::builder(e.into()))
+                .and_then(|value| {
+                    value
+                        .to_str()
+                        .map_err(crate::error::builder)?
+                        .parse::<mime::Mime>()
+                        .map_err(crate::error::builder)?;
+                    Ok(value)
+                });
+            match (content_type, serde_urlencoded::to_string(form)) {
+                (Ok(content_type), Ok(body)) => {
+                    req.headers_mut().insert(CONTENT_TYPE, content_type);
                     *req.body_mut() = Some(body.into());
                 }
-                Err(err) => error = Some(crate::error::builder(err)),
+                (Err(err), _) => error = Some(err),
+                (_, Err(err)) => error = Some(crate::error::builder(err)),
             }
         }
         if let Some(err) = error {
@@ -505,18 +858,48 @@ impl RequestBuilder {
     /// ```
     ///
     /// See [`multipart`](multipart/) for more examples.
-    pub fn multipart(self, mut multipart: multipart::Form) -> RequestBuilder {
-        let mut builder = self.header(
-            CONTENT_TYPE,
-            format!("multipart/form-data; boundary={}", multipart.boundary()).as_str(),
-        );
-        if let Ok(ref mut req) = builder.request {
-            *req.body_mut() = Some(match multipart.compute_length() {
+    ///
+    /// Any `Content-Type` header already set on the request is replaced.
+    ///
+    /// If the form has a file or reader part (see `Form::is_streaming`),
+    /// the request can't be cloned or retried automatically.
+    ///
+    /// # Errors
+    ///
+    /// The builder errors if the form holds more bytes in memory than its
+    /// `Form::max_in_memory` limit.
+    pub fn multipart(mut self, mut multipart: multipart::Form) -> RequestBuilder {
+        if let Err(err) = multipart.check_in_memory() {
+            if self.request.is_ok() {
+                self.request = Err(err);
+            }
+            return self;
+        }
+        let mut builder_err = None;
+        if let Ok(ref mut req) = self.request {
+            let content_type = format!("multipart/form-data; boundary={}", multipart.boundary());
+            req.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_str(&content_type).expect("boundary is a valid header value"),
+            );
+            let streaming = multipart.is_streaming();
+            let mut body = match multipart.compute_length() {
                 Some(length) => Body::sized(multipart.reader(), length),
                 None => Body::new(multipart.reader()),
-            })
+            };
+            if !streaming {
+                // Every part is in memory, so buffer the form to make the
+                // request reusable.
+                if let Err(err) = body.buffer() {
+                    builder_err = Some(err);
+                }
+            }
+            *req.body_mut() = Some(body);
+        }
+        if let Some(err) = builder_err {
+            self.request = Err(err);
         }
-        builder
+        self
     }
 
     /// Build a `Request`, which can be inspected, modified and executed with
@@ -600,12 +983,14 @@ impl<T> TryFrom<HttpRequest<T>> for Request where T:Into<Body> {
             method,
             uri,
             headers,
+            extensions,
             ..
         } = parts;
         let url = Url::parse(&uri.to_string())
             .map_err(crate::error::builder)?;
         let mut inner = async_impl::Request::new(method, url);
         crate::util::replace_headers(inner.headers_mut(), headers);
+        *inner.extensions_mut() = extensions;
         Ok(Request {
             body: Some(body.into()),
             inner,
@@ -631,7 +1016,7 @@ fn fmt_request_fields<'a, 'b>(
 #[cfg(test)]
 mod tests {
     use super::{HttpRequest, Request};
-    use super::super::{body, Client};
+    use super::super::{body, multipart, Body, Client};
     use crate::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE, HOST};
     use crate::Method;
     use serde::Serialize;
@@ -716,6 +1101,22 @@ mod tests {
         assert_eq!(r.headers().get(HOST), Some(&header));
     }
 
+    #[test]
+    fn add_header_opt() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+
+        let r = client
+            .post(some_url)
+            .header_opt(HOST, Some("google.com"))
+            .header_opt(ACCEPT, None::<&str>)
+            .build()
+            .unwrap();
+
+        assert_eq!(r.headers()[HOST], "google.com");
+        assert_eq!(r.headers().get(ACCEPT), None);
+    }
+
     #[test]
     fn add_headers() {
         let client = Client::new();
@@ -773,6 +1174,53 @@ mod tests {
         assert_eq!(buf, body);
     }
 
+    #[test]
+    fn add_body_reader() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+        let body = "Some interesting content";
+
+        let r = client
+            .post(some_url)
+            .body_reader(std::io::Cursor::new(body), Some(body.len() as u64))
+            .build()
+            .unwrap();
+        assert_eq!(r.body().unwrap().len(), Some(body.len() as u64));
+
+        let mut r = client
+            .post(some_url)
+            .body_reader(std::io::Cursor::new(body), None)
+            .build()
+            .unwrap();
+        assert_eq!(r.body().unwrap().len(), None);
+
+        let buf = body::read_to_string(r.body_mut().take().unwrap()).unwrap();
+        assert_eq!(buf, body);
+    }
+
+    #[test]
+    fn clone_with_body_reader() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+        let body = "Some interesting content";
+
+        let r = client
+            .post(some_url)
+            .body_reader(std::io::Cursor::new(body), None)
+            .build()
+            .unwrap();
+        assert!(r.try_clone().is_none());
+
+        let mut clone = r
+            .clone_with_body(Body::new(std::io::Cursor::new(body)))
+            .unwrap();
+        assert_eq!(clone.method(), Method::POST);
+        assert_eq!(clone.url().as_str(), some_url);
+
+        let buf = body::read_to_string(clone.body_mut().take().unwrap()).unwrap();
+        assert_eq!(buf, body);
+    }
+
     #[test]
     fn add_query_append() {
         let client = Client::new();
@@ -860,6 +1308,75 @@ mod tests {
         assert_eq!(buf, body_should_be);
     }
 
+    #[test]
+    fn add_form_with_content_type() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+
+        let mut form_data = HashMap::new();
+        form_data.insert("foo", "bar");
+
+        let mut r = client
+            .post(some_url)
+            .form_with_content_type(
+                &form_data,
+                "application/x-www-form-urlencoded; charset=UTF-8",
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            r.headers().get(CONTENT_TYPE).unwrap(),
+            &"application/x-www-form-urlencoded; charset=UTF-8"
+        );
+
+        let buf = body::read_to_string(r.body_mut().take().unwrap()).unwrap();
+        assert_eq!(buf, "foo=bar");
+
+        let r = client
+            .post(some_url)
+            .form_with_content_type(&form_data, "not a mime")
+            .build();
+        assert!(r.unwrap_err().is_builder());
+    }
+
+    #[test]
+    fn add_multipart_replaces_content_type() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+        let form = multipart::Form::new().text("key", "value");
+        let content_type = format!("multipart/form-data; boundary={}", form.boundary());
+
+        let r = client
+            .post(some_url)
+            .header(CONTENT_TYPE, "text/plain")
+            .multipart(form)
+            .build()
+            .unwrap();
+
+        let values = r.headers().get_all(CONTENT_TYPE).iter().collect::<Vec<_>>();
+        assert_eq!(values, vec![&content_type]);
+    }
+
+    #[test]
+    fn add_multipart_buffers_in_memory_parts() {
+        let client = Client::new();
+        let some_url = "https://google.com/";
+
+        let form = multipart::Form::new().text("key", "value");
+        assert!(!form.is_streaming());
+        let r = client.post(some_url).multipart(form).build().unwrap();
+        assert!(r.try_clone().is_some());
+
+        let reader = multipart::Part::reader(std::io::Cursor::new("value"));
+        let form = multipart::Form::new()
+            .text("key", "value")
+            .part("reader", reader);
+        assert!(form.is_streaming());
+        let r = client.post(some_url).multipart(form).build().unwrap();
+        assert!(r.try_clone().is_none());
+    }
+
     #[test]
     #[cfg(feature = "json")]
     fn add_json() {
@@ -966,9 +1483,11 @@ mod tests {
         let http_request = HttpRequest::builder().method("GET")
             .uri("http://localhost/")
             .header("User-Agent", "my-awesome-agent/1.0")
+            .extension(42u32)
             .body("test test test")
             .unwrap();
         let req: Request = Request::try_from(http_request).unwrap();
+        assert_eq!(req.extensions().get::<u32>(), Some(&42));
         assert_eq!(req.body().is_none(), false);
         let test_data = b"test test test";
         assert_eq!(req.body().unwrap().as_bytes(), Some(&test_data[..]));
@@ -3,7 +3,7 @@ use std::io::{self, Read};
 use std::mem;
 use std::net::SocketAddr;
 use std::pin::Pin;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use bytes::Bytes;
 use http;
@@ -15,7 +15,7 @@ use super::client::KeepCoreThreadAlive;
 use super::wait;
 #[cfg(feature = "cookies")]
 use crate::cookie;
-use crate::{async_impl, StatusCode, Url, Version};
+use crate::{async_impl, StatusCode, TimeoutKind, Url, Version};
 
 /// A Response to a submitted `Request`.
 pub struct Response {
@@ -93,6 +93,36 @@ impl Response {
         self.inner.status()
     }
 
+    /// Check if the status of this `Response` is informational (1xx).
+    #[inline]
+    pub fn is_informational(&self) -> bool {
+        self.inner.is_informational()
+    }
+
+    /// Check if the status of this `Response` is successful (2xx).
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.inner.is_success()
+    }
+
+    /// Check if the status of this `Response` is a redirection (3xx).
+    #[inline]
+    pub fn is_redirection(&self) -> bool {
+        self.inner.is_redirection()
+    }
+
+    /// Check if the status of this `Response` is a client error (4xx).
+    #[inline]
+    pub fn is_client_error(&self) -> bool {
+        self.inner.is_client_error()
+    }
+
+    /// Check if the status of this `Response` is a server error (5xx).
+    #[inline]
+    pub fn is_server_error(&self) -> bool {
+        self.inner.is_server_error()
+    }
+
     /// Get the `Headers` of this `Response`.
     ///
     /// # Example
@@ -177,8 +207,26 @@ impl Response {
         self.inner.remote_addr()
     }
 
+    /// Returns a reference to the associated extensions.
+    ///
+    /// These carry metadata about the connection the `Response` was received
+    /// on: the `hyper::client::connect::HttpInfo` used by `remote_addr`, and
+    /// a `TlsInfo` with the server's certificate and the ALPN protocol when
+    /// the connection was made with the `rustls` backend.
+    pub fn extensions(&self) -> &http::Extensions {
+        self.inner.extensions()
+    }
+
+    /// Returns a mutable reference to the associated extensions.
+    pub fn extensions_mut(&mut self) -> &mut http::Extensions {
+        self.inner.extensions_mut()
+    }
+
     /// Get the content-length of the response, if it is known.
     ///
+    /// The `content-length` header is used when present, otherwise this
+    /// falls back to the exact size hint of the body.
+    ///
     /// Reasons it may not be known:
     ///
     /// - The server didn't send a `content-length` header.
@@ -188,6 +236,20 @@ impl Response {
         self.inner.content_length()
     }
 
+    /// Get the time of the `Last-Modified` header, if it's a valid
+    /// HTTP-date.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.inner.last_modified()
+    }
+
+    /// Get the entity tag of the `ETag` header, if it's valid.
+    ///
+    /// The tag keeps its quotes, and its `W/` prefix if it's weak, so it
+    /// can be passed back as is to `RequestBuilder::if_none_match`.
+    pub fn etag(&self) -> Option<String> {
+        self.inner.etag()
+    }
+
     /// Try and deserialize the response body as JSON using `serde`.
     ///
     /// # Optional
@@ -227,7 +289,58 @@ impl Response {
     #[cfg(feature = "json")]
     pub fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
         wait::timeout(self.inner.json(), self.timeout).map_err(|e| match e {
-            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Try to deserialize the response body as JSON, after checking that
+    /// the `Content-Type` of the response is a JSON media type.
+    ///
+    /// Both `application/json` and types with a `+json` suffix are
+    /// accepted.
+    ///
+    /// ```rust
+    /// # extern crate reqwest;
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let list: Vec<String> = reqwest::blocking::get("http://example.com/api/list")?
+    ///     .json_strict()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response has no JSON `Content-Type`, or for
+    /// the same reasons as [`json`](Response::json).
+    #[cfg(feature = "json")]
+    pub fn json_strict<T: DeserializeOwned>(self) -> crate::Result<T> {
+        wait::timeout(self.inner.json_strict(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Try to deserialize the response body as JSON, after stripping an
+    /// XSSI guard `prefix` from the front of it.
+    ///
+    /// ```rust
+    /// # extern crate reqwest;
+    /// # fn run() -> Result<(), reqwest::Error> {
+    /// let list: Vec<String> = reqwest::blocking::get("http://example.com/api/list")?
+    ///     .json_xssi(")]}'\n")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the response body doesn't start with `prefix`,
+    /// or for the same reasons as [`json`](Response::json).
+    #[cfg(feature = "json")]
+    pub fn json_xssi<T: DeserializeOwned>(self, prefix: &str) -> crate::Result<T> {
+        wait::timeout(self.inner.json_xssi(prefix), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
             wait::Waited::Inner(e) => e,
         })
     }
@@ -246,7 +359,30 @@ impl Response {
     /// ```
     pub fn bytes(self) -> crate::Result<Bytes> {
         wait::timeout(self.inner.bytes(), self.timeout).map_err(|e| match e {
-            wait::Waited::TimedOut(e) => crate::error::decode(e),
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
+    /// Get the full response body as `Bytes`, failing if it is larger than
+    /// `max` bytes.
+    ///
+    /// Reading stops as soon as the limit is exceeded. The returned error
+    /// reports `true` from `Error::is_body_too_large`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bytes = reqwest::blocking::get("http://httpbin.org/ip")?.bytes_limited(64 * 1024)?;
+    ///
+    /// println!("bytes: {:?}", bytes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bytes_limited(self, max: usize) -> crate::Result<Bytes> {
+        wait::timeout(self.inner.bytes_limited(max), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
             wait::Waited::Inner(e) => e,
         })
     }
@@ -294,12 +430,37 @@ impl Response {
     pub fn text_with_charset(self, default_encoding: &str) -> crate::Result<String> {
         wait::timeout(self.inner.text_with_charset(default_encoding), self.timeout).map_err(|e| {
             match e {
-                wait::Waited::TimedOut(e) => crate::error::decode(e),
+                wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
                 wait::Waited::Inner(e) => e,
             }
         })
     }
 
+    /// Get the response text, detecting its encoding.
+    ///
+    /// The encoding is taken from the `charset` parameter of `Content-Type`
+    /// if it names a known encoding, then from a byte order mark at the
+    /// start of the body, and otherwise defaults to UTF-8. Malformed
+    /// sequences are replaced with the REPLACEMENT CHARACTER.
+    ///
+    /// Returns the text along with the name of the encoding that was used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn run() -> Result<(), Box<std::error::Error>> {
+    /// let (content, encoding) = reqwest::blocking::get("http://httpbin.org/range/26")?
+    ///     .text_charset_detect()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn text_charset_detect(self) -> crate::Result<(String, &'static str)> {
+        wait::timeout(self.inner.text_charset_detect(), self.timeout).map_err(|e| match e {
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Body),
+            wait::Waited::Inner(e) => e,
+        })
+    }
+
     /// Copy the response body into a writer.
     ///
     /// This function internally uses [`std::io::copy`] and hence will continuously read data from
@@ -399,7 +560,7 @@ impl Read for Response {
 
         let timeout = self.timeout;
         wait::timeout(self.body_mut().read(buf), timeout).map_err(|e| match e {
-            wait::Waited::TimedOut(e) => crate::error::decode(e).into_io(),
+            wait::Waited::TimedOut => timed_out(TimeoutKind::Read).into_io(),
             wait::Waited::Inner(e) => e,
         })
     }
@@ -411,3 +572,7 @@ impl<T: Into<async_impl::body::Body>> From<http::Response<T>> for Response {
         Response::new(response, None, KeepCoreThreadAlive::empty())
     }
 }
+
+fn timed_out(kind: TimeoutKind) -> crate::Error {
+    crate::error::decode(crate::error::TimedOut(kind))
+}
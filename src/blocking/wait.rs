@@ -37,7 +37,7 @@ where
             let now = Instant::now();
             if now >= deadline {
                 log::trace!("wait timeout exceeded");
-                return Err(Waited::TimedOut(crate::error::TimedOut));
+                return Err(Waited::TimedOut);
             }
 
             log::trace!("({:?}) park timeout {:?}", thread::current().id(), deadline - now);
@@ -51,7 +51,7 @@ where
 
 #[derive(Debug)]
 pub(crate) enum Waited<E> {
-    TimedOut(crate::error::TimedOut),
+    TimedOut,
     Inner(E),
 }
 
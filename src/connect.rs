@@ -3,20 +3,25 @@ use http::uri::{Scheme, Authority};
 use http::Uri;
 use hyper::client::connect::{Connected, Connection};
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
 #[cfg(feature = "native-tls-crate")]
 use native_tls_crate::{TlsConnector, TlsConnectorBuilder};
 #[cfg(feature = "__tls")]
 use http::header::HeaderValue;
 use futures_util::future::Either;
+use futures_util::task::AtomicWaker;
 use bytes::{Buf, BufMut};
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::io;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::mem::MaybeUninit;
 use pin_project_lite::pin_project;
 
@@ -28,9 +33,23 @@ use crate::error::BoxError;
 use self::native_tls_conn::NativeTlsConn;
 #[cfg(feature = "rustls-tls")]
 use self::rustls_tls_conn::RustlsTlsConn;
+#[cfg(feature = "rustls-tls")]
+use crate::tls::TlsInfo;
+
+// Only the rustls backend gives access to the TLS session.
+#[cfg(not(feature = "rustls-tls"))]
+enum TlsInfo {}
 
 #[derive(Clone)]
-pub(crate) enum HttpConnector {
+pub(crate) struct HttpConnector {
+    resolver: Resolver,
+    overrides: Arc<HashMap<String, IpAddr>>,
+    #[cfg(target_os = "linux")]
+    user_timeout: Option<Duration>,
+}
+
+#[derive(Clone)]
+enum Resolver {
     Gai(hyper::client::HttpConnector),
     #[cfg(feature = "trust-dns")]
     TrustDns(hyper::client::HttpConnector<TrustDnsResolver>),
@@ -38,16 +57,51 @@ pub(crate) enum HttpConnector {
 
 impl HttpConnector {
     pub(crate) fn new_gai() -> Self {
-        Self::Gai(hyper::client::HttpConnector::new())
+        Self::from_resolver(Resolver::Gai(hyper::client::HttpConnector::new()))
     }
 
     #[cfg(feature = "trust-dns")]
     pub(crate) fn new_trust_dns() -> crate::Result<HttpConnector> {
         TrustDnsResolver::new()
             .map(hyper::client::HttpConnector::new_with_resolver)
-            .map(Self::TrustDns)
+            .map(Resolver::TrustDns)
+            .map(Self::from_resolver)
             .map_err(crate::error::builder)
     }
+
+    fn from_resolver(resolver: Resolver) -> Self {
+        HttpConnector {
+            resolver,
+            overrides: Arc::new(HashMap::new()),
+            #[cfg(target_os = "linux")]
+            user_timeout: None,
+        }
+    }
+
+    /// Hosts in `overrides` connect to the given address instead of being
+    /// looked up with the resolver.
+    pub(crate) fn set_overrides(&mut self, overrides: HashMap<String, IpAddr>) {
+        self.overrides = Arc::new(overrides);
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn set_user_timeout(&mut self, timeout: Option<Duration>) {
+        self.user_timeout = timeout;
+    }
+
+    /// Bind connections to IPv4 targets to `ipv4`, and connections to IPv6
+    /// targets to `ipv6`.
+    pub(crate) fn set_local_addresses_by_family(
+        &mut self,
+        ipv4: Option<Ipv4Addr>,
+        ipv6: Option<Ipv6Addr>,
+    ) {
+        match (ipv4, ipv6) {
+            (Some(ipv4), Some(ipv6)) => self.set_local_addresses(ipv4, ipv6),
+            (Some(ipv4), None) => self.set_local_address(Some(ipv4.into())),
+            (None, ipv6) => self.set_local_address(ipv6.map(IpAddr::from)),
+        }
+    }
 }
 
 macro_rules! impl_http_connector {
@@ -56,10 +110,10 @@ macro_rules! impl_http_connector {
         impl HttpConnector {
             $(
                 fn $name(&mut self, $($par_name: $par_type),*)$( -> $return)? {
-                    match self {
-                        Self::Gai(resolver) => resolver.$name($($par_name),*),
+                    match &mut self.resolver {
+                        Resolver::Gai(resolver) => resolver.$name($($par_name),*),
                         #[cfg(feature = "trust-dns")]
-                        Self::TrustDns(resolver) => resolver.$name($($par_name),*),
+                        Resolver::TrustDns(resolver) => resolver.$name($($par_name),*),
                     }
                 }
             )+
@@ -69,39 +123,146 @@ macro_rules! impl_http_connector {
 
 impl_http_connector! {
     fn set_local_address(&mut self, addr: Option<IpAddr>);
+    fn set_local_addresses(&mut self, addr_ipv4: Ipv4Addr, addr_ipv6: Ipv6Addr);
     fn enforce_http(&mut self, is_enforced: bool);
     fn set_nodelay(&mut self, nodelay: bool);
 }
 
+#[cfg(feature = "trust-dns")]
+type Resolving = Either<
+    <hyper::client::HttpConnector as Service<Uri>>::Future,
+    <hyper::client::HttpConnector<TrustDnsResolver> as Service<Uri>>::Future,
+>;
+#[cfg(not(feature = "trust-dns"))]
+type Resolving = Either<
+    <hyper::client::HttpConnector as Service<Uri>>::Future,
+    <hyper::client::HttpConnector as Service<Uri>>::Future,
+>;
+
 impl Service<Uri> for HttpConnector {
-    type Response = <hyper::client::HttpConnector as Service<Uri>>::Response;
-    type Error = <hyper::client::HttpConnector as Service<Uri>>::Error;
-    #[cfg(feature = "trust-dns")]
-    type Future = Either<
-        <hyper::client::HttpConnector as Service<Uri>>::Future,
-        <hyper::client::HttpConnector<TrustDnsResolver> as Service<Uri>>::Future,
-    >;
-    #[cfg(not(feature = "trust-dns"))]
-    type Future = Either<
-        <hyper::client::HttpConnector as Service<Uri>>::Future,
-        <hyper::client::HttpConnector as Service<Uri>>::Future,
-    >;
+    type Response = TcpStream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, BoxError>> + Send>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        match self {
-            Self::Gai(resolver) => resolver.poll_ready(cx),
+        match &mut self.resolver {
+            Resolver::Gai(resolver) => resolver.poll_ready(cx).map_err(Into::into),
             #[cfg(feature = "trust-dns")]
-            Self::TrustDns(resolver) => resolver.poll_ready(cx),
+            Resolver::TrustDns(resolver) => resolver.poll_ready(cx).map_err(Into::into),
         }
     }
 
     fn call(&mut self, dst: Uri) -> Self::Future {
-        match self {
-            Self::Gai(resolver) => Either::Left(resolver.call(dst)),
+        let dst = match dst.host().and_then(|host| self.overrides.get(host)) {
+            Some(&ip) => with_ip_host(dst, ip),
+            None => dst,
+        };
+        let connecting: Resolving = match &mut self.resolver {
+            Resolver::Gai(resolver) => Either::Left(resolver.call(dst)),
             #[cfg(feature = "trust-dns")]
-            Self::TrustDns(resolver) => Either::Right(resolver.call(dst)),
+            Resolver::TrustDns(resolver) => Either::Right(resolver.call(dst)),
+        };
+        #[cfg(target_os = "linux")]
+        let user_timeout = self.user_timeout;
+
+        Box::pin(async move {
+            let tcp = connecting.await?;
+            #[cfg(target_os = "linux")]
+            {
+                if let Some(timeout) = user_timeout {
+                    set_tcp_user_timeout(&tcp, timeout)?;
+                }
+            }
+            Ok(tcp)
+        })
+    }
+}
+
+/// Replaces the host of `dst` with `ip`, so the connector skips resolving it.
+fn with_ip_host(dst: Uri, ip: IpAddr) -> Uri {
+    let host = match ip {
+        IpAddr::V4(ip) => ip.to_string(),
+        IpAddr::V6(ip) => format!("[{}]", ip),
+    };
+    let authority = match dst.port_u16() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    };
+
+    let mut parts = dst.into_parts();
+    parts.authority = Some(authority.parse().expect("IP address is a valid authority"));
+    Uri::from_parts(parts).expect("replacing the host keeps the uri valid")
+}
+
+/// Reads a hosts-file-format file into `overrides`.
+///
+/// Each line holds an IP address followed by one or more host names, and
+/// `#` starts a comment. As with the system hosts file, the first address
+/// listed for a name wins, and names already in `overrides` are kept.
+pub(crate) fn read_hosts_file(
+    path: &Path,
+    overrides: &mut HashMap<String, IpAddr>,
+) -> crate::Result<()> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        crate::error::builder(format!(
+            "error reading hosts file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    for (n, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let ip = match fields.next() {
+            Some(ip) => ip,
+            None => continue,
+        };
+
+        let invalid = |what: &str| {
+            crate::error::builder(format!(
+                "invalid hosts file {} line {}: {}",
+                path.display(),
+                n + 1,
+                what
+            ))
+        };
+        let ip = ip
+            .parse::<IpAddr>()
+            .map_err(|_| invalid("bad IP address"))?;
+        let mut names = fields.peekable();
+        if names.peek().is_none() {
+            return Err(invalid("missing host name"));
+        }
+        for name in names {
+            overrides.entry(name.to_ascii_lowercase()).or_insert(ip);
         }
     }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn set_tcp_user_timeout(tcp: &TcpStream, timeout: Duration) -> io::Result<()> {
+    use std::convert::TryFrom;
+    use std::os::unix::io::AsRawFd;
+
+    // saturate rather than wrap for absurdly long timeouts
+    let millis = libc::c_uint::try_from(timeout.as_millis()).unwrap_or(!0);
+    let ret = unsafe {
+        libc::setsockopt(
+            tcp.as_raw_fd(),
+            libc::IPPROTO_TCP,
+            libc::TCP_USER_TIMEOUT,
+            &millis as *const libc::c_uint as *const libc::c_void,
+            std::mem::size_of::<libc::c_uint>() as libc::socklen_t,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
 }
 
 #[derive(Clone)]
@@ -110,6 +271,13 @@ pub(crate) struct Connector {
     proxies: Arc<Vec<Proxy>>,
     verbose: verbose::Wrapper,
     timeout: Option<Duration>,
+    http2_tls_only: bool,
+    warm: Arc<Mutex<HashMap<String, Vec<Warm>>>>,
+    warm_idle_timeout: Option<Duration>,
+    warm_max_per_host: usize,
+    // Connect timeouts of requests waiting for a connection, by destination.
+    request_connect_timeouts: Arc<Mutex<HashMap<String, Vec<Duration>>>>,
+    reuse_max_requests: Option<usize>,
     #[cfg(feature = "__tls")]
     nodelay: bool,
     #[cfg(feature = "__tls")]
@@ -127,71 +295,66 @@ enum Inner {
         http: HttpConnector,
         tls: Arc<rustls::ClientConfig>,
         tls_proxy: Arc<rustls::ClientConfig>,
+        /// The config of connections that send early data, which only
+        /// offers HTTP/1.1 with ALPN.
+        early_data: Option<Arc<rustls::ClientConfig>>,
     },
 }
 
 impl Connector {
     #[cfg(not(feature = "__tls"))]
-    pub(crate) fn new<T>(
+    pub(crate) fn new(
         mut http: HttpConnector,
         proxies: Arc<Vec<Proxy>>,
-        local_addr: T,
         nodelay: bool,
-    ) -> Connector
-    where
-        T: Into<Option<IpAddr>>,
-    {
-        http.set_local_address(local_addr.into());
+    ) -> Connector {
         http.set_nodelay(nodelay);
         Connector {
             inner: Inner::Http(http),
             verbose: verbose::OFF,
+            warm: Default::default(),
+            warm_idle_timeout: None,
+            warm_max_per_host: !0,
+            request_connect_timeouts: Default::default(),
+            reuse_max_requests: None,
+            http2_tls_only: false,
             proxies,
             timeout: None,
         }
     }
 
     #[cfg(feature = "default-tls")]
-    pub(crate) fn new_default_tls<T>(
+    pub(crate) fn new_default_tls(
         http: HttpConnector,
         tls: TlsConnectorBuilder,
         proxies: Arc<Vec<Proxy>>,
         user_agent: Option<HeaderValue>,
-        local_addr: T,
         nodelay: bool,
-    ) -> crate::Result<Connector>
-    where
-        T: Into<Option<IpAddr>>,
-    {
+    ) -> crate::Result<Connector> {
         let tls = tls.build().map_err(crate::error::builder)?;
-        Ok(Self::from_built_default_tls(
-            http,
-            tls,
-            proxies,
-            user_agent,
-            local_addr,
-            nodelay,
-        ))
+        Ok(Self::from_built_default_tls(http, tls, proxies, user_agent, nodelay))
     }
 
     #[cfg(feature = "default-tls")]
-    pub(crate) fn from_built_default_tls<T> (
+    pub(crate) fn from_built_default_tls(
         mut http: HttpConnector,
         tls: TlsConnector,
         proxies: Arc<Vec<Proxy>>,
         user_agent: Option<HeaderValue>,
-        local_addr: T,
-        nodelay: bool) -> Connector
-        where
-            T: Into<Option<IpAddr>>,
-    {
-        http.set_local_address(local_addr.into());
+        nodelay: bool,
+    ) -> Connector {
         http.enforce_http(false);
 
         Connector {
             inner: Inner::DefaultTls(http, tls),
             proxies,
             verbose: verbose::OFF,
+            warm: Default::default(),
+            warm_idle_timeout: None,
+            warm_max_per_host: !0,
+            request_connect_timeouts: Default::default(),
+            reuse_max_requests: None,
+            http2_tls_only: false,
             timeout: None,
             nodelay,
             user_agent,
@@ -199,18 +362,13 @@ impl Connector {
     }
 
     #[cfg(feature = "rustls-tls")]
-    pub(crate) fn new_rustls_tls<T>(
+    pub(crate) fn new_rustls_tls(
         mut http: HttpConnector,
         tls: rustls::ClientConfig,
         proxies: Arc<Vec<Proxy>>,
         user_agent: Option<HeaderValue>,
-        local_addr: T,
         nodelay: bool,
-    ) -> Connector
-    where
-        T: Into<Option<IpAddr>>,
-    {
-        http.set_local_address(local_addr.into());
+    ) -> Connector {
         http.enforce_http(false);
 
         let (tls, tls_proxy) = if proxies.is_empty() {
@@ -227,23 +385,154 @@ impl Connector {
                 http,
                 tls,
                 tls_proxy,
+                early_data: None,
             },
             proxies,
             verbose: verbose::OFF,
+            warm: Default::default(),
+            warm_idle_timeout: None,
+            warm_max_per_host: !0,
+            request_connect_timeouts: Default::default(),
+            reuse_max_requests: None,
+            http2_tls_only: false,
             timeout: None,
             nodelay,
             user_agent,
         }
     }
 
+    /// Establishes a connection to `dst`, kept for the next time a
+    /// connection to the same destination is needed.
+    pub(crate) async fn preconnect(&self, dst: Uri) -> Result<(), BoxError> {
+        let key = warm_key(&dst);
+        let conn = self.connect(dst).await?;
+        if self.warm_max_per_host == 0 {
+            return Ok(());
+        }
+
+        let mut warm = self.warm.lock().unwrap();
+        let conns = warm.entry(key).or_default();
+        let idle_timeout = self.warm_idle_timeout;
+        conns.retain(|warm| !warm.expired(idle_timeout));
+        if conns.len() >= self.warm_max_per_host {
+            // Make room by dropping the oldest.
+            conns.remove(0);
+        }
+        conns.push(Warm {
+            conn,
+            since: Instant::now(),
+        });
+        Ok(())
+    }
+
+    /// Takes the newest preconnected connection to `dst` that is still
+    /// usable, dropping the ones that expired or were closed.
+    fn take_warm(&self, dst: &Uri) -> Option<Conn> {
+        let mut warm = self.warm.lock().unwrap();
+        if warm.is_empty() {
+            return None;
+        }
+        let key = warm_key(dst);
+        let conns = warm.get_mut(&key)?;
+        let mut found = None;
+        while let Some(mut candidate) = conns.pop() {
+            if !candidate.expired(self.warm_idle_timeout) && candidate.is_open() {
+                found = Some(candidate.conn);
+                break;
+            }
+        }
+        if conns.is_empty() {
+            warm.remove(&key);
+        }
+        found
+    }
+
+    /// Limit preconnected connections as the pool limits idle ones.
+    pub(crate) fn set_warm_limits(&mut self, idle_timeout: Option<Duration>, max_per_host: usize) {
+        self.warm_idle_timeout = idle_timeout;
+        self.warm_max_per_host = max_per_host;
+    }
+
     pub(crate) fn set_timeout(&mut self, timeout: Option<Duration>) {
         self.timeout = timeout;
     }
 
+    /// Overrides the connect timeout of new connections to `dst` with
+    /// `timeout`, until the returned guard is dropped.
+    ///
+    /// When several requests waiting for a connection to `dst` set one, the
+    /// shortest applies.
+    pub(crate) fn request_connect_timeout(
+        &self,
+        dst: &Uri,
+        timeout: Duration,
+    ) -> ConnectTimeoutGuard {
+        let key = warm_key(dst);
+        self.request_connect_timeouts
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .push(timeout);
+        ConnectTimeoutGuard {
+            timeouts: self.request_connect_timeouts.clone(),
+            key,
+            timeout,
+        }
+    }
+
+    fn timeout_for(&self, dst: &Uri) -> Option<Duration> {
+        let timeouts = self.request_connect_timeouts.lock().unwrap();
+        if timeouts.is_empty() {
+            return self.timeout;
+        }
+        match timeouts.get(&warm_key(dst)) {
+            Some(timeouts) => timeouts.iter().min().cloned(),
+            None => self.timeout,
+        }
+    }
+
     pub(crate) fn set_verbose(&mut self, enabled: bool) {
         self.verbose.0 = enabled;
     }
 
+    /// Use HTTP/2 with prior knowledge for `https` connections only.
+    pub(crate) fn set_http2_tls_only(&mut self, enabled: bool) {
+        self.http2_tls_only = enabled;
+    }
+
+    /// Close connections once they served `max` responses.
+    pub(crate) fn set_reuse_max_requests(&mut self, max: Option<usize>) {
+        self.reuse_max_requests = max;
+    }
+
+    /// Send the request as TLS 1.3 early data on resumed sessions.
+    ///
+    /// Early data is sent before the server picks a protocol, so these
+    /// connections only offer HTTP/1.1, while the config they are derived
+    /// from is left as is.
+    #[cfg(feature = "rustls-tls")]
+    pub(crate) fn set_tls_early_data(&mut self, enabled: bool) {
+        match self.inner {
+            Inner::RustlsTls {
+                ref tls,
+                ref mut early_data,
+                ..
+            } => {
+                *early_data = if enabled {
+                    let mut early_tls = (**tls).clone();
+                    early_tls.set_protocols(&["http/1.1".into()]);
+                    early_tls.enable_early_data = true;
+                    Some(Arc::new(early_tls))
+                } else {
+                    None
+                };
+            }
+            #[cfg(feature = "default-tls")]
+            _ => (),
+        }
+    }
+
     #[cfg(feature = "socks")]
     async fn connect_socks(
         &self,
@@ -278,6 +567,9 @@ impl Connector {
                     return Ok(Conn {
                         inner: self.verbose.wrap(NativeTlsConn { inner: io }),
                         is_proxy: false,
+                        negotiated_h2: false,
+                        alpn: false,
+                        tls_info: None,
                     });
                 }
             }
@@ -299,9 +591,13 @@ impl Connector {
                     let io = RustlsConnector::from(tls)
                         .connect(dnsname.as_ref(), conn)
                         .await?;
+                    let tls_info = TlsInfo::from_session(io.get_ref().1);
                     return Ok(Conn {
                         inner: self.verbose.wrap(RustlsTlsConn { inner: io }),
                         is_proxy: false,
+                        negotiated_h2: false,
+                        alpn: tls_info.alpn_protocol().is_some(),
+                        tls_info: Some(tls_info),
                     });
                 }
             }
@@ -312,6 +608,9 @@ impl Connector {
         socks::connect(proxy, dst, dns).await.map(|tcp| Conn {
             inner: self.verbose.wrap(tcp),
             is_proxy: false,
+            negotiated_h2: false,
+            alpn: false,
+            tls_info: None,
         })
     }
 
@@ -327,6 +626,9 @@ impl Connector {
                 Ok(Conn {
                     inner: self.verbose.wrap(io),
                     is_proxy,
+                    negotiated_h2: false,
+                    alpn: false,
+                    tls_info: None,
                 })
             }
             #[cfg(feature = "default-tls")]
@@ -354,10 +656,18 @@ impl Connector {
                 Ok(Conn {
                     inner: self.verbose.wrap(io),
                     is_proxy,
+                    negotiated_h2: false,
+                    alpn: false,
+                    tls_info: None,
                 })
             }
             #[cfg(feature = "rustls-tls")]
-            Inner::RustlsTls { http, tls, .. } => {
+            Inner::RustlsTls {
+                http,
+                tls,
+                early_data,
+                ..
+            } => {
                 let mut http = http.clone();
 
                 // Disable Nagle's algorithm for TLS handshake
@@ -367,19 +677,57 @@ impl Connector {
                     http.set_nodelay(true);
                 }
 
+                // hyper-rustls can't send early data, so the handshake is
+                // done here. It completes in the background, while the
+                // request is written.
+                let early_tls = early_data.filter(|_| dst.scheme() == Some(&Scheme::HTTPS));
+                if let Some(early_tls) = early_tls {
+                    use tokio_rustls::webpki::DNSNameRef;
+                    use tokio_rustls::TlsConnector as RustlsConnector;
+
+                    let host = dst.host().ok_or("no host in url")?.to_string();
+                    let dnsname = DNSNameRef::try_from_ascii_str(&host)
+                        .map(|dnsname| dnsname.to_owned())
+                        .map_err(|_| "Invalid DNS Name")?;
+                    let tcp = http.call(dst).await?;
+                    if !self.nodelay {
+                        tcp.set_nodelay(false)?;
+                    }
+                    let io = RustlsConnector::from(early_tls)
+                        .early_data(true)
+                        .connect(dnsname.as_ref(), tcp)
+                        .await?;
+                    let tls_info = TlsInfo::from_session(io.get_ref().1);
+                    return Ok(Conn {
+                        inner: self.verbose.wrap(RustlsTlsConn { inner: io }),
+                        is_proxy,
+                        negotiated_h2: false,
+                        alpn: tls_info.alpn_protocol().is_some(),
+                        tls_info: Some(tls_info),
+                    });
+                }
+
                 let mut http = hyper_rustls::HttpsConnector::from((http, tls.clone()));
                 let io = http.call(dst).await?;
 
+                let mut alpn = false;
+                let mut tls_info = None;
                 if let hyper_rustls::MaybeHttpsStream::Https(stream) = &io {
+                    let (io, session) = stream.get_ref();
                     if !self.nodelay {
-                        let (io, _) = stream.get_ref();
                         io.set_nodelay(false)?;
                     }
+                    let info = TlsInfo::from_session(session);
+                    alpn = info.alpn_protocol().is_some();
+                    tls_info = Some(info);
                 }
 
                 Ok(Conn {
                     inner: self.verbose.wrap(io),
                     is_proxy,
+                    negotiated_h2: false,
+                    alpn,
+                    tls_info,
                 })
             }
         }
@@ -430,6 +778,9 @@ impl Connector {
                     return Ok(Conn {
                         inner: self.verbose.wrap(NativeTlsConn { inner: io }),
                         is_proxy: false,
+                        negotiated_h2: false,
+                        alpn: false,
+                        tls_info: None,
                     });
                 }
             }
@@ -438,6 +789,7 @@ impl Connector {
                 http,
                 tls,
                 tls_proxy,
+                ..
             } => {
                 if dst.scheme() == Some(&Scheme::HTTPS) {
                     use tokio_rustls::webpki::DNSNameRef;
@@ -462,9 +814,14 @@ impl Connector {
                         .connect(dnsname.as_ref(), tunneled)
                         .await?;
 
+                    let tls_info = TlsInfo::from_session(io.get_ref().1);
+
                     return Ok(Conn {
                         inner: self.verbose.wrap(RustlsTlsConn { inner: io }),
                         is_proxy: false,
+                        negotiated_h2: false,
+                        alpn: tls_info.alpn_protocol().is_some(),
+                        tls_info: Some(tls_info),
                     });
                 }
             }
@@ -486,13 +843,68 @@ fn into_uri(scheme: Scheme, host: Authority) -> Uri {
         .expect("scheme and authority is valid Uri")
 }
 
+/// A connection opened by `Connector::preconnect`, waiting to be used.
+struct Warm {
+    conn: Conn,
+    since: Instant,
+}
+
+impl Warm {
+    fn expired(&self, idle_timeout: Option<Duration>) -> bool {
+        match idle_timeout {
+            Some(timeout) => self.since.elapsed() > timeout,
+            None => false,
+        }
+    }
+
+    /// Whether the peer hasn't closed the connection, or sent anything,
+    /// while it waited.
+    fn is_open(&mut self) -> bool {
+        let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+        let mut buf = [0; 1];
+        Pin::new(&mut self.conn)
+            .poll_read(&mut cx, &mut buf)
+            .is_pending()
+    }
+}
+
+/// A connect timeout set by a request, see `Connector::request_connect_timeout`.
+pub(crate) struct ConnectTimeoutGuard {
+    timeouts: Arc<Mutex<HashMap<String, Vec<Duration>>>>,
+    key: String,
+    timeout: Duration,
+}
+
+impl Drop for ConnectTimeoutGuard {
+    fn drop(&mut self) {
+        let mut timeouts = self.timeouts.lock().unwrap();
+        if let Some(set) = timeouts.get_mut(&self.key) {
+            if let Some(pos) = set.iter().position(|t| *t == self.timeout) {
+                set.swap_remove(pos);
+            }
+            if set.is_empty() {
+                timeouts.remove(&self.key);
+            }
+        }
+    }
+}
+
+fn warm_key(dst: &Uri) -> String {
+    let scheme = dst.scheme_str().unwrap_or("");
+    let authority = dst.authority().map(|a| a.as_str()).unwrap_or("");
+    format!("{}://{}", scheme, authority)
+}
+
 async fn with_timeout<T, F>(f: F, timeout: Option<Duration>) -> Result<T, BoxError>
 where
     F: Future<Output = Result<T, BoxError>>,
 {
     if let Some(to) = timeout {
         match tokio::time::timeout(to, f).await {
-            Err(_elapsed) => Err(Box::new(crate::error::TimedOut) as BoxError),
+            Err(_elapsed) => {
+                let err = crate::error::TimedOut(crate::TimeoutKind::Connect);
+                Err(Box::new(err) as BoxError)
+            }
             Ok(Ok(try_res)) => Ok(try_res),
             Ok(Err(e)) => Err(e),
         }
@@ -511,21 +923,51 @@ impl Service<Uri> for Connector {
     }
 
     fn call(&mut self, dst: Uri) -> Self::Future {
-        log::debug!("starting new connection: {:?}", dst);
-        let timeout = self.timeout;
-        for prox in self.proxies.iter() {
-            if let Some(proxy_scheme) = prox.intercept(&dst) {
-                return Box::pin(with_timeout(
-                    self.clone().connect_via_proxy(dst, proxy_scheme),
-                    timeout,
-                ));
-            }
+        if let Some(conn) = self.take_warm(&dst) {
+            log::debug!("using preconnected connection: {:?}", dst);
+            return Box::pin(async move { Ok(conn) });
         }
+        self.connect(dst)
+    }
+}
 
-        Box::pin(with_timeout(
-            self.clone().connect_with_maybe_proxy(dst, false),
-            timeout,
-        ))
+impl Connector {
+    /// Opens a new connection to `dst`.
+    fn connect(&self, dst: Uri) -> Connecting {
+        log::debug!("starting new connection: {:?}", dst);
+        let timeout = self.timeout_for(&dst);
+        let h2 = self.http2_tls_only && dst.scheme() == Some(&Scheme::HTTPS);
+        let proxy_scheme = self.proxies.iter().find_map(|prox| prox.intercept(&dst));
+        let connecting: Connecting = match proxy_scheme {
+            Some(proxy_scheme) => Box::pin(with_timeout(
+                self.clone().connect_via_proxy(dst, proxy_scheme),
+                timeout,
+            )),
+            None => Box::pin(with_timeout(
+                self.clone().connect_with_maybe_proxy(dst, false),
+                timeout,
+            )),
+        };
+
+        let max_requests = self.reuse_max_requests;
+        if !h2 && max_requests.is_none() {
+            return connecting;
+        }
+        Box::pin(async move {
+            let mut conn = connecting.await?;
+            // A protocol picked with ALPN is honored, HTTP/2 is only
+            // assumed when the handshake didn't negotiate one.
+            if h2 && !conn.alpn {
+                conn.negotiated_h2 = true;
+            }
+            if let Some(max) = max_requests {
+                conn.inner = Box::new(Retiring {
+                    inner: conn.inner,
+                    reuse: ConnReuse::new(max),
+                });
+            }
+            Ok(conn)
+        })
     }
 }
 
@@ -544,12 +986,28 @@ pin_project! {
         #[pin]
         inner: BoxConn,
         is_proxy: bool,
+        // Speak HTTP/2 with prior knowledge, without ALPN saying so.
+        negotiated_h2: bool,
+        // Whether the TLS handshake agreed on a protocol with ALPN.
+        alpn: bool,
+        // Added to the extensions of the responses on this connection.
+        tls_info: Option<TlsInfo>,
     }
 }
 
 impl Connection for Conn {
     fn connected(&self) -> Connected {
-        self.inner.connected().proxy(self.is_proxy)
+        let connected = self.inner.connected().proxy(self.is_proxy);
+        #[cfg(feature = "rustls-tls")]
+        let connected = match self.tls_info {
+            Some(ref info) => connected.extra(info.clone()),
+            None => connected,
+        };
+        if self.negotiated_h2 {
+            connected.negotiated_h2()
+        } else {
+            connected
+        }
     }
 }
 
@@ -620,6 +1078,102 @@ impl AsyncWrite for Conn {
 pub(crate) type Connecting =
     Pin<Box<dyn Future<Output = Result<Conn, BoxError>> + Send>>;
 
+/// Counts the responses a connection served. hyper hands a clone out in
+/// the extensions of each of them.
+#[derive(Clone)]
+pub(crate) struct ConnReuse(Arc<ReuseState>);
+
+struct ReuseState {
+    max_requests: usize,
+    served: AtomicUsize,
+    retired: AtomicBool,
+    reader: AtomicWaker,
+}
+
+/// Retires its connection once dropped.
+pub(crate) struct RetireConn(ConnReuse);
+
+/// A connection that reads as closed once retired, so that hyper drops it
+/// instead of returning it to the pool.
+struct Retiring {
+    inner: BoxConn,
+    reuse: ConnReuse,
+}
+
+impl ConnReuse {
+    fn new(max_requests: usize) -> ConnReuse {
+        ConnReuse(Arc::new(ReuseState {
+            max_requests,
+            served: AtomicUsize::new(0),
+            retired: AtomicBool::new(false),
+            reader: AtomicWaker::new(),
+        }))
+    }
+
+    /// Counts a response served on the connection. If that was its last
+    /// one, the connection should be retired once the response is done.
+    pub(crate) fn served(self) -> Option<RetireConn> {
+        let served = self.0.served.fetch_add(1, Ordering::SeqCst) + 1;
+        if served >= self.0.max_requests {
+            Some(RetireConn(self))
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for RetireConn {
+    fn drop(&mut self) {
+        let state = &(self.0).0;
+        state.retired.store(true, Ordering::SeqCst);
+        // hyper keeps reading idle connections, and sees this one closed.
+        state.reader.wake();
+    }
+}
+
+impl Connection for Retiring {
+    fn connected(&self) -> Connected {
+        self.inner.connected().extra(self.reuse.clone())
+    }
+}
+
+impl AsyncRead for Retiring {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let state = &self.reuse.0;
+        state.reader.register(cx.waker());
+        if state.retired.load(Ordering::SeqCst) {
+            return Poll::Ready(Ok(0));
+        }
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [MaybeUninit<u8>]) -> bool {
+        self.inner.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl AsyncWrite for Retiring {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
 #[cfg(feature = "__tls")]
 async fn tunnel<T>(
     mut conn: T,
@@ -1074,6 +1628,38 @@ mod tests {
     use tokio::net::TcpStream;
     use tokio::runtime;
 
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn tls_early_data_keeps_alpn() {
+        use super::{Connector, HttpConnector, Inner};
+        use std::sync::Arc;
+
+        let mut tls = rustls::ClientConfig::new();
+        tls.set_protocols(&["h2".into(), "http/1.1".into()]);
+        let mut connector = Connector::new_rustls_tls(
+            HttpConnector::new_gai(),
+            tls,
+            Arc::new(Vec::new()),
+            None,
+            true,
+        );
+        connector.set_tls_early_data(true);
+
+        match connector.inner {
+            Inner::RustlsTls {
+                tls, early_data, ..
+            } => {
+                assert_eq!(tls.alpn_protocols, [b"h2".to_vec(), b"http/1.1".to_vec()]);
+                assert!(!tls.enable_early_data);
+                let early_data = early_data.expect("early data config");
+                assert_eq!(early_data.alpn_protocols, [b"http/1.1".to_vec()]);
+                assert!(early_data.enable_early_data);
+            }
+            #[cfg(feature = "default-tls")]
+            _ => unreachable!("rustls connector"),
+        }
+    }
+
     static TUNNEL_UA: &'static str = "tunnel-test/x.y";
     static TUNNEL_OK: &[u8] = b"\
         HTTP/1.1 200 OK\r\n\
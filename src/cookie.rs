@@ -3,9 +3,13 @@
 use std::convert::TryInto;
 
 use crate::header;
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::SystemTime;
 
+use url::Url;
+
 /// A single HTTP cookie.
 pub struct Cookie<'a>(cookie_crate::Cookie<'a>);
 
@@ -92,11 +96,133 @@ pub(crate) fn extract_response_cookies<'a>(
 
 /// A persistent cookie store that provides session support.
 #[derive(Default)]
-pub(crate) struct CookieStore(pub(crate) cookie_store::CookieStore);
+pub(crate) struct CookieStore {
+    store: cookie_store::CookieStore,
+    capacity: Option<usize>,
+    // Only tracked while there is a `capacity`. Sending cookies only needs
+    // a read lock on the store, hence the `Mutex`.
+    recency: Mutex<Recency>,
+}
+
+/// When each cookie was last stored or sent, by domain, path and name.
+#[derive(Default)]
+struct Recency {
+    clock: u64,
+    last_used: HashMap<(String, String, String), u64>,
+}
+
+impl CookieStore {
+    /// Bounds the number of stored cookies to `capacity`, evicting the least
+    /// recently used ones when there are more.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = Some(capacity);
+        self.evict();
+    }
+
+    /// Stores the `cookies` received from `url`.
+    pub(crate) fn store_response_cookies<I>(&mut self, cookies: I, url: &Url)
+    where
+        I: Iterator<Item = cookie_crate::Cookie<'static>>,
+    {
+        for cookie in cookies {
+            log::debug!("inserting Set-Cookie '{:?}'", cookie);
+            let stored = cookie_store::Cookie::try_from_raw_cookie(&cookie, url)
+                .and_then(|cookie| self.store.insert(cookie.clone(), url).map(|_| cookie));
+            match stored {
+                Ok(ref cookie) if self.capacity.is_some() => {
+                    self.recency.get_mut().expect("lock poisoned").touch(cookie);
+                }
+                Ok(_) => (),
+                Err(e) => log::debug!("unable to store Set-Cookie: {:?}", e),
+            }
+        }
+        self.evict();
+    }
+
+    /// Returns the cookies to send in a request to `url`.
+    pub(crate) fn request_cookies(&self, url: &Url) -> Vec<&cookie_crate::Cookie<'static>> {
+        let cookies = self.store.matches(url);
+        if self.capacity.is_some() {
+            let mut recency = self.recency.lock().expect("lock poisoned");
+            for cookie in &cookies {
+                recency.touch(cookie);
+            }
+        }
+        cookies.into_iter().map(|cookie| &**cookie).collect()
+    }
+
+    fn evict(&mut self) {
+        let capacity = match self.capacity {
+            Some(capacity) => capacity,
+            None => return,
+        };
+        let recency = self.recency.get_mut().expect("lock poisoned");
+
+        // Expired cookies are kept by the store, and go first.
+        let mut cookies = self
+            .store
+            .iter_any()
+            .map(|cookie| {
+                let key = cookie_key(cookie);
+                let last_used = recency.last_used.get(&key).cloned().unwrap_or(0);
+                (!cookie.is_expired(), last_used, key)
+            })
+            .collect::<Vec<_>>();
+        if cookies.len() <= capacity {
+            return;
+        }
+        cookies.sort();
+
+        let excess = cookies.len() - capacity;
+        for (_, _, key) in cookies.into_iter().take(excess) {
+            log::debug!("evicting cookie {:?}", key);
+            self.store.remove(&key.0, &key.1, &key.2);
+            recency.last_used.remove(&key);
+        }
+    }
+}
+
+impl Recency {
+    fn touch(&mut self, cookie: &cookie_store::Cookie<'_>) {
+        self.clock += 1;
+        self.last_used.insert(cookie_key(cookie), self.clock);
+    }
+}
+
+fn cookie_key(cookie: &cookie_store::Cookie<'_>) -> (String, String, String) {
+    (
+        String::from(&cookie.domain),
+        String::from(&cookie.path),
+        cookie.name().to_owned(),
+    )
+}
 
 impl<'a> fmt::Debug for CookieStore {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        self.store.fmt(f)
+    }
+}
+
+/// A shareable handle to a cookie store.
+///
+/// Clients built with the same handle share their cookies, so a session
+/// can be split across clients configured differently, for example with
+/// different timeouts.
+///
+/// Cloning a handle is cheap, and all clones refer to the same store.
+#[derive(Clone, Default)]
+pub struct CookieStoreHandle(pub(crate) Arc<RwLock<CookieStore>>);
+
+impl CookieStoreHandle {
+    /// Create a handle to a new, empty cookie store.
+    pub fn new() -> CookieStoreHandle {
+        CookieStoreHandle::default()
+    }
+}
+
+impl fmt::Debug for CookieStoreHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CookieStoreHandle").finish()
     }
 }
 
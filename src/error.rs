@@ -94,6 +94,21 @@ impl Error {
         false
     }
 
+    /// Returns which phase of the request timed out, if the error is related
+    /// to a timeout.
+    pub fn timeout_kind(&self) -> Option<TimeoutKind> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(timed_out) = err.downcast_ref::<TimedOut>() {
+                return Some(timed_out.0);
+            }
+            source = err.source();
+        }
+
+        None
+    }
+
     /// Returns true if the error is related to the request
     pub fn is_request(&self) -> bool {
         match self.inner.kind {
@@ -110,6 +125,26 @@ impl Error {
         }
     }
 
+    /// Returns true if the response body exceeded the configured size limit.
+    pub fn is_body_too_large(&self) -> bool {
+        self.body_limit().is_some()
+    }
+
+    /// Returns the size limit that was exceeded, if the error was caused by
+    /// a response body that was too large.
+    pub fn body_limit(&self) -> Option<u64> {
+        let mut source = self.source();
+
+        while let Some(err) = source {
+            if let Some(too_large) = err.downcast_ref::<BodyTooLarge>() {
+                return Some(too_large.limit);
+            }
+            source = err.source();
+        }
+
+        None
+    }
+
     /// Returns true if the error is related to decoding the response's body
     pub fn is_decode(&self) -> bool {
         match self.inner.kind {
@@ -135,7 +170,31 @@ impl Error {
 
     #[allow(unused)]
     pub(crate) fn into_io(self) -> io::Error {
-        io::Error::new(io::ErrorKind::Other, self)
+        io::Error::new(self.io_kind(), self)
+    }
+
+    /// The `io::ErrorKind` closest to this error: `TimedOut` for timeouts,
+    /// or the kind of an `io::Error` it was caused by.
+    ///
+    /// `Interrupted` and `WouldBlock` are reported as `Other`, since readers
+    /// retry on those kinds, while this error is final.
+    fn io_kind(&self) -> io::ErrorKind {
+        if self.is_timeout() {
+            return io::ErrorKind::TimedOut;
+        }
+
+        let mut source = self.source();
+        while let Some(err) = source {
+            if let Some(io) = err.downcast_ref::<io::Error>() {
+                return match io.kind() {
+                    io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock => io::ErrorKind::Other,
+                    kind => kind,
+                };
+            }
+            source = err.source();
+        }
+
+        io::ErrorKind::Other
     }
 }
 
@@ -176,6 +235,7 @@ impl fmt::Display for Error {
             Kind::Body => f.write_str("request or response body error")?,
             Kind::Decode => f.write_str("error decoding response body")?,
             Kind::Redirect => f.write_str("error following redirect")?,
+            Kind::Upgrade => f.write_str("error upgrading connection")?,
             Kind::Status(ref code) => {
                 let prefix = if code.is_client_error() {
                     "HTTP status client error"
@@ -203,6 +263,28 @@ impl StdError for Error {
     }
 }
 
+/// Converts to an `io::Error` wrapping this error, such as for use with
+/// `AsyncRead` or `Read` adapters.
+///
+/// The kind is `TimedOut` for timeouts, or the kind of the `io::Error` that
+/// caused this error, such as `ConnectionRefused`, and `Other` otherwise.
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        err.into_io()
+    }
+}
+
+/// Converts an `io::Error`, such as one from a custom body, into a body
+/// error.
+///
+/// If the `io::Error` wraps a `reqwest::Error`, that error is returned
+/// instead.
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        unwrap_io(err).unwrap_or_else(body)
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 impl From<crate::error::Error> for wasm_bindgen::JsValue {
     fn from(err: Error) -> wasm_bindgen::JsValue {
@@ -225,6 +307,7 @@ pub(crate) enum Kind {
     Status(StatusCode),
     Body,
     Decode,
+    Upgrade,
 }
 
 // constructors
@@ -237,6 +320,10 @@ pub(crate) fn body<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Body, Some(e))
 }
 
+pub(crate) fn body_too_large(limit: u64) -> Error {
+    Error::new(Kind::Body, Some(BodyTooLarge { limit }))
+}
+
 pub(crate) fn decode<E: Into<BoxError>>(e: E) -> Error {
     Error::new(Kind::Decode, Some(e))
 }
@@ -257,6 +344,12 @@ pub(crate) fn url_bad_scheme(url: Url) -> Error {
     Error::new(Kind::Builder, Some("URL scheme is not allowed")).with_url(url)
 }
 
+if_hyper! {
+    pub(crate) fn upgrade<E: Into<BoxError>>(e: E) -> Error {
+        Error::new(Kind::Upgrade, Some(e))
+    }
+}
+
 if_wasm! {
     pub(crate) fn wasm(js_val: wasm_bindgen::JsValue) -> BoxError {
         format!("{:?}", js_val).into()
@@ -272,20 +365,41 @@ pub(crate) fn into_io(e: Error) -> io::Error {
 
 #[allow(unused)]
 pub(crate) fn decode_io(e: io::Error) -> Error {
+    unwrap_io(e).unwrap_or_else(decode)
+}
+
+/// Takes the `Error` out of `e`, if it wraps one.
+fn unwrap_io(e: io::Error) -> std::result::Result<Error, io::Error> {
     if e.get_ref().map(|r| r.is::<Error>()).unwrap_or(false) {
-        *e.into_inner()
+        Ok(*e
+            .into_inner()
             .expect("io::Error::get_ref was Some(_)")
             .downcast::<Error>()
-            .expect("StdError::is() was true")
+            .expect("StdError::is() was true"))
     } else {
-        decode(e)
+        Err(e)
     }
 }
 
 // internal Error "sources"
 
+/// The phase of a request in which a timeout elapsed.
+///
+/// See [`Error::timeout_kind`](Error::timeout_kind).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeoutKind {
+    /// Establishing the connection took too long.
+    Connect,
+    /// A single read of the response body took too long.
+    Read,
+    /// Reading the response body took too long.
+    Body,
+    /// The request did not complete in time.
+    Overall,
+}
+
 #[derive(Debug)]
-pub(crate) struct TimedOut;
+pub(crate) struct TimedOut(pub(crate) TimeoutKind);
 
 impl fmt::Display for TimedOut {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -295,6 +409,19 @@ impl fmt::Display for TimedOut {
 
 impl StdError for TimedOut {}
 
+#[derive(Debug)]
+pub(crate) struct BodyTooLarge {
+    limit: u64,
+}
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "body exceeded the size limit of {} bytes", self.limit)
+    }
+}
+
+impl StdError for BodyTooLarge {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -333,6 +460,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn into_io_error_kind() {
+        let timeout = super::request(super::TimedOut(TimeoutKind::Connect));
+        assert_eq!(io::Error::from(timeout).kind(), io::ErrorKind::TimedOut);
+
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        let err = super::request(super::request(refused));
+        let kind = io::Error::from(err).kind();
+        assert_eq!(kind, io::ErrorKind::ConnectionRefused);
+
+        // Kinds that callers retry on aren't passed through.
+        let interrupted = io::Error::new(io::ErrorKind::Interrupted, "interrupted");
+        let err = super::body(interrupted);
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::Other);
+        let would_block = io::Error::new(io::ErrorKind::WouldBlock, "would block");
+        let err = super::body(would_block);
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::Other);
+
+        let other = super::builder("bad");
+        let io = io::Error::from(other);
+        assert_eq!(io.kind(), io::ErrorKind::Other);
+        // The original error is kept, and comes back out.
+        assert!(Error::from(io).is_builder());
+
+        let custom = io::Error::new(io::ErrorKind::NotFound, "upload.txt");
+        assert!(Error::from(custom).is_body());
+    }
+
     #[test]
     fn from_unknown_io_error() {
         let orig = io::Error::new(io::ErrorKind::Other, "orly");
@@ -345,11 +500,33 @@ mod tests {
 
     #[test]
     fn is_timeout() {
-        let err = super::request(super::TimedOut);
+        let err = super::request(super::TimedOut(TimeoutKind::Overall));
         assert!(err.is_timeout());
+        assert_eq!(err.timeout_kind(), Some(TimeoutKind::Overall));
 
         let io = io::Error::new(io::ErrorKind::Other, err);
         let nested = super::request(io);
         assert!(nested.is_timeout());
+        assert_eq!(nested.timeout_kind(), Some(TimeoutKind::Overall));
+
+        let other = super::request("orly");
+        assert_eq!(other.timeout_kind(), None);
+    }
+
+    #[test]
+    fn is_body_too_large() {
+        let err = super::body_too_large(1024);
+        assert!(err.is_body());
+        assert!(err.is_body_too_large());
+        assert_eq!(err.body_limit(), Some(1024));
+
+        let io = err.into_io();
+        let nested = super::decode(io);
+        assert!(nested.is_body_too_large());
+        assert_eq!(nested.body_limit(), Some(1024));
+
+        let other = super::body(super::TimedOut(TimeoutKind::Body));
+        assert!(!other.is_body_too_large());
+        assert_eq!(other.body_limit(), None);
     }
 }
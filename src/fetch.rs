@@ -0,0 +1,68 @@
+//! Options for the `fetch` API used by the WebAssembly target.
+
+/// The [cache mode][mdn] of a request made with `fetch`.
+///
+/// This option is only effective with the WebAssembly target.
+///
+/// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Use the HTTP cache as usual, revalidating stale responses.
+    Default,
+    /// Bypass the HTTP cache, and don't store the response in it.
+    NoStore,
+    /// Bypass the HTTP cache, but store the response in it.
+    Reload,
+    /// Always revalidate a cached response with the server.
+    NoCache,
+    /// Use a cached response regardless of its freshness.
+    ForceCache,
+    /// Only use a cached response, failing if there is none.
+    ///
+    /// Requires the request mode to be `same-origin`.
+    OnlyIfCached,
+}
+
+/// The [redirect mode][mdn] of a request made with `fetch`.
+///
+/// This option is only effective with the WebAssembly target. Native
+/// clients use their `redirect::Policy` instead.
+///
+/// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/redirect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchRedirect {
+    /// Follow redirects.
+    Follow,
+    /// Fail the request if the response is a redirect.
+    Error,
+    /// Don't follow redirects, and return the redirect response instead.
+    ///
+    /// The browser hides the status and headers of such an opaque redirect,
+    /// so it is returned with a `302 Found` status and no headers.
+    Manual,
+}
+
+if_wasm! {
+    impl From<FetchRedirect> for web_sys::RequestRedirect {
+        fn from(mode: FetchRedirect) -> web_sys::RequestRedirect {
+            match mode {
+                FetchRedirect::Follow => web_sys::RequestRedirect::Follow,
+                FetchRedirect::Error => web_sys::RequestRedirect::Error,
+                FetchRedirect::Manual => web_sys::RequestRedirect::Manual,
+            }
+        }
+    }
+
+    impl From<CacheMode> for web_sys::RequestCache {
+        fn from(mode: CacheMode) -> web_sys::RequestCache {
+            match mode {
+                CacheMode::Default => web_sys::RequestCache::Default,
+                CacheMode::NoStore => web_sys::RequestCache::NoStore,
+                CacheMode::Reload => web_sys::RequestCache::Reload,
+                CacheMode::NoCache => web_sys::RequestCache::NoCache,
+                CacheMode::ForceCache => web_sys::RequestCache::ForceCache,
+                CacheMode::OnlyIfCached => web_sys::RequestCache::OnlyIfCached,
+            }
+        }
+    }
+}
@@ -213,9 +213,11 @@ pub use url::Url;
 // universal mods
 #[macro_use]
 mod error;
+mod fetch;
 mod into_url;
 
-pub use self::error::{Error, Result};
+pub use self::error::{Error, Result, TimeoutKind};
+pub use self::fetch::{CacheMode, FetchRedirect};
 pub use self::into_url::IntoUrl;
 
 /// Shortcut method to quickly make a `GET` request.
@@ -282,11 +284,15 @@ if_hyper! {
     doctest!("../README.md");
 
     pub use self::async_impl::{
-        multipart, Body, Client, ClientBuilder, Request, RequestBuilder, Response, ResponseBuilderExt,
+        multipart, Body, Client, ClientBuilder, ClientConfig, ContentEncoding, HttpService,
+        Request, RequestBuilder, RequestHead, Response, ResponseBuilderExt, ResponseFuture,
+        Upgraded,
     };
     pub use self::proxy::Proxy;
     #[cfg(feature = "__tls")]
     pub use self::tls::{Certificate, Identity};
+    #[cfg(feature = "rustls-tls")]
+    pub use self::tls::TlsInfo;
 
 
     mod async_impl;
@@ -299,6 +305,7 @@ if_hyper! {
     mod dns;
     mod proxy;
     pub mod redirect;
+    pub mod retry;
     #[cfg(feature = "__tls")]
     mod tls;
     mod util;
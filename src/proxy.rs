@@ -42,6 +42,20 @@ use winreg::RegKey;
 /// `Proxy` added first with eager intercept rules, such as `Proxy::all`,
 /// would prevent a `Proxy` later in the list from ever working, so take care.
 ///
+/// Cleartext and TLS traffic can be sent to different proxies, like the
+/// `HTTP_PROXY` and `HTTPS_PROXY` environment variables do, by adding both
+/// a `Proxy::http` and a `Proxy::https`:
+///
+/// ```rust
+/// # fn run() -> Result<(), Box<std::error::Error>> {
+/// let client = reqwest::Client::builder()
+///     .proxy(reqwest::Proxy::http("http://plain.example:3128")?)
+///     .proxy(reqwest::Proxy::https("http://tls.example:3128")?)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+///
 /// By enabling the `"socks"` feature it is possible to use a socks proxy:
 /// ```rust
 /// # fn run() -> Result<(), Box<std::error::Error>> {
@@ -180,7 +194,11 @@ impl Proxy {
         )))
     }
 
-    /// Provide a custom function to determine what traffix to proxy to where.
+    /// Provide a custom function to determine what traffic to proxy to where.
+    ///
+    /// The function is called with the `Url` of each request, including
+    /// any redirects that are followed, and returns the proxy to use for
+    /// it. Returning `None` connects to the destination directly.
     ///
     /// # Example
     ///
@@ -200,6 +218,7 @@ impl Proxy {
     /// # Ok(())
     /// # }
     /// # fn main() {}
+    /// ```
     pub fn custom<F, U: IntoProxyScheme>(fun: F) -> Proxy
     where
         F: Fn(&Url) -> Option<U> + Send + Sync + 'static,
@@ -255,6 +274,15 @@ impl Proxy {
         }
     }
 
+    /// Whether this proxy could intercept any request, which a system
+    /// proxy without any configured proxies can't.
+    pub(crate) fn is_active(&self) -> bool {
+        match self.intercept {
+            Intercept::System(ref map) => !map.is_empty(),
+            _ => true,
+        }
+    }
+
     pub(crate) fn http_basic_auth<D: Dst>(&self, uri: &D) -> Option<HeaderValue> {
         match self.intercept {
             Intercept::All(ProxyScheme::Http { ref auth, .. })
@@ -23,6 +23,7 @@ use crate::Url;
 /// - `custom` can be used to create a customized policy.
 pub struct Policy {
     inner: PolicyKind,
+    keep_method: Vec<StatusCode>,
 }
 
 /// A type that holds information on the next request and previous requests
@@ -47,6 +48,7 @@ impl Policy {
     pub fn limited(max: usize) -> Self {
         Self {
             inner: PolicyKind::Limit(max),
+            keep_method: Vec::new(),
         }
     }
 
@@ -54,6 +56,7 @@ impl Policy {
     pub fn none() -> Self {
         Self {
             inner: PolicyKind::None,
+            keep_method: Vec::new(),
         }
     }
 
@@ -101,9 +104,39 @@ impl Policy {
     {
         Self {
             inner: PolicyKind::Custom(Box::new(policy)),
+            keep_method: Vec::new(),
         }
     }
 
+    /// Keep the method and body of a request when following a redirect
+    /// with `status`.
+    ///
+    /// By default, a `POST` (or other non-`GET`) request redirected with
+    /// `301 Moved Permanently`, `302 Found` or `303 See Other` is followed
+    /// with a `GET` and no body, as browsers do. Some older servers expect
+    /// the original method to be sent again instead.
+    ///
+    /// # Note
+    ///
+    /// Resending the method on `301` and `302` is allowed, but goes against
+    /// what most clients do. For `303`, RFC 7231 expects a `GET`. Prefer
+    /// servers that answer with `307` or `308` for this.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use reqwest::{redirect, StatusCode};
+    /// let policy = redirect::Policy::default()
+    ///     .keep_method_on(StatusCode::MOVED_PERMANENTLY)
+    ///     .keep_method_on(StatusCode::FOUND);
+    /// ```
+    pub fn keep_method_on(mut self, status: StatusCode) -> Self {
+        if !self.keep_method.contains(&status) {
+            self.keep_method.push(status);
+        }
+        self
+    }
+
     /// Apply this policy to a given [`Attempt`] to produce a [`Action`].
     ///
     /// # Note
@@ -149,10 +182,25 @@ impl Policy {
 
     pub(crate) fn is_default(&self) -> bool {
         match self.inner {
-            PolicyKind::Limit(10) => true,
+            PolicyKind::Limit(10) => self.keep_method.is_empty(),
             _ => false,
         }
     }
+
+    /// Whether a redirect with `status` keeps the request's method and body.
+    pub(crate) fn keeps_method(&self, status: StatusCode) -> bool {
+        self.keep_method.contains(&status)
+    }
+
+    /// The maximum number of redirects followed, or `None` for a custom
+    /// policy.
+    pub(crate) fn max_redirects(&self) -> Option<usize> {
+        match self.inner {
+            PolicyKind::Custom(..) => None,
+            PolicyKind::Limit(max) => Some(max),
+            PolicyKind::None => Some(0),
+        }
+    }
 }
 
 impl Default for Policy {
@@ -211,7 +259,14 @@ enum PolicyKind {
 
 impl fmt::Debug for Policy {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_tuple("Policy").field(&self.inner).finish()
+        if self.keep_method.is_empty() {
+            f.debug_tuple("Policy").field(&self.inner).finish()
+        } else {
+            f.debug_tuple("Policy")
+                .field(&self.inner)
+                .field(&self.keep_method)
+                .finish()
+        }
     }
 }
 
@@ -0,0 +1,184 @@
+//! Retry Handling
+//!
+//! By default, a `Client` doesn't send a request again when it fails. To
+//! retry requests after transient errors, a `retry::Policy` can be used
+//! with a `ClientBuilder`.
+
+use std::time::Duration;
+
+use hyper::StatusCode;
+
+/// A type that controls the policy on retrying failed requests.
+///
+/// The default value doesn't retry at all.
+///
+/// - `limited` retries connect errors and connections that were reset, up
+///   to a maximum number of times.
+/// - `none` disables retries.
+///
+/// Only requests with an idempotent method, such as `GET` or `PUT`, and a
+/// body that can be sent again are retried, unless `retry_non_idempotent`
+/// is enabled. Retries happen within the request's timeout, if any.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use reqwest::{retry, StatusCode};
+/// let policy = retry::Policy::limited(3)
+///     .backoff(Duration::from_millis(200))
+///     .retry_status(StatusCode::SERVICE_UNAVAILABLE);
+/// let client = reqwest::Client::builder()
+///     .retry(policy)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Policy {
+    max_retries: usize,
+    backoff: Duration,
+    statuses: Vec<StatusCode>,
+    connect_errors: bool,
+    connection_resets: bool,
+    non_idempotent: bool,
+}
+
+impl Policy {
+    /// Create a `Policy` that retries a request at most `max_retries` times.
+    ///
+    /// Connect errors and connections that were reset before a response
+    /// arrived are retried, and no status code is. The first retry waits
+    /// 100 milliseconds.
+    pub fn limited(max_retries: usize) -> Policy {
+        Policy {
+            max_retries,
+            backoff: Duration::from_millis(100),
+            statuses: Vec::new(),
+            connect_errors: true,
+            connection_resets: true,
+            non_idempotent: false,
+        }
+    }
+
+    /// Create a `Policy` that doesn't retry requests.
+    pub fn none() -> Policy {
+        Policy::limited(0)
+    }
+
+    /// Set how long to wait before the first retry.
+    ///
+    /// The wait doubles with each following retry.
+    pub fn backoff(mut self, initial: Duration) -> Policy {
+        self.backoff = initial;
+        self
+    }
+
+    /// Also retry requests answered with `status`, such as
+    /// `503 Service Unavailable`.
+    ///
+    /// The response is discarded. A small body is read during the backoff,
+    /// so that its connection can be reused, while a larger one closes its
+    /// connection. If no retries are left, the response is returned.
+    pub fn retry_status(mut self, status: StatusCode) -> Policy {
+        if !self.statuses.contains(&status) {
+            self.statuses.push(status);
+        }
+        self
+    }
+
+    /// Set whether requests are retried when a connection can't be opened.
+    ///
+    /// Default is `true`.
+    pub fn retry_connect_errors(mut self, enable: bool) -> Policy {
+        self.connect_errors = enable;
+        self
+    }
+
+    /// Set whether requests are retried when their connection is closed or
+    /// reset before a response arrived.
+    ///
+    /// Default is `true`.
+    pub fn retry_connection_resets(mut self, enable: bool) -> Policy {
+        self.connection_resets = enable;
+        self
+    }
+
+    /// Set whether requests with a method that isn't idempotent, such as
+    /// `POST`, are retried as well.
+    ///
+    /// Such a request may have been processed before its connection was
+    /// reset, so retrying it can apply it twice.
+    ///
+    /// Default is `false`.
+    pub fn retry_non_idempotent(mut self, enable: bool) -> Policy {
+        self.non_idempotent = enable;
+        self
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        self.max_retries == 0
+    }
+
+    /// Whether a request with a method that is `idempotent` or not may be
+    /// retried at all.
+    pub(crate) fn allows(&self, idempotent: bool) -> bool {
+        self.max_retries > 0 && (idempotent || self.non_idempotent)
+    }
+
+    /// Whether the `retries`th retry should follow a connect error, if
+    /// `connect`, or a reset connection.
+    pub(crate) fn retries_error(&self, retries: usize, connect: bool) -> bool {
+        let kind = if connect {
+            self.connect_errors
+        } else {
+            self.connection_resets
+        };
+        kind && retries < self.max_retries
+    }
+
+    /// Whether the `retries`th retry should follow a response with `status`.
+    pub(crate) fn retries_status(&self, retries: usize, status: StatusCode) -> bool {
+        self.statuses.contains(&status) && retries < self.max_retries
+    }
+
+    /// How long to wait before the `retries`th retry, counting from zero.
+    pub(crate) fn delay(&self, retries: usize) -> Duration {
+        let factor = 1u32 << retries.min(31);
+        self.backoff
+            .checked_mul(factor)
+            .unwrap_or_else(|| Duration::from_secs(u64::from(!0u32)))
+    }
+}
+
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limited() {
+        let policy = Policy::limited(2).retry_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(policy.allows(true));
+        assert!(!policy.allows(false));
+        assert!(policy.retries_error(1, true));
+        assert!(!policy.retries_error(2, false));
+        assert!(policy.retries_status(0, StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!policy.retries_status(0, StatusCode::INTERNAL_SERVER_ERROR));
+
+        assert!(!Policy::none().allows(true));
+        assert!(Policy::limited(1).retry_non_idempotent(true).allows(false));
+    }
+
+    #[test]
+    fn test_delay_doubles() {
+        let policy = Policy::limited(3).backoff(Duration::from_millis(10));
+        assert_eq!(policy.delay(0), Duration::from_millis(10));
+        assert_eq!(policy.delay(2), Duration::from_millis(40));
+        assert!(policy.delay(64) >= policy.delay(2));
+    }
+}
@@ -1,5 +1,5 @@
 #[cfg(feature = "rustls-tls")]
-use rustls::{RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError};
+use rustls::{RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError, WebPKIVerifier};
 use std::fmt;
 #[cfg(feature = "rustls-tls")]
 use tokio_rustls::webpki::DNSNameRef;
@@ -266,6 +266,65 @@ impl fmt::Debug for Identity {
     }
 }
 
+/// Information about the TLS session a `Response` was received on.
+///
+/// It's found in the `Response`'s extensions when the connection was made
+/// with the `rustls` backend:
+///
+/// ```no_run
+/// # async fn run() -> Result<(), reqwest::Error> {
+/// let res = reqwest::get("https://hyper.rs").await?;
+/// if let Some(info) = res.extensions().get::<reqwest::TlsInfo>() {
+///     println!("ALPN: {:?}", info.alpn_protocol());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Optional
+///
+/// This requires the optional `rustls-tls` feature to be enabled.
+#[cfg(feature = "rustls-tls")]
+#[derive(Clone)]
+pub struct TlsInfo {
+    peer_certificate: Option<Vec<u8>>,
+    alpn_protocol: Option<Vec<u8>>,
+}
+
+#[cfg(feature = "rustls-tls")]
+impl TlsInfo {
+    pub(crate) fn from_session(session: &rustls::ClientSession) -> TlsInfo {
+        use rustls::Session;
+
+        TlsInfo {
+            peer_certificate: session
+                .get_peer_certificates()
+                .and_then(|certs| certs.into_iter().next())
+                .map(|cert| cert.0),
+            alpn_protocol: session.get_alpn_protocol().map(|proto| proto.to_vec()),
+        }
+    }
+
+    /// Returns the DER encoded certificate of the server, if it sent one.
+    pub fn peer_certificate(&self) -> Option<&[u8]> {
+        self.peer_certificate.as_ref().map(|der| &der[..])
+    }
+
+    /// Returns the protocol agreed on with ALPN, if any.
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_ref().map(|proto| &proto[..])
+    }
+}
+
+#[cfg(feature = "rustls-tls")]
+impl fmt::Debug for TlsInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TlsInfo")
+            .field("alpn_protocol", &self.alpn_protocol)
+            .finish()
+    }
+}
+
 pub(crate) enum TlsBackend {
     #[cfg(feature = "default-tls")]
     Default,
@@ -282,6 +341,22 @@ pub(crate) enum TlsBackend {
     UnknownPreconfigured,
 }
 
+impl TlsBackend {
+    /// The name of the crate feature selecting this backend.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "default-tls")]
+            TlsBackend::Default => "default-tls",
+            #[cfg(feature = "native-tls")]
+            TlsBackend::BuiltNativeTls(_) => "native-tls",
+            #[cfg(feature = "rustls-tls")]
+            TlsBackend::Rustls | TlsBackend::BuiltRustls(_) => "rustls-tls",
+            #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+            TlsBackend::UnknownPreconfigured => "unknown",
+        }
+    }
+}
+
 impl fmt::Debug for TlsBackend {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -332,6 +407,79 @@ impl ServerCertVerifier for NoVerifier {
     }
 }
 
+/// Verifies the certificate chain, but accepts it for any hostname.
+#[cfg(feature = "rustls-tls")]
+pub(crate) struct IgnoreHostname {
+    inner: WebPKIVerifier,
+}
+
+#[cfg(feature = "rustls-tls")]
+impl IgnoreHostname {
+    pub(crate) fn new() -> IgnoreHostname {
+        IgnoreHostname {
+            inner: WebPKIVerifier::new(),
+        }
+    }
+}
+
+#[cfg(feature = "rustls-tls")]
+impl ServerCertVerifier for IgnoreHostname {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        dns_name: DNSNameRef,
+        ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        // The chain is checked before the name, so a name mismatch means
+        // the chain itself was valid.
+        match self
+            .inner
+            .verify_server_cert(roots, presented_certs, dns_name, ocsp_response)
+        {
+            Err(TLSError::WebPKIError(tokio_rustls::webpki::Error::CertNotValidForName)) => {
+                Ok(ServerCertVerified::assertion())
+            }
+            res => res,
+        }
+    }
+}
+
+/// Accepts any certificate for the listed hosts, and has `inner` verify
+/// the certificates of all other hosts.
+#[cfg(feature = "rustls-tls")]
+pub(crate) struct AcceptInvalidFor {
+    hosts: Vec<String>,
+    inner: Box<dyn ServerCertVerifier>,
+}
+
+#[cfg(feature = "rustls-tls")]
+impl AcceptInvalidFor {
+    pub(crate) fn new(hosts: Vec<String>, inner: Box<dyn ServerCertVerifier>) -> AcceptInvalidFor {
+        AcceptInvalidFor { hosts, inner }
+    }
+}
+
+#[cfg(feature = "rustls-tls")]
+impl ServerCertVerifier for AcceptInvalidFor {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[rustls::Certificate],
+        dns_name: DNSNameRef,
+        ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let name: &str = dns_name.into();
+        for host in &self.hosts {
+            if host.eq_ignore_ascii_case(name) {
+                return Ok(ServerCertVerified::assertion());
+            }
+        }
+        self.inner
+            .verify_server_cert(roots, presented_certs, dns_name, ocsp_response)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -370,4 +518,38 @@ mod tests {
 
         Identity::from_pem(pem).unwrap();
     }
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn ignore_hostname_still_verifies_chain() {
+        let verifier = IgnoreHostname::new();
+        let roots = RootCertStore::empty();
+        let certs = [rustls::Certificate(b"not der".to_vec())];
+
+        let name = DNSNameRef::try_from_ascii_str("hyper.rs").unwrap();
+        assert!(verifier
+            .verify_server_cert(&roots, &certs, name, &[])
+            .is_err());
+    }
+
+    #[cfg(feature = "rustls-tls")]
+    #[test]
+    fn accept_invalid_certs_for_listed_hosts() {
+        let verifier = AcceptInvalidFor::new(
+            vec!["internal.example".into()],
+            Box::new(WebPKIVerifier::new()),
+        );
+        let roots = RootCertStore::empty();
+        let certs = [rustls::Certificate(b"not der".to_vec())];
+
+        let listed = DNSNameRef::try_from_ascii_str("Internal.Example").unwrap();
+        assert!(verifier
+            .verify_server_cert(&roots, &certs, listed, &[])
+            .is_ok());
+
+        let other = DNSNameRef::try_from_ascii_str("hyper.rs").unwrap();
+        assert!(verifier
+            .verify_server_cert(&roots, &certs, other, &[])
+            .is_err());
+    }
 }
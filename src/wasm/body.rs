@@ -38,6 +38,14 @@ impl Body {
         }
     }
 
+    /// Multipart bodies cannot be cloned.
+    pub(crate) fn try_clone(&self) -> Option<Body> {
+        match self.inner {
+            Inner::Bytes(ref bytes) => Some(Body::from(bytes.clone())),
+            Inner::Multipart(_) => None,
+        }
+    }
+
     #[inline]
     pub(crate) fn from_form(f: Form) -> Body {
         Self {
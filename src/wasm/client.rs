@@ -157,6 +157,14 @@ async fn fetch(req: Request) -> crate::Result<Response> {
         init.mode(web_sys::RequestMode::NoCors);
     }
 
+    if let Some(cache) = req.cache {
+        init.cache(cache.into());
+    }
+
+    if let Some(redirect) = req.redirect {
+        init.redirect(redirect.into());
+    }
+
     if let Some(body) = req.body() {
         init.body(Some(&body.to_js_value()?.as_ref().as_ref()));
     }
@@ -172,8 +180,14 @@ async fn fetch(req: Request) -> crate::Result<Response> {
         .map_err(crate::error::request)?;
 
     // Convert from the js Response
+    //
+    // An opaque redirect, from `FetchRedirect::Manual`, has a status of 0.
+    let status = match js_resp.type_() {
+        web_sys::ResponseType::Opaqueredirect => http::StatusCode::FOUND.as_u16(),
+        _ => js_resp.status(),
+    };
     let mut resp = http::Response::builder()
-        .status(js_resp.status());
+        .status(status);
 
     let url = Url::parse(&js_resp.url()).expect_throw("url parse");
 
@@ -10,6 +10,7 @@ use serde_urlencoded;
 
 use super::{Body, Client, Response};
 use crate::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use crate::{CacheMode, FetchRedirect};
 
 /// A request which can be executed with `Client::execute()`.
 pub struct Request {
@@ -18,6 +19,8 @@ pub struct Request {
     headers: HeaderMap,
     body: Option<Body>,
     pub(super) cors: bool,
+    pub(super) cache: Option<CacheMode>,
+    pub(super) redirect: Option<FetchRedirect>,
 }
 
 /// A builder to construct the properties of a `Request`.
@@ -34,6 +37,8 @@ impl Request {
             headers: HeaderMap::new(),
             body: None,
             cors: true,
+            cache: None,
+            redirect: None,
         }
     }
 
@@ -84,6 +89,33 @@ impl Request {
     pub fn body_mut(&mut self) -> &mut Option<Body> {
         &mut self.body
     }
+
+    /// Get whether CORS is enabled when fetching the request.
+    ///
+    /// This is `false` if it was disabled with
+    /// `RequestBuilder::fetch_mode_no_cors`.
+    #[inline]
+    pub fn cors(&self) -> bool {
+        self.cors
+    }
+
+    /// Attempt to clone the request.
+    ///
+    /// `None` is returned if the request can not be cloned, i.e. if the body
+    /// is a multipart form.
+    pub fn try_clone(&self) -> Option<Request> {
+        let body = match self.body.as_ref() {
+            Some(body) => Some(body.try_clone()?),
+            None => None,
+        };
+        let mut req = Request::new(self.method().clone(), self.url().clone());
+        *req.headers_mut() = self.headers().clone();
+        req.body = body;
+        req.cors = self.cors;
+        req.cache = self.cache;
+        req.redirect = self.redirect;
+        Some(req)
+    }
 }
 
 impl RequestBuilder {
@@ -250,6 +282,40 @@ impl RequestBuilder {
         self
     }
 
+    /// Set the cache mode used when fetching the request.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request cache mode][mdn] will be set to the given mode.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/cache
+    pub fn fetch_cache(mut self, mode: CacheMode) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.cache = Some(mode);
+        }
+        self
+    }
+
+    /// Set how redirects are handled when fetching the request.
+    ///
+    /// # WASM
+    ///
+    /// This option is only effective with WebAssembly target.
+    ///
+    /// The [request redirect mode][mdn] will be set to the given mode. With
+    /// `FetchRedirect::Manual`, the redirect response is returned instead
+    /// of being followed.
+    ///
+    /// [mdn]: https://developer.mozilla.org/en-US/docs/Web/API/Request/redirect
+    pub fn fetch_redirect(mut self, mode: FetchRedirect) -> RequestBuilder {
+        if let Ok(ref mut req) = self.request {
+            req.redirect = Some(mode);
+        }
+        self
+    }
+
     /// Constructs the Request and sends it to the target URL, returning a
     /// future Response.
     ///
@@ -274,6 +340,21 @@ impl RequestBuilder {
         let req = self.request?;
         self.client.execute_request(req).await
     }
+
+    /// Attempt to clone the RequestBuilder.
+    ///
+    /// `None` is returned if the RequestBuilder can not be cloned,
+    /// i.e. if the request body is a multipart form.
+    pub fn try_clone(&self) -> Option<RequestBuilder> {
+        self.request
+            .as_ref()
+            .ok()
+            .and_then(|req| req.try_clone())
+            .map(|req| RequestBuilder {
+                client: self.client.clone(),
+                request: Ok(req),
+            })
+    }
 }
 
 impl fmt::Debug for Request {
@@ -322,6 +403,64 @@ where
             headers,
             body: Some(body.into()),
             cors: true,
+            cache: None,
+            redirect: None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Client;
+    use crate::{CacheMode, FetchRedirect};
+
+    #[test]
+    fn cors_is_enabled_by_default() {
+        let client = Client::new();
+        let req = client
+            .get("https://localhost/")
+            .request
+            .expect("request build");
+        assert!(req.cors());
+
+        let req = client
+            .get("https://localhost/")
+            .fetch_mode_no_cors()
+            .request
+            .expect("request build");
+        assert!(!req.cors());
+    }
+
+    #[test]
+    fn try_clone_keeps_fetch_options() {
+        let client = Client::new();
+        let builder = client
+            .get("https://localhost/")
+            .header("x-clone", "yes")
+            .body("body")
+            .fetch_mode_no_cors()
+            .fetch_cache(CacheMode::NoStore)
+            .fetch_redirect(FetchRedirect::Manual);
+
+        let req = builder
+            .try_clone()
+            .expect("clone builder")
+            .request
+            .expect("request build");
+        assert!(!req.cors());
+
+        let clone = req.try_clone().expect("clone request");
+        assert!(!clone.cors());
+        assert_eq!(clone.cache, Some(CacheMode::NoStore));
+        assert_eq!(clone.redirect, Some(FetchRedirect::Manual));
+        assert_eq!(clone.headers()["x-clone"], "yes");
+        assert!(clone.body().is_some());
+    }
+
+    #[test]
+    fn try_clone_fails_for_multipart() {
+        let form = crate::multipart::Form::new().text("key", "value");
+        let builder = Client::new().get("https://localhost/").multipart(form);
+        assert!(builder.try_clone().is_none());
+    }
+}
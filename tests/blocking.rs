@@ -35,6 +35,42 @@ fn test_response_non_utf_8_text() {
     assert_eq!(b"\xe4\xbd\xa0\xe5\xa5\xbd", body.as_bytes()); // Now it's utf-8
 }
 
+#[test]
+fn test_response_body_too_large() {
+    let server = server::http(move |_req| async { http::Response::new("Hello World".into()) });
+
+    let url = format!("http://{}/text", server.addr());
+    let client = reqwest::blocking::Client::builder()
+        .max_response_size(5)
+        .build()
+        .unwrap();
+    let res = client.get(&url).send().unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let err = res.text().unwrap_err();
+    assert!(err.is_body_too_large());
+    assert_eq!(err.body_limit(), Some(5));
+}
+
+#[test]
+fn test_response_bytes_limited() {
+    let server = server::http(move |_req| async { http::Response::new("Hello World".into()) });
+
+    let url = format!("http://{}/bytes", server.addr());
+
+    let err = reqwest::blocking::get(&url)
+        .unwrap()
+        .bytes_limited(5)
+        .unwrap_err();
+    assert!(err.is_body_too_large());
+
+    let bytes = reqwest::blocking::get(&url)
+        .unwrap()
+        .bytes_limited(11)
+        .unwrap();
+    assert_eq!("Hello World", bytes);
+}
+
 #[test]
 #[cfg(feature = "json")]
 fn test_response_json() {
@@ -101,6 +137,38 @@ fn test_post() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[test]
+fn test_post_inspected_reader() {
+    use std::sync::{Arc, Mutex};
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-length"], "11");
+
+        let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&*data, b"hello world");
+
+        http::Response::default()
+    });
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let inspected = seen.clone();
+    let body = reqwest::blocking::Body::from_reader_inspected(
+        std::io::Cursor::new(b"hello world".to_vec()),
+        Some(11),
+        move |chunk| inspected.lock().unwrap().extend_from_slice(chunk),
+    );
+
+    let url = format!("http://{}/inspect", server.addr());
+    let res = reqwest::blocking::Client::new()
+        .post(&url)
+        .body(body)
+        .send()
+        .unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(&*seen.lock().unwrap(), b"hello world");
+}
+
 #[test]
 fn test_post_form() {
     let server = server::http(move |req| async move {
@@ -288,3 +356,69 @@ fn test_blocking_inside_a_runtime() {
         let _should_panic = reqwest::blocking::get(&url);
     });
 }
+
+#[test]
+fn test_client_shutdown_timeout() {
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let url = format!("http://{}/shutdown", server.addr());
+    let client = reqwest::blocking::Client::new();
+
+    let res = client.get(&url).send().unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    // a clone is still alive, so the runtime cannot be stopped yet
+    let clone = client.clone();
+    assert!(!clone.shutdown_timeout(std::time::Duration::from_secs(5)));
+
+    drop(res);
+    assert!(client.shutdown_timeout(std::time::Duration::from_secs(5)));
+}
+
+#[test]
+fn test_post_file_retry() {
+    use std::io::Write;
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-length"], "11");
+
+        let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&*data, b"hello world");
+
+        http::Response::default()
+    });
+
+    let path = std::env::temp_dir().join("reqwest-test-blocking-file-retry.txt");
+    std::fs::File::create(&path)
+        .and_then(|mut file| file.write_all(b"hello world"))
+        .unwrap();
+    let file = std::fs::File::open(&path).unwrap();
+
+    let client = reqwest::blocking::Client::new();
+    let url = format!("http://{}/file", server.addr());
+    let req = client.post(&url).body(file).build().unwrap();
+    let retry = req.try_clone().expect("file body is cloneable");
+
+    let res = client.execute(req).unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    let res = client.execute(retry).unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_client_config() {
+    let client = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(3))
+        .build()
+        .unwrap();
+    assert_eq!(client.config().timeout(), Some(std::time::Duration::from_secs(30)));
+    assert_eq!(client.config().max_redirects(), Some(3));
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(None)
+        .build()
+        .unwrap();
+    assert_eq!(client.config().timeout(), None);
+}
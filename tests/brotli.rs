@@ -36,6 +36,38 @@ async fn test_brotli_empty_body() {
     assert_eq!(body, "");
 }
 
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_accept_encoding_priority() {
+    let server = server::http(move |req| async move {
+        let expected = match req.uri().path() {
+            "/default" => "gzip, br",
+            "/prefer-br" => "br, gzip",
+            _ => unreachable!(),
+        };
+        assert_eq!(req.headers()["accept-encoding"], expected);
+        http::Response::default()
+    });
+
+    let res = reqwest::Client::new()
+        .get(&format!("http://{}/default", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let client = reqwest::Client::builder()
+        .accept_encoding_priority(vec![reqwest::ContentEncoding::Brotli])
+        .build()
+        .unwrap();
+    let res = client
+        .get(&format!("http://{}/prefer-br", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[tokio::test]
 async fn test_accept_header_is_not_changed_if_set() {
     let server = server::http(move |req| async move {
@@ -85,6 +117,44 @@ async fn test_accept_encoding_header_is_not_changed_if_set() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_stacked_gzip_brotli() {
+    use std::io::Write;
+
+    let content = "stacked encodings";
+
+    let mut gzip = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+    gzip.write_all(content.as_bytes()).unwrap();
+    let gzipped = gzip.finish().into_result().unwrap();
+
+    let mut encoder = brotli_crate::CompressorReader::new(&gzipped[..], 4096, 5, 20);
+    let mut encoded = Vec::new();
+    encoder.read_to_end(&mut encoded).unwrap();
+
+    let server = server::http(move |_req| {
+        let encoded = encoded.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip, br")
+                .header("content-length", encoded.len())
+                .body(encoded.into())
+                .unwrap()
+        }
+    });
+
+    let client = reqwest::Client::new();
+
+    let res = client
+        .get(&format!("http://{}/stacked", server.addr()))
+        .send()
+        .await
+        .expect("response");
+
+    assert_eq!(res.headers().get("content-encoding"), None);
+    assert_eq!(res.text().await.expect("text"), content);
+}
+
 async fn brotli_case(response_size: usize, chunk_size: usize) {
     use futures_util::stream::StreamExt;
 
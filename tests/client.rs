@@ -42,122 +42,1445 @@ async fn auto_headers() {
     assert_eq!(res.remote_addr(), Some(server.addr()));
 }
 
+#[tokio::test]
+async fn default_headers_merge() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let accept = req
+            .headers()
+            .get_all("accept")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        match req.uri().path() {
+            "/default" => assert_eq!(accept, ["text/html"]),
+            "/merged" => assert_eq!(accept, ["text/html", "application/json"]),
+            _ => unreachable!(),
+        }
+        assert_eq!(req.headers()["x-replaced"], "request");
+        http::Response::default()
+    });
+
+    let mut merged = reqwest::header::HeaderMap::new();
+    merged.insert("accept", "text/html".parse().unwrap());
+    let mut replaced = reqwest::header::HeaderMap::new();
+    replaced.insert("x-replaced", "default".parse().unwrap());
+
+    let client = Client::builder()
+        .default_headers(replaced)
+        .default_headers_merge(merged)
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(&format!("http://{}/default", server.addr()))
+        .header("x-replaced", "request")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let res = client
+        .get(&format!("http://{}/merged", server.addr()))
+        .header("accept", "application/json")
+        .header("x-replaced", "request")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[tokio::test]
 async fn user_agent() {
     let server = server::http(move |req| async move {
-        assert_eq!(req.headers()["user-agent"], "reqwest-test-agent");
+        assert_eq!(req.headers()["user-agent"], "reqwest-test-agent");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/ua", server.addr());
+    let res = reqwest::Client::builder()
+        .user_agent("reqwest-test-agent")
+        .build()
+        .expect("client builder")
+        .get(&url)
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn http1_writev() {
+    let server = server::http(move |req| async move {
+        let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&*body, b"Hello World");
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/writev", server.addr());
+    for &writev in &[true, false] {
+        let res = reqwest::Client::builder()
+            .http1_writev(writev)
+            .build()
+            .expect("client builder")
+            .post(&url)
+            .body("Hello World")
+            .send()
+            .await
+            .expect("request");
+
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn http2_prior_knowledge_https_only() {
+    let server = server::http(move |req| async move {
+        assert_eq!(req.version(), http::Version::HTTP_11);
+        http::Response::default()
+    });
+
+    let url = format!("http://{}/h1", server.addr());
+    let res = reqwest::Client::builder()
+        .http2_prior_knowledge_https_only()
+        .build()
+        .expect("client builder")
+        .get(&url)
+        .send()
+        .await
+        .expect("request");
+
+    assert_eq!(res.version(), reqwest::Version::HTTP_11);
+}
+
+#[tokio::test]
+async fn get_many() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let status = if req.uri() == "/missing" { 404 } else { 200 };
+        http::Response::builder()
+            .status(status)
+            .body(req.uri().path().to_owned().into())
+            .unwrap()
+    });
+
+    let urls = ["/a", "/b", "/missing"]
+        .iter()
+        .map(|path| format!("http://{}{}", server.addr(), path).parse().unwrap())
+        .collect::<Vec<reqwest::Url>>();
+
+    let check = |responses: Vec<(reqwest::Url, reqwest::Result<reqwest::Response>)>| {
+        let mut statuses = responses
+            .into_iter()
+            .map(|(url, res)| {
+                let res = res.expect("request");
+                assert_eq!(res.url(), &url);
+                (url.path().to_owned(), res.status().as_u16())
+            })
+            .collect::<Vec<_>>();
+        statuses.sort();
+        assert_eq!(
+            statuses,
+            vec![
+                ("/a".to_owned(), 200),
+                ("/b".to_owned(), 200),
+                ("/missing".to_owned(), 404),
+            ]
+        );
+    };
+
+    let client = Client::new();
+    check(client.get_many(urls.clone()).collect().await);
+    check(client.get_many_limited(urls.clone(), 1).collect().await);
+    check(client.get_many_limited(urls, 0).collect().await);
+}
+
+#[tokio::test]
+async fn concurrency_limit_per_host() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let _ = env_logger::try_init();
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+    let server = {
+        let in_flight = in_flight.clone();
+        let max_in_flight = max_in_flight.clone();
+        server::http(move |_req| {
+            let in_flight = in_flight.clone();
+            let max_in_flight = max_in_flight.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::delay_for(std::time::Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                http::Response::new("Hello".into())
+            }
+        })
+    };
+
+    let urls = (0..6)
+        .map(|i| format!("http://{}/{}", server.addr(), i).parse().unwrap())
+        .collect::<Vec<reqwest::Url>>();
+
+    let client = Client::builder()
+        .concurrency_limit_per_host(2)
+        .build()
+        .unwrap();
+    let mut responses = client.get_many(urls);
+    let mut count = 0;
+    while let Some((_, res)) = responses.next().await {
+        let text = res.unwrap().text().await.unwrap();
+        assert_eq!(text, "Hello");
+        count += 1;
+    }
+
+    assert_eq!(count, 6);
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn client_as_service() {
+    use hyper::service::Service;
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.uri(), "/service");
+        http::Response::new("Hello".into())
+    });
+
+    let mut client = Client::new();
+    let req = client
+        .get(&format!("http://{}/service", server.addr()))
+        .build()
+        .unwrap();
+
+    futures_util::future::poll_fn(|cx| client.poll_ready(cx))
+        .await
+        .expect("poll_ready");
+    let fut: reqwest::ResponseFuture = client.call(req);
+    let res = fut.await.expect("call");
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(res.text().await.expect("text"), "Hello");
+}
+
+#[tokio::test]
+async fn client_as_http_service() {
+    use hyper::service::Service;
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.uri(), "/http-service");
+        assert_eq!(req.headers()["x-foo"], "bar");
+        http::Response::new("Hello".into())
+    });
+
+    let mut service = Client::new().http_service();
+    let req = http::Request::builder()
+        .uri(format!("http://{}/http-service", server.addr()))
+        .header("x-foo", "bar")
+        .body(reqwest::Body::from(""))
+        .unwrap();
+
+    let res = service.call(req).await.expect("call");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let text = reqwest::Response::from(res).text().await.expect("text");
+    assert_eq!(text, "Hello");
+}
+
+#[tokio::test]
+async fn default_user_agent_if_unset() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let ua = match req.uri().path() {
+            "/default" => concat!("reqwest/", env!("CARGO_PKG_VERSION")),
+            "/explicit" => "explicit",
+            _ => unreachable!(),
+        };
+        assert_eq!(req.headers()["user-agent"], ua);
+        http::Response::default()
+    });
+
+    let client = Client::builder()
+        .default_user_agent_if_unset()
+        .build()
+        .expect("client builder");
+
+    let res = client
+        .get(&format!("http://{}/default", server.addr()))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let res = client
+        .get(&format!("http://{}/explicit", server.addr()))
+        .header("user-agent", "explicit")
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let client = Client::builder()
+        .user_agent("explicit")
+        .default_user_agent_if_unset()
+        .build()
+        .expect("client builder");
+
+    let res = client
+        .get(&format!("http://{}/explicit", server.addr()))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn send_and_discard() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .status(202)
+            .body("discarded".into())
+            .unwrap()
+    });
+
+    let client = Client::new();
+
+    let status = client
+        .get(&format!("http://{}/discard", server.addr()))
+        .send_and_discard()
+        .await
+        .expect("send_and_discard");
+    assert_eq!(status, reqwest::StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn response_extensions() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let mut res = Client::new()
+        .get(&format!("http://{}/extensions", server.addr()))
+        .send()
+        .await
+        .expect("request");
+
+    let info = res
+        .extensions()
+        .get::<hyper::client::connect::HttpInfo>()
+        .expect("HttpInfo");
+    assert_eq!(info.remote_addr(), server.addr());
+
+    res.extensions_mut().insert(42u32);
+    assert_eq!(res.extensions().get::<u32>(), Some(&42));
+}
+
+#[cfg(feature = "rustls-tls")]
+#[tokio::test]
+async fn response_extensions_tls_info() {
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    let _ = env_logger::try_init();
+
+    let cert = include_bytes!("support/server.cert").to_vec();
+    let key = include_bytes!("support/server.key").to_vec();
+    let mut config = rustls::ServerConfig::new(rustls::NoClientAuth::new());
+    config
+        .set_single_cert(
+            vec![rustls::Certificate(cert.clone())],
+            rustls::PrivateKey(key),
+        )
+        .unwrap();
+    config.set_protocols(&["http/1.1".into()]);
+    let config = Arc::new(config);
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut tcp, _) = listener.accept().unwrap();
+        let mut session = rustls::ServerSession::new(&config);
+        let mut tls = rustls::Stream::new(&mut session, &mut tcp);
+
+        let mut req = Vec::new();
+        let mut buf = [0; 1024];
+        while !req.ends_with(b"\r\n\r\n") {
+            let n = tls.read(&mut buf).unwrap();
+            req.extend_from_slice(&buf[..n]);
+        }
+        tls.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+            .unwrap();
+        tls.flush().unwrap();
+    });
+
+    let res = Client::builder()
+        .use_rustls_tls()
+        .danger_accept_invalid_certs(true)
+        .resolve("tls.test", addr.ip())
+        .build()
+        .unwrap()
+        .get(&format!("https://tls.test:{}/tls", addr.port()))
+        .send()
+        .await
+        .expect("request");
+    server.join().unwrap();
+
+    let info = res
+        .extensions()
+        .get::<hyper::client::connect::HttpInfo>()
+        .expect("HttpInfo");
+    assert_eq!(info.remote_addr(), addr);
+
+    let info = res.extensions().get::<reqwest::TlsInfo>().expect("TlsInfo");
+    assert_eq!(info.peer_certificate(), Some(&cert[..]));
+    assert_eq!(info.alpn_protocol(), Some(&b"http/1.1"[..]));
+}
+
+#[tokio::test]
+async fn response_read_to_end() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let client = Client::new();
+    let mut buf = b"prefix:".to_vec();
+
+    let n = client
+        .get(&format!("http://{}/read-to-end", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get")
+        .read_to_end(&mut buf)
+        .await
+        .expect("read_to_end");
+    assert_eq!(n, 5);
+    assert_eq!(buf, b"prefix:Hello");
+}
+
+#[tokio::test]
+async fn response_text() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let client = Client::new();
+
+    let res = client
+        .get(&format!("http://{}/text", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get");
+    assert_eq!(res.content_length(), Some(5));
+    let text = res.text().await.expect("Failed to get text");
+    assert_eq!("Hello", text);
+}
+
+#[tokio::test]
+async fn response_text_charset_detect() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        match req.uri().path() {
+            "/declared" => http::Response::builder()
+                .header("content-type", "text/plain; charset=gbk")
+                .body(b"\xc4\xe3\xba\xc3"[..].into())
+                .unwrap(),
+            "/bom" => http::Response::builder()
+                .header("content-type", "text/plain")
+                .body(b"\xff\xfeh\x00i\x00"[..].into())
+                .unwrap(),
+            _ => http::Response::new("Hello".into()),
+        }
+    });
+
+    let client = Client::new();
+
+    for &(path, text, encoding) in &[
+        ("/declared", "你好", "GBK"),
+        ("/bom", "hi", "UTF-16LE"),
+        ("/plain", "Hello", "UTF-8"),
+    ] {
+        let res = client
+            .get(&format!("http://{}{}", server.addr(), path))
+            .send()
+            .await
+            .expect("Failed to get");
+        let detected = res.text_charset_detect().await.expect("Failed to get text");
+        assert_eq!(detected, (text.to_string(), encoding));
+    }
+}
+
+#[tokio::test]
+async fn response_bytes() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+
+    let client = Client::new();
+
+    let res = client
+        .get(&format!("http://{}/bytes", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get");
+    assert_eq!(res.content_length(), Some(5));
+    let bytes = res.bytes().await.expect("res.bytes()");
+    assert_eq!("Hello", bytes);
+}
+
+#[tokio::test]
+async fn response_bytes_chunked() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let chunks = vec![Ok::<_, std::convert::Infallible>("Hello"), Ok(" "), Ok("World")];
+        let mut res = http::Response::builder();
+        if req.uri() == "/known" {
+            res = res.header("content-length", "11");
+        }
+        res.body(hyper::Body::wrap_stream(futures_util::stream::iter(chunks)))
+            .unwrap()
+    });
+
+    let client = Client::new();
+
+    for &(path, len) in &[("/known", Some(11)), ("/unknown", None)] {
+        let res = client
+            .get(&format!("http://{}{}", server.addr(), path))
+            .send()
+            .await
+            .expect("Failed to get");
+        assert_eq!(res.content_length(), len);
+        let bytes = res.bytes().await.expect("res.bytes()");
+        assert_eq!("Hello World", bytes);
+    }
+}
+
+#[tokio::test]
+async fn response_bytes_dropped_mid_body() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        if req.uri() == "/stall" {
+            // Send the first chunk, then never finish the body.
+            let first = futures_util::stream::once(async {
+                Ok::<_, std::convert::Infallible>("Hello")
+            });
+            let body = first.chain(futures_util::stream::pending());
+            http::Response::builder()
+                .header("content-length", "11")
+                .body(hyper::Body::wrap_stream(body))
+                .unwrap()
+        } else {
+            http::Response::new("Hello World".into())
+        }
+    });
+
+    let client = Client::new();
+
+    let res = client
+        .get(&format!("http://{}/stall", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get");
+    let read = tokio::time::timeout(std::time::Duration::from_millis(100), res.bytes()).await;
+    assert!(read.is_err(), "body should not have completed");
+
+    for _ in 0..2 {
+        let bytes = client
+            .get(&format!("http://{}/done", server.addr()))
+            .send()
+            .await
+            .expect("Failed to get")
+            .bytes()
+            .await
+            .expect("res.bytes()");
+        assert_eq!("Hello World", bytes);
+    }
+}
+
+/// Serves "Hello World" by first cutting the connection off after "Hello",
+/// then answering the next connection with `second`, which is passed the
+/// request head.
+fn cut_off_server(second: fn(&str) -> Vec<u8>) -> std::net::SocketAddr {
+    use std::io::{Read, Write};
+
+    fn read_head(sock: &mut std::net::TcpStream) -> String {
+        let mut head = Vec::new();
+        let mut byte = [0; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            sock.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+        String::from_utf8(head).unwrap().to_ascii_lowercase()
+    }
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        read_head(&mut sock);
+        sock.write_all(
+            b"HTTP/1.1 200 OK\r\n\
+              content-length: 11\r\n\
+              accept-ranges: bytes\r\n\
+              etag: \"v1\"\r\n\
+              \r\n\
+              Hello",
+        )
+        .unwrap();
+        drop(sock);
+
+        let (mut sock, _) = listener.accept().unwrap();
+        let head = read_head(&mut sock);
+        sock.write_all(&second(&head)).unwrap();
+    });
+    addr
+}
+
+#[tokio::test]
+async fn response_resume_download() {
+    let _ = env_logger::try_init();
+
+    let addr = cut_off_server(|head| {
+        assert!(head.contains("range: bytes=5-\r\n"), "{}", head);
+        assert!(head.contains("if-range: \"v1\"\r\n"), "{}", head);
+        b"HTTP/1.1 206 Partial Content\r\n\
+          content-length: 6\r\n\
+          content-range: bytes 5-10/11\r\n\
+          \r\n \
+          World"
+            .to_vec()
+    });
+
+    let client = Client::builder().resume_downloads(true).build().unwrap();
+    let res = client
+        .get(&format!("http://{}/resume", addr))
+        .send()
+        .await
+        .expect("Failed to get");
+    assert_eq!(res.content_length(), Some(11));
+    let bytes = res.bytes().await.expect("res.bytes()");
+    assert_eq!("Hello World", bytes);
+}
+
+#[tokio::test]
+async fn response_resume_download_not_honored() {
+    let _ = env_logger::try_init();
+
+    let addr = cut_off_server(|_head| {
+        b"HTTP/1.1 200 OK\r\n\
+          content-length: 11\r\n\
+          \r\n\
+          Hello World"
+            .to_vec()
+    });
+
+    let client = Client::builder().resume_downloads(true).build().unwrap();
+    let err = client
+        .get(&format!("http://{}/resume", addr))
+        .send()
+        .await
+        .expect("Failed to get")
+        .bytes()
+        .await
+        .unwrap_err();
+    assert!(err.is_body());
+}
+
+#[tokio::test]
+async fn response_bytes_with_progress() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        let chunks = vec![Ok::<_, std::convert::Infallible>("Hello"), Ok(" "), Ok("World")];
+        http::Response::builder()
+            .header("content-length", "11")
+            .body(hyper::Body::wrap_stream(futures_util::stream::iter(chunks)))
+            .unwrap()
+    });
+
+    let client = Client::new();
+
+    let mut progress = Vec::new();
+    let bytes = client
+        .get(&format!("http://{}/progress", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get")
+        .bytes_with_progress(|received, total| progress.push((received, total)))
+        .await
+        .expect("res.bytes_with_progress()");
+    assert_eq!("Hello World", bytes);
+    assert_eq!(progress.last(), Some(&(11, Some(11))));
+    assert!(progress.windows(2).all(|w| w[0].0 < w[1].0));
+}
+
+#[tokio::test]
+async fn response_body_too_large() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("Hello World".into()) });
+
+    let client = Client::builder().max_response_size(5).build().unwrap();
+
+    let err = client
+        .get(&format!("http://{}/too-large", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get")
+        .bytes()
+        .await
+        .unwrap_err();
+    assert!(err.is_body());
+    assert!(err.is_body_too_large());
+    assert_eq!(err.body_limit(), Some(5));
+
+    let client = Client::builder().max_response_size(11).build().unwrap();
+
+    let bytes = client
+        .get(&format!("http://{}/fits", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get")
+        .bytes()
+        .await
+        .expect("res.bytes()");
+    assert_eq!("Hello World", bytes);
+}
+
+#[tokio::test]
+async fn response_bytes_limited() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("Hello World".into()) });
+
+    let client = Client::new();
+
+    let err = client
+        .get(&format!("http://{}/too-large", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get")
+        .bytes_limited(5)
+        .await
+        .unwrap_err();
+    assert!(err.is_body_too_large());
+    assert_eq!(err.body_limit(), Some(5));
+
+    let bytes = client
+        .get(&format!("http://{}/fits", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get")
+        .bytes_limited(11)
+        .await
+        .expect("res.bytes_limited()");
+    assert_eq!("Hello World", bytes);
+}
+
+#[tokio::test]
+async fn response_content_length_head() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.method(), "HEAD");
+        http::Response::builder()
+            .header("content-length", "42")
+            .body(Default::default())
+            .unwrap()
+    });
+
+    let client = Client::new();
+
+    let res = client
+        .head(&format!("http://{}/head", server.addr()))
+        .send()
+        .await
+        .expect("Failed to head");
+    assert_eq!(res.content_length(), Some(42));
+}
+
+#[tokio::test]
+#[cfg(feature = "json")]
+async fn response_json() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("\"Hello\"".into()) });
+
+    let client = Client::new();
+
+    let res = client
+        .get(&format!("http://{}/json", server.addr()))
+        .send()
+        .await
+        .expect("Failed to get");
+    let text = res.json::<String>().await.expect("Failed to get json");
+    assert_eq!("Hello", text);
+}
+
+#[tokio::test]
+#[cfg(feature = "json")]
+async fn response_json_xssi() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        http::Response::new(")]}'\n[\"Hello\"]".into())
+    });
+
+    let client = Client::new();
+    let url = format!("http://{}/json", server.addr());
+
+    let res = client.get(&url).send().await.expect("Failed to get");
+    let list = res
+        .json_xssi::<Vec<String>>(")]}'\n")
+        .await
+        .expect("Failed to get json");
+    assert_eq!(list, ["Hello"]);
+
+    let res = client.get(&url).send().await.expect("Failed to get");
+    let err = res
+        .json_xssi::<Vec<String>>("while(1);")
+        .await
+        .unwrap_err();
+    assert!(err.is_decode());
+}
+
+#[tokio::test]
+#[cfg(feature = "json")]
+async fn response_json_strict() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let content_type = match req.uri().path() {
+            "/json" => "application/json",
+            "/problem" => "application/problem+json; charset=utf-8",
+            _ => "text/html",
+        };
+        http::Response::builder()
+            .header("content-type", content_type)
+            .body("[\"Hello\"]".into())
+            .unwrap()
+    });
+
+    let client = Client::new();
+    for path in &["json", "problem"] {
+        let url = format!("http://{}/{}", server.addr(), path);
+        let res = client.get(&url).send().await.expect("Failed to get");
+        let list = res.json_strict::<Vec<String>>().await.expect("Failed to get json");
+        assert_eq!(list, ["Hello"]);
+    }
+
+    let url = format!("http://{}/html", server.addr());
+    let res = client.get(&url).send().await.expect("Failed to get");
+    let err = res.json_strict::<Vec<String>>().await.unwrap_err();
+    assert!(err.is_decode());
+    assert!(err.to_string().contains("expected JSON, got text/html"));
+}
+
+#[tokio::test]
+async fn body_pipe_response() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |mut req| async move {
+        if req.uri() == "/get" {
+            http::Response::new("pipe me".into())
+        } else {
+            assert_eq!(req.uri(), "/pipe");
+            assert_eq!(req.headers()["transfer-encoding"], "chunked");
+
+            let mut full: Vec<u8> = Vec::new();
+            while let Some(item) = req.body_mut().next().await {
+                full.extend(&*item.unwrap());
+            }
+
+            assert_eq!(full, b"pipe me");
+
+            http::Response::default()
+        }
+    });
+
+    let client = Client::new();
+
+    let res1 = client
+        .get(&format!("http://{}/get", server.addr()))
+        .send()
+        .await
+        .expect("get1");
+
+    assert_eq!(res1.status(), reqwest::StatusCode::OK);
+    assert_eq!(res1.content_length(), Some(7));
+
+    // and now ensure we can "pipe" the response to another request
+    let res2 = client
+        .post(&format!("http://{}/pipe", server.addr()))
+        .body(res1)
+        .send()
+        .await
+        .expect("res2");
+
+    assert_eq!(res2.status(), reqwest::StatusCode::OK);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn body_stream() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["transfer-encoding"], "chunked");
+        assert_eq!(req.headers().get("content-length"), None);
+
+        let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&*data, b"hello world");
+
+        http::Response::default()
+    });
+
+    let chunks = vec![Ok::<_, std::io::Error>("hello"), Ok(" "), Ok("world")];
+    let builder = Client::new()
+        .post(&format!("http://{}/stream", server.addr()))
+        .stream_body(futures_util::stream::iter(chunks));
+    assert!(builder.try_clone().is_none());
+
+    let res = builder.send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn body_empty() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-length"], "0");
+        http::Response::default()
+    });
+
+    let body = reqwest::Body::empty();
+    assert_eq!(body.is_empty(), Some(true));
+
+    let res = Client::new()
+        .post(&format!("http://{}/empty", server.addr()))
+        .body(body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn body_content_type() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let expected = match req.uri().path() {
+            "/hinted" => "application/json",
+            "/explicit" => "text/plain",
+            _ => unreachable!(),
+        };
+        assert_eq!(req.headers()["content-type"], expected);
+        assert_eq!(req.headers().get_all("content-type").iter().count(), 1);
+        http::Response::default()
+    });
+
+    let client = Client::new();
+    let body = || {
+        reqwest::Body::from("{}")
+            .with_content_type(reqwest::header::HeaderValue::from_static("application/json"))
+    };
+
+    let res = client
+        .post(&format!("http://{}/hinted", server.addr()))
+        .body(body())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    let res = client
+        .post(&format!("http://{}/explicit", server.addr()))
+        .header("content-type", "text/plain")
+        .body(body())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn body_inspect() {
+    use std::sync::{Arc, Mutex};
+
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.headers()["content-length"], "11");
+
+        let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&*data, b"hello world");
+
         http::Response::default()
     });
 
-    let url = format!("http://{}/ua", server.addr());
-    let res = reqwest::Client::builder()
-        .user_agent("reqwest-test-agent")
-        .build()
-        .expect("client builder")
-        .get(&url)
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let inspected = seen.clone();
+    let body = reqwest::Body::from("hello world")
+        .inspect(move |chunk| inspected.lock().unwrap().extend_from_slice(chunk));
+
+    let res = Client::new()
+        .post(&format!("http://{}/inspect", server.addr()))
+        .body(body)
         .send()
         .await
-        .expect("request");
+        .unwrap();
 
     assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(&*seen.lock().unwrap(), b"hello world");
 }
 
 #[tokio::test]
-async fn response_text() {
+async fn body_inspect_cloneable() {
+    use std::sync::{Arc, Mutex};
+
     let _ = env_logger::try_init();
 
-    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+    let server = server::http(move |req| async move {
+        if req.uri() == "/redirect" {
+            return http::Response::builder()
+                .status(307)
+                .header("location", "/inspect")
+                .body(Default::default())
+                .unwrap();
+        }
+
+        let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+        assert_eq!(&*data, b"hello world");
+        http::Response::default()
+    });
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let inspected = seen.clone();
+    let body = reqwest::Body::from("hello world")
+        .inspect_cloneable(move |chunk| inspected.lock().unwrap().extend_from_slice(chunk));
+
+    let builder = Client::new()
+        .post(&format!("http://{}/redirect", server.addr()))
+        .body(body);
+    assert!(builder.try_clone().is_some());
+
+    let res = builder.send().await.unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(res.url().path(), "/inspect");
+    assert_eq!(&*seen.lock().unwrap(), b"hello worldhello world");
+}
+
+#[tokio::test]
+async fn preconnect() {
+    use std::io::{Read, Write};
+
+    let _ = env_logger::try_init();
+
+    // Only a single connection is ever accepted, so the request has to
+    // reuse the one opened by `preconnect`.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let server = std::thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        drop(listener);
+        tx.send(()).unwrap();
+
+        let mut buf = [0; 1024];
+        let n = sock.read(&mut buf).unwrap();
+        assert!(buf[..n].starts_with(b"GET /warm HTTP/1.1\r\n"));
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nHello")
+            .unwrap();
+    });
 
     let client = Client::new();
+    client
+        .preconnect(&format!("http://{}/", addr))
+        .await
+        .expect("preconnect");
+    rx.recv_timeout(std::time::Duration::from_secs(5))
+        .expect("connection accepted before any request");
 
-    let res = client
-        .get(&format!("http://{}/text", server.addr()))
+    let text = client
+        .get(&format!("http://{}/warm", addr))
         .send()
         .await
-        .expect("Failed to get");
-    assert_eq!(res.content_length(), Some(5));
-    let text = res.text().await.expect("Failed to get text");
-    assert_eq!("Hello", text);
+        .expect("Failed to get")
+        .text()
+        .await
+        .expect("res.text()");
+    assert_eq!(text, "Hello");
+
+    server.join().unwrap();
 }
 
 #[tokio::test]
-async fn response_bytes() {
+async fn preconnect_skips_unusable_connections() {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
     let _ = env_logger::try_init();
 
-    let server = server::http(move |_req| async { http::Response::new("Hello".into()) });
+    // Of the three preconnected connections, the newest is closed by the
+    // server and the others are kept open but expire, so the request
+    // needs a fourth one.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (idle1, _) = listener.accept().unwrap();
+        let (idle2, _) = listener.accept().unwrap();
+        let (closed, _) = listener.accept().unwrap();
+        drop(closed);
 
-    let client = Client::new();
+        let (mut sock, _) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let n = sock.read(&mut buf).unwrap();
+        assert!(buf[..n].starts_with(b"GET /fresh HTTP/1.1\r\n"));
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .unwrap();
+        drop((idle1, idle2));
+    });
 
-    let res = client
-        .get(&format!("http://{}/bytes", server.addr()))
+    let client = Client::builder()
+        .pool_idle_timeout(Duration::from_millis(300))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", addr);
+    client.preconnect(&url).await.expect("preconnect");
+    client.preconnect(&url).await.expect("preconnect");
+    tokio::time::delay_for(Duration::from_millis(400)).await;
+    client.preconnect(&url).await.expect("preconnect");
+    tokio::time::delay_for(Duration::from_millis(100)).await;
+
+    let res = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.get(&format!("http://{}/fresh", addr)).send(),
+    )
+    .await
+    .expect("request should use a new connection")
+    .expect("request");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn retry_dead_pooled_connection() {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    let _ = env_logger::try_init();
+
+    // The first connection answers one request, then dies while the
+    // second one is sent on it.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut buf = [0; 1024];
+
+        let (mut sock, _) = listener.accept().unwrap();
+        assert!(sock.read(&mut buf).unwrap() > 0);
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nfirst")
+            .unwrap();
+        assert!(sock.read(&mut buf).unwrap() > 0);
+        drop(sock);
+
+        let (mut sock, _) = listener.accept().unwrap();
+        assert!(sock.read(&mut buf).unwrap() > 0);
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nretry")
+            .unwrap();
+    });
+
+    let policy = reqwest::retry::Policy::limited(1).backoff(Duration::from_millis(10));
+    let client = Client::builder().retry(policy).build().unwrap();
+    let url = format!("http://{}/", addr);
+
+    let text = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert_eq!(text, "first");
+    let text = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert_eq!(text, "retry");
+
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn response_trailers() {
+    use std::io::{Read, Write};
+
+    let _ = env_logger::try_init();
+
+    fn frame(kind: u8, flags: u8, stream: u8, payload: &[u8]) -> Vec<u8> {
+        let len = payload.len();
+        let mut frame = vec![0, (len >> 8) as u8, len as u8, kind, flags, 0, 0, 0, stream];
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    // hyper's server can't send trailers, so this speaks just enough
+    // HTTP/2 to answer one request with them.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let server = std::thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        let mut received = Vec::new();
+        let mut buf = [0; 1024];
+
+        // Skip the preface, then wait for the request's HEADERS frame.
+        let mut pos = 24;
+        'request: loop {
+            let n = sock.read(&mut buf).unwrap();
+            assert!(n > 0);
+            received.extend_from_slice(&buf[..n]);
+            while received.len() >= pos + 9 {
+                if received[pos + 3] == 1 {
+                    break 'request;
+                }
+                let len = (received[pos] as usize) << 16
+                    | (received[pos + 1] as usize) << 8
+                    | received[pos + 2] as usize;
+                pos += 9 + len;
+            }
+        }
+
+        let mut trailers = vec![0, 11];
+        trailers.extend_from_slice(b"grpc-status");
+        trailers.extend_from_slice(&[1, b'0']);
+
+        let mut response = frame(4, 0, 0, &[]);
+        response.extend(frame(4, 1, 0, &[]));
+        // HEADERS with `:status: 200`, then DATA, then trailers ending it.
+        response.extend(frame(1, 4, 1, &[0x88]));
+        response.extend(frame(0, 0, 1, b"hello"));
+        response.extend(frame(1, 5, 1, &trailers));
+        sock.write_all(&response).unwrap();
+        let _ = done_rx.recv();
+    });
+
+    let client = Client::builder().http2_prior_knowledge().build().unwrap();
+    let mut res = client
+        .get(&format!("http://{}/", addr))
         .send()
         .await
-        .expect("Failed to get");
-    assert_eq!(res.content_length(), Some(5));
-    let bytes = res.bytes().await.expect("res.bytes()");
-    assert_eq!("Hello", bytes);
+        .unwrap();
+    assert_eq!(res.version(), http::Version::HTTP_2);
+    assert_eq!(res.chunk().await.unwrap().unwrap(), "hello");
+
+    let trailers = res.trailers().await.unwrap().expect("trailers");
+    assert_eq!(trailers["grpc-status"], "0");
+
+    done_tx.send(()).unwrap();
+    server.join().unwrap();
+
+    // HTTP/1 responses never have trailers.
+    let server = server::http(move |_req| async { http::Response::new("hello".into()) });
+    let mut res = reqwest::get(&format!("http://{}/", server.addr()))
+        .await
+        .unwrap();
+    assert_eq!(res.trailers().await.unwrap(), None);
 }
 
+// Only Linux routes all of 127.0.0.0/8 to the loopback interface without
+// configuration, and binding 127.0.0.2 shows the address is used.
+#[cfg(target_os = "linux")]
 #[tokio::test]
-#[cfg(feature = "json")]
-async fn response_json() {
-    let _ = env_logger::try_init();
+async fn local_addresses_by_family() {
+    use std::io::{Read, Write};
 
-    let server = server::http(move |_req| async { http::Response::new("\"Hello\"".into()) });
+    let _ = env_logger::try_init();
 
-    let client = Client::new();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut sock, peer) = listener.accept().unwrap();
+        assert_eq!(peer.ip(), std::net::Ipv4Addr::new(127, 0, 0, 2));
+        assert!(sock.read(&mut [0; 1024]).unwrap() > 0);
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+            .unwrap();
+    });
 
+    let client = Client::builder()
+        .local_addresses(
+            Some(std::net::Ipv4Addr::new(127, 0, 0, 2)),
+            Some(std::net::Ipv6Addr::LOCALHOST),
+        )
+        .build()
+        .unwrap();
     let res = client
-        .get(&format!("http://{}/json", server.addr()))
+        .get(&format!("http://{}/", addr))
         .send()
         .await
-        .expect("Failed to get");
-    let text = res.json::<String>().await.expect("Failed to get json");
-    assert_eq!("Hello", text);
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    server.join().unwrap();
 }
 
 #[tokio::test]
-async fn body_pipe_response() {
+async fn head_response_is_empty() {
+    use std::io::{Read, Write};
+
     let _ = env_logger::try_init();
 
-    let server = server::http(move |mut req| async move {
-        if req.uri() == "/get" {
-            http::Response::new("pipe me".into())
-        } else {
-            assert_eq!(req.uri(), "/pipe");
-            assert_eq!(req.headers()["transfer-encoding"], "chunked");
+    // The `Content-Length` of the `HEAD` response can't be satisfied, and
+    // both requests must share the one connection.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let mut buf = [0; 1024];
 
-            let mut full: Vec<u8> = Vec::new();
-            while let Some(item) = req.body_mut().next().await {
-                full.extend(&*item.unwrap());
-            }
+        let (mut sock, _) = listener.accept().unwrap();
+        assert!(sock.read(&mut buf).unwrap() > 0);
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\n")
+            .unwrap();
+        assert!(sock.read(&mut buf).unwrap() > 0);
+        sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nhello")
+            .unwrap();
+    });
 
-            assert_eq!(full, b"pipe me");
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", addr);
 
-            http::Response::default()
-        }
+    let res = client.head(&url).send().await.unwrap();
+    assert_eq!(res.content_length(), Some(100));
+    assert_eq!(res.bytes().await.unwrap(), "");
+
+    let text = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert_eq!(text, "hello");
+
+    server.join().unwrap();
+}
+
+#[tokio::test]
+async fn preserve_header_order() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        let names = req.headers().keys().take(3).collect::<Vec<_>>();
+        assert_eq!(names, ["x-first", "x-second", "x-third"]);
+        assert_eq!(req.headers()["x-first"], "default");
+        http::Response::default()
     });
 
-    let client = Client::new();
+    let mut merged = reqwest::header::HeaderMap::new();
+    merged.insert("x-first", "default".parse().unwrap());
+    let client = Client::builder()
+        .default_headers_merge(merged)
+        .preserve_header_order(true)
+        .build()
+        .unwrap();
 
-    let res1 = client
-        .get(&format!("http://{}/get", server.addr()))
+    let res = client
+        .get(&format!("http://{}/", server.addr()))
+        .header("x-first", "own")
+        .header("x-second", "2")
+        .header("x-third", "3")
         .send()
         .await
-        .expect("get1");
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
 
-    assert_eq!(res1.status(), reqwest::StatusCode::OK);
-    assert_eq!(res1.content_length(), Some(7));
+#[tokio::test]
+async fn resolve_overrides() {
+    let _ = env_logger::try_init();
 
-    // and now ensure we can "pipe" the response to another request
-    let res2 = client
-        .post(&format!("http://{}/pipe", server.addr()))
-        .body(res1)
+    let server = server::http(move |_req| async { http::Response::default() });
+    let port = server.addr().port();
+
+    let hosts = std::env::temp_dir().join(format!("reqwest-test-hosts-{}", port));
+    std::fs::write(
+        &hosts,
+        "# test hosts\n127.0.0.1 hosts.test override.test # trailing comment\n127.0.0.2 hosts.test\n",
+    )
+    .unwrap();
+
+    let client = Client::builder()
+        .no_proxy()
+        .resolve_from_hosts_file(hosts.clone())
+        .build()
+        .unwrap();
+    let res = client
+        .get(&format!("http://hosts.test:{}/", port))
         .send()
         .await
-        .expect("res2");
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
 
-    assert_eq!(res2.status(), reqwest::StatusCode::OK);
+    // `resolve` takes precedence over the hosts file
+    std::fs::write(&hosts, "127.0.0.2 override.test\n").unwrap();
+    let client = Client::builder()
+        .no_proxy()
+        .resolve_from_hosts_file(hosts.clone())
+        .resolve("Override.Test", std::net::IpAddr::from([127, 0, 0, 1]))
+        .build()
+        .unwrap();
+    let res = client
+        .get(&format!("http://override.test:{}/", port))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    std::fs::write(&hosts, "not-an-ip override.test\n").unwrap();
+    let err = Client::builder()
+        .resolve_from_hosts_file(hosts.clone())
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+
+    std::fs::remove_file(&hosts).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn tcp_user_timeout() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let client = Client::builder()
+        .tcp_user_timeout(std::time::Duration::from_secs(30))
+        .build()
+        .unwrap();
+
+    let res = client
+        .get(&format!("http://{}/user-timeout", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
+#[cfg(not(target_os = "linux"))]
+#[test]
+fn tcp_user_timeout_unsupported() {
+    let err = Client::builder()
+        .tcp_user_timeout(std::time::Duration::from_secs(30))
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
 }
 
 #[cfg(any(feature = "native-tls", feature = "rustls-tls",))]
@@ -198,3 +1521,220 @@ fn use_preconfigured_rustls_default() {
         .build()
         .expect("preconfigured rustls tls");
 }
+
+#[cfg(feature = "rustls-tls")]
+#[test]
+fn tls_session_cache() {
+    reqwest::Client::builder()
+        .use_rustls_tls()
+        .tls_session_cache(64)
+        .build()
+        .expect("client with session cache");
+
+    reqwest::Client::builder()
+        .use_rustls_tls()
+        .tls_session_cache(0)
+        .build()
+        .expect("client without session cache");
+}
+
+#[cfg(feature = "rustls-tls")]
+#[test]
+fn danger_accept_invalid_hostnames_rustls() {
+    reqwest::Client::builder()
+        .use_rustls_tls()
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .expect("rustls client accepting invalid hostnames");
+
+    // Without native-tls, the default backend can't skip hostnames.
+    #[cfg(all(feature = "default-tls", not(feature = "native-tls")))]
+    {
+        let err = reqwest::Client::builder()
+            .danger_accept_invalid_hostnames(true)
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+}
+
+#[cfg(feature = "rustls-tls")]
+#[tokio::test]
+async fn tls_early_data() {
+    let server = server::http(move |req| async move {
+        http::Response::new(req.method().as_str().to_owned().into())
+    });
+
+    let client = reqwest::Client::builder()
+        .use_rustls_tls()
+        .tls_early_data(true)
+        .build()
+        .expect("client with early data");
+
+    // Plain `http` requests are sent as usual, whatever their method.
+    let url = format!("http://{}/early", server.addr());
+    let text = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert_eq!(text, "GET");
+    let text = client.post(&url).send().await.unwrap().text().await.unwrap();
+    assert_eq!(text, "POST");
+
+    let err = reqwest::Client::builder()
+        .use_rustls_tls()
+        .tls_early_data(true)
+        .http2_prior_knowledge()
+        .build()
+        .unwrap_err();
+    assert!(err.is_builder());
+
+    // The native backend can't send early data.
+    #[cfg(feature = "default-tls")]
+    {
+        let err = reqwest::Client::builder()
+            .tls_early_data(true)
+            .build()
+            .unwrap_err();
+        assert!(err.is_builder());
+    }
+}
+
+#[tokio::test]
+async fn retry_connection_reset() {
+    use std::io::{Read, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let _ = env_logger::try_init();
+
+    // The first connection is closed without an answer. The second one
+    // answers `503` and then, once retried on it, `200`.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    {
+        let accepted = accepted.clone();
+        std::thread::spawn(move || {
+            for sock in listener.incoming() {
+                let mut sock = sock.unwrap();
+                let conn = accepted.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut buf = [0; 1024];
+                    if sock.read(&mut buf).unwrap() == 0 || conn == 0 {
+                        return;
+                    }
+                    // The body comes later, so it's still unread when the
+                    // response is discarded.
+                    let head = b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 4\r\n\r\n";
+                    sock.write_all(head).unwrap();
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    sock.write_all(b"busy").unwrap();
+                    if sock.read(&mut buf).unwrap() == 0 {
+                        return;
+                    }
+                    let response = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                    sock.write_all(response).unwrap();
+                    let _ = sock.read(&mut buf);
+                });
+            }
+        });
+    }
+
+    let status = reqwest::StatusCode::SERVICE_UNAVAILABLE;
+    let policy = reqwest::retry::Policy::limited(2).retry_status(status);
+    let client = Client::builder().retry(policy).build().unwrap();
+    let url = format!("http://{}/reset", addr);
+
+    let text = client.get(&url).send().await.unwrap().text().await.unwrap();
+    assert_eq!(text, "ok");
+    // The body of the `503` was read, so its connection was reused.
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn retry_status() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let _ = env_logger::try_init();
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let server = {
+        let attempts = attempts.clone();
+        server::http(move |_req| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let status = if attempt == 0 { 503 } else { 200 };
+                http::Response::builder()
+                    .status(status)
+                    .body(Default::default())
+                    .unwrap()
+            }
+        })
+    };
+
+    let policy = reqwest::retry::Policy::limited(2)
+        .backoff(Duration::from_millis(10))
+        .retry_status(reqwest::StatusCode::SERVICE_UNAVAILABLE);
+    let client = Client::builder().retry(policy).build().unwrap();
+    let url = format!("http://{}/retry", server.addr());
+
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+    // `POST` isn't idempotent, so it isn't retried.
+    attempts.store(0, Ordering::SeqCst);
+    let res = client.post(&url).body("hello").send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn connection_reuse_max_requests() {
+    use std::io::{Read, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let _ = env_logger::try_init();
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let accepted = Arc::new(AtomicUsize::new(0));
+    {
+        let accepted = accepted.clone();
+        std::thread::spawn(move || {
+            for sock in listener.incoming() {
+                let mut sock = sock.unwrap();
+                accepted.fetch_add(1, Ordering::SeqCst);
+                std::thread::spawn(move || {
+                    let mut received = Vec::new();
+                    let mut buf = [0; 1024];
+                    loop {
+                        let n = match sock.read(&mut buf) {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => n,
+                        };
+                        received.extend_from_slice(&buf[..n]);
+                        while let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                            received.drain(..end + 4);
+                            let response = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                            sock.write_all(response).unwrap();
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    let client = Client::builder()
+        .connection_reuse_max_requests(2)
+        .build()
+        .unwrap();
+    let url = format!("http://{}/", addr);
+    for _ in 0..4 {
+        let text = client.get(&url).send().await.unwrap().text().await.unwrap();
+        assert_eq!(text, "ok");
+    }
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+}
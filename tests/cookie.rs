@@ -95,6 +95,34 @@ async fn cookie_store_simple() {
     client.get(&url).send().await.unwrap();
 }
 
+#[tokio::test]
+async fn cookie_store_shared_between_clients() {
+    let server = server::http(move |req| async move {
+        if req.uri() == "/2" {
+            assert_eq!(req.headers()["cookie"], "key=val");
+        }
+        http::Response::builder()
+            .header("Set-Cookie", "key=val; HttpOnly")
+            .body(Default::default())
+            .unwrap()
+    });
+
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .build()
+        .unwrap();
+    let other = reqwest::Client::builder()
+        .cookie_store_handle(client.cookie_store_handle().unwrap())
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/", server.addr());
+    client.get(&url).send().await.unwrap();
+
+    let url = format!("http://{}/2", server.addr());
+    other.get(&url).send().await.unwrap();
+}
+
 #[tokio::test]
 async fn cookie_store_overwrite_existing() {
     let server = server::http(move |req| async move {
@@ -201,3 +229,42 @@ async fn cookie_store_path() {
     let url = format!("http://{}/subpath", server.addr());
     client.get(&url).send().await.unwrap();
 }
+
+#[tokio::test]
+async fn cookie_store_capacity_evicts_least_recently_used() {
+    let server = server::http(move |req| async move {
+        let cookie = req
+            .headers()
+            .get("cookie")
+            .map(|value| value.to_str().unwrap().to_owned());
+        let res = http::Response::builder();
+        let res = match req.uri().path() {
+            "/a" => res.header("Set-Cookie", "a=1; Path=/a"),
+            "/b" => res.header("Set-Cookie", "b=1; Path=/b"),
+            "/c" => res.header("Set-Cookie", "c=1; Path=/c"),
+            _ => res,
+        };
+        res.body(cookie.unwrap_or_default().into()).unwrap()
+    });
+
+    let client = reqwest::Client::builder()
+        .cookie_store(true)
+        .cookie_store_capacity(2)
+        .build()
+        .unwrap();
+    let get = |path: &'static str| {
+        let url = format!("http://{}{}", server.addr(), path);
+        let req = client.get(&url);
+        async move { req.send().await.unwrap().text().await.unwrap() }
+    };
+
+    get("/a").await;
+    get("/b").await;
+    // sending `a` makes `b` the least recently used cookie
+    assert_eq!(get("/a/x").await, "a=1");
+    get("/c").await;
+
+    assert_eq!(get("/a/x").await, "a=1");
+    assert_eq!(get("/b/x").await, "");
+    assert_eq!(get("/c/x").await, "c=1");
+}
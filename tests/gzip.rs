@@ -86,6 +86,105 @@ async fn test_accept_encoding_header_is_not_changed_if_set() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[tokio::test]
+async fn test_range_request_is_not_decompressed() {
+    let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+    encoder.write_all(b"range of gzip").unwrap();
+    let gzipped_content = encoder.finish().into_result().unwrap();
+    let partial = gzipped_content[..10].to_vec();
+    let expected = partial.clone();
+
+    let server = server::http(move |req| {
+        assert_eq!(req.headers()["range"], "bytes=0-9");
+        assert_eq!(req.headers().get("accept-encoding"), None);
+
+        let partial = partial.clone();
+        async move {
+            http::Response::builder()
+                .status(206)
+                .header("content-encoding", "gzip")
+                .header("content-length", partial.len())
+                .body(partial.into())
+                .unwrap()
+        }
+    });
+
+    let client = reqwest::Client::new();
+
+    let res = client
+        .get(&format!("http://{}/range", server.addr()))
+        .header(reqwest::header::RANGE, "bytes=0-9")
+        .send()
+        .await
+        .expect("response");
+
+    assert_eq!(res.status(), reqwest::StatusCode::PARTIAL_CONTENT);
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+
+    let body = res.bytes().await.expect("bytes");
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn test_no_auto_decompress() {
+    let mut encoder = libflate::gzip::Encoder::new(Vec::new()).unwrap();
+    encoder.write_all(b"raw gzip").unwrap();
+    let gzipped_content = encoder.finish().into_result().unwrap();
+    let expected = gzipped_content.clone();
+
+    let server = server::http(move |req| {
+        assert!(req.headers()["accept-encoding"]
+            .to_str()
+            .unwrap()
+            .contains("gzip"));
+
+        let gzipped = gzipped_content.clone();
+        async move {
+            http::Response::builder()
+                .header("content-encoding", "gzip")
+                .header("content-length", gzipped.len())
+                .body(gzipped.into())
+                .unwrap()
+        }
+    });
+
+    let client = reqwest::Client::new();
+
+    let res = client
+        .get(&format!("http://{}/gzip", server.addr()))
+        .no_auto_decompress()
+        .send()
+        .await
+        .expect("response");
+
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(res.content_length(), Some(expected.len() as u64));
+
+    let body = res.bytes().await.expect("bytes");
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn test_stacked_unsupported_encoding() {
+    let server = server::http(move |_req| async move {
+        http::Response::builder()
+            .header("content-encoding", "gzip, unknown")
+            .body("not really encoded".into())
+            .unwrap()
+    });
+
+    let client = reqwest::Client::new();
+
+    let res = client
+        .get(&format!("http://{}/stacked", server.addr()))
+        .send()
+        .await
+        .expect("response");
+
+    let err = res.bytes().await.unwrap_err();
+    assert!(err.is_decode());
+}
+
 async fn gzip_case(response_size: usize, chunk_size: usize) {
     use futures_util::stream::StreamExt;
 
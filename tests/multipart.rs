@@ -55,6 +55,65 @@ async fn text_part() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn file_part() {
+    let _ = env_logger::try_init();
+
+    let path = std::env::temp_dir().join("reqwest-test-multipart-file_part.txt");
+    std::fs::write(&path, "file contents").unwrap();
+
+    let form = reqwest::multipart::Form::new()
+        .file("upload", &path)
+        .await
+        .unwrap();
+
+    let expected_body = format!(
+        "\
+         --{0}\r\n\
+         Content-Disposition: form-data; name=\"upload\"; \
+         filename=\"reqwest-test-multipart-file_part.txt\"\r\n\
+         Content-Type: text/plain\r\n\r\n\
+         file contents\r\n\
+         --{0}--\r\n\
+         ",
+        form.boundary()
+    );
+
+    let server = server::http(move |mut req| {
+        let expected_body = expected_body.clone();
+        async move {
+            assert_eq!(
+                req.headers()["content-length"],
+                expected_body.len().to_string()
+            );
+            assert_eq!(req.headers().get("transfer-encoding"), None);
+
+            let mut full: Vec<u8> = Vec::new();
+            while let Some(item) = req.body_mut().next().await {
+                full.extend(&*item.unwrap());
+            }
+
+            assert_eq!(full, expected_body.as_bytes());
+
+            http::Response::default()
+        }
+    });
+
+    let url = format!("http://{}/multipart/file", server.addr());
+
+    let res = reqwest::Client::new()
+        .post(&url)
+        .multipart(form)
+        .send()
+        .await
+        .unwrap();
+    // The file is read while the request is sent.
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[cfg(feature = "stream")]
 #[tokio::test]
 async fn stream_part() {
@@ -180,3 +239,58 @@ fn blocking_file_part() {
     assert_eq!(res.url().as_str(), &url);
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_streaming_part() {
+    let _ = env_logger::try_init();
+
+    let contents = "streaming content ".repeat(1024);
+    let part = reqwest::blocking::multipart::Part::reader(std::io::Cursor::new(contents.clone()));
+    let form = reqwest::blocking::multipart::Form::new().part("foo", part);
+
+    let expected_body = format!(
+        "\
+         --{0}\r\n\
+         Content-Disposition: form-data; name=\"foo\"\r\n\r\n\
+         {1}\r\n\
+         --{0}--\r\n\
+         ",
+        form.boundary(),
+        contents
+    );
+
+    let ct = format!("multipart/form-data; boundary={}", form.boundary());
+
+    let server = server::http(move |mut req| {
+        let ct = ct.clone();
+        let expected_body = expected_body.clone();
+        async move {
+            assert_eq!(req.method(), "POST");
+            assert_eq!(req.headers()["content-type"], ct);
+            // readers have an unknown size, so the body is chunked
+            assert_eq!(req.headers()["transfer-encoding"], "chunked");
+            assert_eq!(req.headers().get("content-length"), None);
+
+            let mut full: Vec<u8> = Vec::new();
+            while let Some(item) = req.body_mut().next().await {
+                full.extend(&*item.unwrap());
+            }
+
+            assert_eq!(full, expected_body.as_bytes());
+
+            http::Response::default()
+        }
+    });
+
+    let url = format!("http://{}/multipart/3", server.addr());
+
+    let res = reqwest::blocking::Client::new()
+        .post(&url)
+        .multipart(form)
+        .send()
+        .unwrap();
+
+    assert_eq!(res.url().as_str(), &url);
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
@@ -29,6 +29,52 @@ async fn http_proxy() {
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
 
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn proxy_per_scheme() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let http_server = server::http(move |req| {
+        assert_eq!(req.method(), "GET");
+        assert_eq!(req.uri(), "http://hyper.rs/prox");
+
+        async { http::Response::default() }
+    });
+
+    let tunneled = Arc::new(AtomicBool::new(false));
+    let tunneled2 = tunneled.clone();
+    let https_server = server::http(move |req| {
+        assert_eq!(req.method(), "CONNECT");
+        assert_eq!(req.uri(), "hyper.rs:443");
+        tunneled2.store(true, Ordering::SeqCst);
+
+        async {
+            http::Response::builder()
+                .status(403)
+                .body(Default::default())
+                .unwrap()
+        }
+    });
+
+    let http_proxy = format!("http://{}", http_server.addr());
+    let https_proxy = format!("http://{}", https_server.addr());
+
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::https(&https_proxy).unwrap())
+        .proxy(reqwest::Proxy::http(&http_proxy).unwrap())
+        .build()
+        .unwrap();
+
+    let res = client.get("http://hyper.rs/prox").send().await.unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+    assert!(!tunneled.load(Ordering::SeqCst));
+
+    // The tunnel is refused, but it has to be asked of the https proxy.
+    client.get("https://hyper.rs/prox").send().await.unwrap_err();
+    assert!(tunneled.load(Ordering::SeqCst));
+}
+
 #[tokio::test]
 async fn http_proxy_basic_auth() {
     let url = "http://hyper.rs/prox";
@@ -178,3 +224,47 @@ async fn http_over_http() {
     assert_eq!(res.url().as_str(), url);
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
+
+#[tokio::test]
+async fn custom_proxy_on_redirect() {
+    let server = server::http(move |req| {
+        assert_eq!(req.method(), "GET");
+        assert_eq!(
+            req.headers()["proxy-authorization"],
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+
+        async move {
+            if req.uri() == "http://hyper.rs/start" {
+                assert_eq!(req.headers()["host"], "hyper.rs");
+                http::Response::builder()
+                    .status(302)
+                    .header("location", "http://crates.io/end")
+                    .body(Default::default())
+                    .unwrap()
+            } else {
+                assert_eq!(req.uri(), "http://crates.io/end");
+                assert_eq!(req.headers()["host"], "crates.io");
+                http::Response::default()
+            }
+        }
+    });
+
+    let proxy = format!("http://Aladdin:open sesame@{}", server.addr());
+    let proxy = reqwest::Url::parse(&proxy).unwrap();
+
+    let res = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::custom(move |url| match url.host_str() {
+            Some("hyper.rs") | Some("crates.io") => Some(proxy.clone()),
+            _ => None,
+        }))
+        .build()
+        .unwrap()
+        .get("http://hyper.rs/start")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.url().as_str(), "http://crates.io/end");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
@@ -117,6 +117,41 @@ async fn test_redirect_307_and_308_tries_to_post_again() {
     }
 }
 
+#[tokio::test]
+async fn test_redirect_policy_can_keep_post_on_301_and_302() {
+    let policy = reqwest::redirect::Policy::default()
+        .keep_method_on(reqwest::StatusCode::MOVED_PERMANENTLY)
+        .keep_method_on(reqwest::StatusCode::FOUND);
+    let client = reqwest::Client::builder().redirect(policy).build().unwrap();
+    let codes = [301u16, 302];
+    for &code in codes.iter() {
+        let redirect = server::http(move |mut req| async move {
+            assert_eq!(req.method(), "POST");
+            assert_eq!(req.headers()["content-length"], "5");
+
+            let data = req.body_mut().next().await.unwrap().unwrap();
+            assert_eq!(&*data, b"Hello");
+
+            if req.uri() == &*format!("/{}", code) {
+                http::Response::builder()
+                    .status(code)
+                    .header("location", "/dst")
+                    .body(Default::default())
+                    .unwrap()
+            } else {
+                assert_eq!(req.uri(), "/dst");
+                http::Response::default()
+            }
+        });
+
+        let url = format!("http://{}/{}", redirect.addr(), code);
+        let dst = format!("http://{}/{}", redirect.addr(), "dst");
+        let res = client.post(&url).body("Hello").send().await.unwrap();
+        assert_eq!(res.url().as_str(), dst);
+        assert_eq!(res.status(), reqwest::StatusCode::OK);
+    }
+}
+
 #[cfg(feature = "blocking")]
 #[test]
 fn test_redirect_307_does_not_try_if_reader_cannot_reset() {
@@ -265,6 +300,35 @@ async fn test_referer_is_not_set_if_disabled() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_referer_trim_query() {
+    let server = server::http(move |req| async move {
+        if req.uri().path() == "/src" {
+            http::Response::builder()
+                .status(302)
+                .header("location", "/dst")
+                .body(Default::default())
+                .unwrap()
+        } else {
+            assert_eq!(req.uri(), "/dst");
+            let referer = req.headers()["referer"].to_str().unwrap();
+            assert!(referer.ends_with("/src"), "referer: {}", referer);
+
+            http::Response::default()
+        }
+    });
+
+    let res = reqwest::Client::builder()
+        .referer_trim_query(true)
+        .build()
+        .unwrap()
+        .get(&format!("http://{}/src?token=secret", server.addr()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+}
+
 #[tokio::test]
 async fn test_invalid_location_stops_redirect_gh484() {
     let server = server::http(move |_req| async move {
@@ -314,3 +378,41 @@ async fn test_redirect_302_with_set_cookies() {
     assert_eq!(res.url().as_str(), dst);
     assert_eq!(res.status(), reqwest::StatusCode::OK);
 }
+
+#[tokio::test]
+async fn test_redirect_preserves_fragment() {
+    let server = server::http(move |req| async move {
+        match req.uri().path() {
+            "/inherit" => http::Response::builder()
+                .status(302)
+                .header("location", "/dst")
+                .body(Default::default())
+                .unwrap(),
+            "/own" => http::Response::builder()
+                .status(302)
+                .header("location", "/dst#theirs")
+                .body(Default::default())
+                .unwrap(),
+            _ => {
+                assert_eq!(req.uri(), "/dst");
+                http::Response::default()
+            }
+        }
+    });
+
+    let client = reqwest::Client::new();
+
+    let url = format!("http://{}/inherit#mine", server.addr());
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(
+        res.url().as_str(),
+        format!("http://{}/dst#mine", server.addr())
+    );
+
+    let url = format!("http://{}/own#mine", server.addr());
+    let res = client.get(&url).send().await.unwrap();
+    assert_eq!(
+        res.url().as_str(),
+        format!("http://{}/dst#theirs", server.addr())
+    );
+}
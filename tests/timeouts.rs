@@ -27,9 +27,38 @@ async fn client_timeout() {
     let err = res.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Overall));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
+#[tokio::test]
+async fn timeout_while_waiting_for_connection() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::new("hello".into()) });
+
+    // The only request allowed to the host holds on to its slot until its
+    // body is read, so the next one is queued.
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(500))
+        .concurrency_limit_per_host(1)
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/queued", server.addr());
+
+    let first = client.get(&url).send().await.unwrap();
+    let res = client.get(&url).send().await;
+
+    let err = res.unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Overall));
+    assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
+
+    drop(first);
+}
+
 #[tokio::test]
 async fn request_timeout() {
     let _ = env_logger::try_init();
@@ -55,6 +84,7 @@ async fn request_timeout() {
     let err = res.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Overall));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
@@ -87,6 +117,42 @@ async fn response_timeout() {
     let err = body.unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Body));
+}
+
+#[tokio::test]
+async fn response_bytes_timeout() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| {
+        async {
+            // immediate response, but delayed body
+            let body = hyper::Body::wrap_stream(futures_util::stream::once(async {
+                tokio::time::delay_for(Duration::from_secs(2)).await;
+                Ok::<_, std::convert::Infallible>("Hello")
+            }));
+
+            http::Response::new(body)
+        }
+    });
+
+    // The client's timeout is generous, the body's deadline isn't.
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .no_proxy()
+        .build()
+        .unwrap();
+
+    let url = format!("http://{}/slow", server.addr());
+    let res = client.get(&url).send().await.expect("Failed to get");
+    let err = res
+        .bytes_timeout(Duration::from_millis(500))
+        .await
+        .unwrap_err();
+
+    assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Body));
+    assert_eq!(err.url().map(|u| u.as_str()), Some(&url[..]));
 }
 
 /// Tests that internal client future cancels when the oneshot channel
@@ -115,6 +181,7 @@ fn timeout_closes_connection() {
     let err = client.get(&url).send().unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Overall));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
@@ -143,6 +210,7 @@ fn timeout_blocking_request() {
         .unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Overall));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
 
@@ -177,5 +245,87 @@ fn write_timeout_large_body() {
         .unwrap_err();
 
     assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Overall));
     assert_eq!(err.url().map(|u| u.as_str()), Some(url.as_str()));
 }
+
+#[cfg(feature = "__tls")]
+#[tokio::test]
+async fn request_connect_timeout() {
+    let _ = env_logger::try_init();
+
+    // A proxy that is never accepted from, so the CONNECT tunnel to it
+    // never completes.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy = format!("http://{}", listener.local_addr().unwrap());
+
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::https(&proxy).unwrap())
+        .connect_timeout(Duration::from_secs(60))
+        .build()
+        .unwrap();
+
+    let res = tokio::time::timeout(
+        Duration::from_secs(5),
+        client
+            .get("https://hyper.rs.local/connect")
+            .connect_timeout(Duration::from_millis(100))
+            .send(),
+    )
+    .await
+    .expect("request connect_timeout should override the client's");
+
+    let err = res.unwrap_err();
+    assert!(err.is_timeout());
+    assert_eq!(err.timeout_kind(), Some(reqwest::TimeoutKind::Connect));
+
+    // The override ended with the request, later ones use the client's.
+    let res = tokio::time::timeout(
+        Duration::from_millis(500),
+        client.get("https://hyper.rs.local/connect").send(),
+    )
+    .await;
+    assert!(res.is_err(), "client connect_timeout should apply again");
+}
+
+#[tokio::test]
+async fn request_connect_timeout_reuses_pooled_connection() {
+    use std::io::{Read, Write};
+
+    let _ = env_logger::try_init();
+
+    // Serves two requests on the only connection it accepts.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        for _ in 0..2 {
+            let mut req = Vec::new();
+            let mut byte = [0];
+            while !req.ends_with(b"\r\n\r\n") {
+                sock.read_exact(&mut byte).unwrap();
+                req.push(byte[0]);
+            }
+            sock.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+        }
+    });
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/", addr);
+    client.get(&url).send().await.expect("first request");
+
+    let res = tokio::time::timeout(
+        Duration::from_secs(5),
+        client
+            .get(&url)
+            .connect_timeout(Duration::from_secs(1))
+            .send(),
+    )
+    .await
+    .expect("pooled connection should be reused")
+    .expect("second request");
+    assert_eq!(res.status(), reqwest::StatusCode::OK);
+
+    server.join().unwrap();
+}
@@ -0,0 +1,67 @@
+mod support;
+use support::*;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn connect_tunnel() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |req| async move {
+        assert_eq!(req.method(), "CONNECT");
+        assert_eq!(req.uri(), "tunnel.example:22");
+
+        tokio::spawn(async move {
+            let mut upgraded = req.into_body().on_upgrade().await.unwrap();
+            let mut buf = [0; 4];
+            upgraded.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"ping");
+            upgraded.write_all(b"pong").await.unwrap();
+        });
+
+        http::Response::default()
+    });
+
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::http(&format!("http://{}", server.addr())).unwrap())
+        .build()
+        .unwrap();
+
+    let mut tunnel = client.connect("tunnel.example:22").await.unwrap();
+    tunnel.write_all(b"ping").await.unwrap();
+    let mut buf = [0; 4];
+    tunnel.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"pong");
+}
+
+#[tokio::test]
+async fn connect_refused() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async {
+        http::Response::builder()
+            .status(403)
+            .body(Default::default())
+            .unwrap()
+    });
+
+    let client = reqwest::Client::builder()
+        .proxy(reqwest::Proxy::http(&format!("http://{}", server.addr())).unwrap())
+        .build()
+        .unwrap();
+
+    let err = client.connect("tunnel.example:22").await.unwrap_err();
+    assert_eq!(err.status(), Some(reqwest::StatusCode::FORBIDDEN));
+}
+
+#[tokio::test]
+async fn response_upgrade_not_upgraded() {
+    let _ = env_logger::try_init();
+
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let res = reqwest::get(&format!("http://{}/", server.addr()))
+        .await
+        .unwrap();
+    assert!(res.upgrade().await.is_err());
+}
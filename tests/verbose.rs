@@ -0,0 +1,63 @@
+mod support;
+use support::*;
+
+use std::sync::Mutex;
+
+// The logger is global, so this file holds the tests that assert on log
+// output, rather than `env_logger` being installed.
+struct Capture;
+
+lazy_static::lazy_static! {
+    static ref RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+}
+
+impl log::Log for Capture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().starts_with("reqwest")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture;
+
+fn captured(f: impl Fn(&str) -> bool) -> bool {
+    RECORDS.lock().unwrap().iter().any(|record| f(record))
+}
+
+#[tokio::test]
+async fn connection_verbose_logs() {
+    log::set_logger(&CAPTURE).expect("set logger");
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let server = server::http(move |_req| async { http::Response::default() });
+
+    let res = reqwest::Client::new()
+        .get(&format!("http://{}/quiet", server.addr()))
+        .send()
+        .await
+        .expect("request");
+    assert_eq!(res.version(), reqwest::Version::HTTP_11);
+    assert!(!captured(|r| r.starts_with("negotiated")));
+    let written = "write: b\"GET /quiet HTTP/1.1\\r\\n";
+    assert!(!captured(|r| r.contains(written)));
+
+    let client = reqwest::Client::builder()
+        .connection_verbose(true)
+        .build()
+        .expect("client builder");
+
+    let url = format!("http://{}/version", server.addr());
+    let res = client.get(&url).send().await.expect("request");
+    assert_eq!(res.version(), reqwest::Version::HTTP_11);
+    assert!(captured(|r| r == format!("negotiated HTTP/1.1 for {}", url)));
+    let written = "write: b\"GET /version HTTP/1.1\\r\\n";
+    assert!(captured(|r| r.contains(written)));
+    assert!(captured(|r| r.contains("read: b\"HTTP/1.1 200 OK\\r\\n")));
+}